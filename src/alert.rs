@@ -0,0 +1,450 @@
+// Alert module - Threshold rules evaluated on every snapshot
+// Rules like "cpu.global > 90 for 30s" fire and resolve with hysteresis
+
+use crate::display::formatter::parse_duration;
+use crate::monitor::filter::Comparison;
+use crate::monitor::refresh::Subsystem;
+use crate::monitor::snapshot::Snapshot;
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// How far below (or above, for `<` rules) the threshold a value has to get
+/// before a firing alert resolves, as a fraction of the threshold
+const DEFAULT_HYSTERESIS: f64 = 0.05;
+
+/// A value an alert rule watches
+#[derive(Debug, Clone, PartialEq)]
+pub enum Metric {
+    /// Global CPU usage in percent (`cpu`, `cpu.global`)
+    CpuGlobal,
+    /// Usage of one core in percent (`cpu[3]`)
+    CpuCore(usize),
+    /// Memory usage in percent (`mem`, `memory`)
+    Memory,
+    /// Usage of one mount point in percent (`disk[/var]`), or of the fullest
+    /// disk (`disk`)
+    Disk(Option<String>),
+    /// Download speed in bytes per second (`net.rx`)
+    NetRx,
+    /// Upload speed in bytes per second (`net.tx`)
+    NetTx,
+}
+
+impl Metric {
+    /// Current value in a snapshot (None if the core or disk is missing)
+    pub fn value(&self, snapshot: &Snapshot) -> Option<f64> {
+        match self {
+            Metric::CpuGlobal => Some(snapshot.cpu.global_usage as f64),
+            Metric::CpuCore(index) => snapshot
+                .cpu
+                .cores
+                .iter()
+                .find(|core| core.index == *index)
+                .map(|core| core.usage as f64),
+            Metric::Memory => Some(snapshot.memory.percentage),
+            Metric::Disk(Some(mount)) => snapshot
+                .disks
+                .iter()
+                .find(|disk| disk.mount_point == *mount)
+                .map(|disk| disk.percentage),
+            Metric::Disk(None) => snapshot
+                .disks
+                .iter()
+                .map(|disk| disk.percentage)
+                .max_by(f64::total_cmp),
+            Metric::NetRx => Some(snapshot.network.selected_download_speed),
+            Metric::NetTx => Some(snapshot.network.selected_upload_speed),
+        }
+    }
+
+    /// Subsystem the value is collected by
+    pub fn subsystem(&self) -> Subsystem {
+        match self {
+            Metric::CpuGlobal | Metric::CpuCore(_) => Subsystem::Cpu,
+            Metric::Memory => Subsystem::Memory,
+            Metric::Disk(_) => Subsystem::Disks,
+            Metric::NetRx | Metric::NetTx => Subsystem::Network,
+        }
+    }
+
+    /// Whether the metric is a rate in bytes per second (otherwise a percentage)
+    pub fn is_rate(&self) -> bool {
+        matches!(self, Metric::NetRx | Metric::NetTx)
+    }
+
+    /// Parse a value for this metric: a percentage ("90", "90%") or a rate
+    /// ("10MB", "512KB/s")
+    fn parse_value(&self, value: &str) -> Result<f64, String> {
+        if !self.is_rate() {
+            return value
+                .trim_end_matches('%')
+                .parse()
+                .map_err(|_| format!("invalid percentage '{}'", value));
+        }
+
+        let value = value.trim_end_matches("/s");
+        let split = value
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(value.len());
+        let (number, unit) = value.split_at(split);
+        let number: f64 = number
+            .parse()
+            .map_err(|_| format!("invalid rate '{}'", value))?;
+        let factor = match unit.to_ascii_uppercase().as_str() {
+            "" | "B" => 1.0,
+            "KB" | "K" => 1024.0,
+            "MB" | "M" => 1024.0 * 1024.0,
+            "GB" | "G" => 1024.0 * 1024.0 * 1024.0,
+            _ => {
+                return Err(format!(
+                    "unknown rate unit '{}' (use B, KB, MB or GB)",
+                    unit
+                ))
+            }
+        };
+        Ok(number * factor)
+    }
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Metric::CpuGlobal => write!(f, "cpu.global"),
+            Metric::CpuCore(index) => write!(f, "cpu[{}]", index),
+            Metric::Memory => write!(f, "mem"),
+            Metric::Disk(Some(mount)) => write!(f, "disk[{}]", mount),
+            Metric::Disk(None) => write!(f, "disk"),
+            Metric::NetRx => write!(f, "net.rx"),
+            Metric::NetTx => write!(f, "net.tx"),
+        }
+    }
+}
+
+impl FromStr for Metric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        // Names are case-insensitive, mount points are not
+        let (name, selector) = match trimmed.split_once('[') {
+            Some((name, rest)) => {
+                let selector = rest
+                    .strip_suffix(']')
+                    .ok_or_else(|| format!("missing ']' in '{}'", trimmed))?;
+                (name.trim().to_ascii_lowercase(), Some(selector))
+            }
+            None => (trimmed.to_ascii_lowercase(), None),
+        };
+
+        match (name.as_str(), selector) {
+            ("cpu" | "cpu.global", None) => Ok(Metric::CpuGlobal),
+            ("cpu", Some(index)) => index
+                .parse()
+                .map(Metric::CpuCore)
+                .map_err(|_| format!("invalid core number '{}'", index)),
+            ("mem" | "memory", None) => Ok(Metric::Memory),
+            ("disk", mount) => Ok(Metric::Disk(mount.map(str::to_string))),
+            ("net.rx", None) => Ok(Metric::NetRx),
+            ("net.tx", None) => Ok(Metric::NetTx),
+            _ => Err(format!(
+                "unknown metric '{}' (expected cpu, cpu.global, cpu[N], mem, disk, disk[MOUNT], net.rx or net.tx)",
+                trimmed
+            )),
+        }
+    }
+}
+
+/// A threshold rule such as `cpu.global > 90 for 30s` or `disk[/var] > 85`
+///
+/// Syntax: `METRIC OP VALUE [for DURATION] [clear VALUE]` with `OP` one of
+/// `>`, `>=`, `<`, `<=`. The rule fires once the condition has held for
+/// `DURATION` (immediately without `for`) and resolves when the value gets
+/// past the `clear` value, which defaults to 5% of the threshold on the safe
+/// side (e.g. 85.5 for `> 90`).
+#[derive(Debug, Clone)]
+pub struct Rule {
+    text: String,
+    /// Watched value
+    pub metric: Metric,
+    comparison: Comparison,
+    /// Value at which the rule fires
+    pub threshold: f64,
+    /// Value at which a firing rule resolves
+    pub clear: f64,
+    /// How long the condition must hold before firing
+    pub hold: Duration,
+}
+
+impl Rule {
+    /// Whether a value breaks the rule
+    pub fn breached(&self, value: f64) -> bool {
+        self.comparison.holds(value, self.threshold)
+    }
+
+    /// Whether a value is back past the clear level
+    pub fn cleared(&self, value: f64) -> bool {
+        match self.comparison {
+            Comparison::Greater | Comparison::GreaterOrEqual => value <= self.clear,
+            _ => value >= self.clear,
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    /// The rule as it was written
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+impl Serialize for Rule {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.text)
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim();
+        let op_start = text
+            .find(['>', '<'])
+            .ok_or_else(|| format!("missing comparison (>, >=, < or <=) in '{}'", text))?;
+
+        let metric: Metric = text[..op_start].parse()?;
+        let rest = &text[op_start..];
+        let (comparison, rest) = [
+            (">=", Comparison::GreaterOrEqual),
+            ("<=", Comparison::LessOrEqual),
+            (">", Comparison::Greater),
+            ("<", Comparison::Less),
+        ]
+        .into_iter()
+        .find_map(|(op, comparison)| rest.strip_prefix(op).map(|rest| (comparison, rest)))
+        .expect("rule contains an operator");
+
+        let mut words = rest.split_whitespace();
+        let threshold = metric.parse_value(
+            words
+                .next()
+                .ok_or_else(|| format!("missing value in '{}'", text))?,
+        )?;
+
+        let mut hold = Duration::ZERO;
+        let mut clear = None;
+        while let Some(word) = words.next() {
+            let mut argument = || {
+                words
+                    .next()
+                    .ok_or_else(|| format!("missing value after '{}'", word))
+            };
+            match word.to_ascii_lowercase().as_str() {
+                "for" => hold = parse_duration(argument()?)?,
+                "clear" => clear = Some(metric.parse_value(argument()?)?),
+                _ => return Err(format!("unexpected '{}' (expected 'for' or 'clear')", word)),
+            }
+        }
+
+        let rises = matches!(comparison, Comparison::Greater | Comparison::GreaterOrEqual);
+        let margin = threshold.abs() * DEFAULT_HYSTERESIS;
+        let clear = clear.unwrap_or(if rises {
+            threshold - margin
+        } else {
+            threshold + margin
+        });
+        if (rises && clear > threshold) || (!rises && clear < threshold) {
+            return Err(format!(
+                "clear value {} is on the wrong side of the threshold {}",
+                clear, threshold
+            ));
+        }
+
+        Ok(Rule {
+            text: text.to_string(),
+            metric,
+            comparison,
+            threshold,
+            clear,
+            hold,
+        })
+    }
+}
+
+/// Whether an alert started or ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertState {
+    /// The rule's condition has held long enough
+    Firing,
+    /// The value went back past the clear level
+    Resolved,
+}
+
+impl fmt::Display for AlertState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AlertState::Firing => write!(f, "firing"),
+            AlertState::Resolved => write!(f, "resolved"),
+        }
+    }
+}
+
+/// An alert firing or resolving
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AlertEvent {
+    /// The rule as it was written
+    pub rule: String,
+    /// Watched metric (e.g. "disk[/var]")
+    pub metric: String,
+    /// New state
+    pub state: AlertState,
+    /// Value that triggered the change
+    pub value: f64,
+    /// Threshold of the rule
+    pub threshold: f64,
+    /// Snapshot time in milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+}
+
+/// Per-rule progress between snapshots
+#[derive(Debug, Clone, Default)]
+struct RuleState {
+    /// When the condition started holding
+    breached_since: Option<u64>,
+    firing: bool,
+}
+
+/// Evaluates alert rules against consecutive snapshots
+#[derive(Debug, Clone)]
+pub struct AlertEngine {
+    rules: Vec<(Rule, RuleState)>,
+}
+
+impl AlertEngine {
+    /// Create an engine with every rule resolved
+    pub fn new(rules: Vec<Rule>) -> Self {
+        AlertEngine {
+            rules: rules
+                .into_iter()
+                .map(|rule| (rule, RuleState::default()))
+                .collect(),
+        }
+    }
+
+    /// Evaluate every rule against a new snapshot
+    ///
+    /// Returns the alerts that fired or resolved with this snapshot. Rules
+    /// whose metric is missing (e.g. an unmounted disk) keep their state.
+    pub fn evaluate(&mut self, snapshot: &Snapshot) -> Vec<AlertEvent> {
+        let now = snapshot.timestamp_ms;
+        let mut events = Vec::new();
+
+        for (rule, state) in &mut self.rules {
+            let Some(value) = rule.metric.value(snapshot) else {
+                continue;
+            };
+
+            let changed = if state.firing {
+                if rule.cleared(value) {
+                    *state = RuleState::default();
+                    Some(AlertState::Resolved)
+                } else {
+                    None
+                }
+            } else if rule.breached(value) {
+                let since = *state.breached_since.get_or_insert(now);
+                if now.saturating_sub(since) >= rule.hold.as_millis() as u64 {
+                    state.firing = true;
+                    Some(AlertState::Firing)
+                } else {
+                    None
+                }
+            } else {
+                state.breached_since = None;
+                None
+            };
+
+            if let Some(new_state) = changed {
+                events.push(AlertEvent {
+                    rule: rule.to_string(),
+                    metric: rule.metric.to_string(),
+                    state: new_state,
+                    value,
+                    threshold: rule.threshold,
+                    timestamp_ms: now,
+                });
+            }
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::snapshot::{sample_snapshot, sample_snapshot_at as at};
+
+    #[test]
+    fn test_parse_rules() {
+        let rule: Rule = "cpu.global > 90 for 30s".parse().unwrap();
+        assert_eq!(rule.metric, Metric::CpuGlobal);
+        assert_eq!(rule.hold, Duration::from_secs(30));
+        assert_eq!(rule.clear, 85.5);
+
+        let rule: Rule = "disk[/var/Log]>=85% clear 80".parse().unwrap();
+        assert_eq!(rule.metric, Metric::Disk(Some("/var/Log".to_string())));
+        assert_eq!(rule.clear, 80.0);
+
+        let rule: Rule = "net.rx < 1KB".parse().unwrap();
+        assert_eq!(rule.threshold, 1024.0);
+        assert!(rule.breached(10.0) && !rule.cleared(1024.0));
+
+        assert_eq!("mem > 95".parse::<Rule>().unwrap().to_string(), "mem > 95");
+        assert!("gpu > 5".parse::<Rule>().is_err());
+        assert!("cpu = 5".parse::<Rule>().is_err());
+        assert!("cpu > 90 for".parse::<Rule>().is_err());
+        assert!("cpu > 90 clear 95".parse::<Rule>().is_err());
+        assert!("cpu > 90 every 5s".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn test_metric_values() {
+        let snapshot = sample_snapshot();
+        let value = |metric: &str| metric.parse::<Metric>().unwrap().value(&snapshot);
+
+        assert_eq!(value("cpu"), Some(25.0));
+        assert_eq!(value("cpu[1]"), Some(30.0));
+        assert_eq!(value("cpu[7]"), None);
+        assert_eq!(value("memory"), Some(50.0));
+        assert_eq!(value("disk[/]"), Some(60.0));
+        assert_eq!(value("disk"), Some(60.0));
+        assert_eq!(value("disk[/mnt]"), None);
+        assert_eq!(value("net.rx"), Some(1024.0));
+    }
+
+    #[test]
+    fn test_fires_after_hold_and_resolves_with_hysteresis() {
+        let rule: Rule = "cpu > 90 for 30s".parse().unwrap();
+        let mut engine = AlertEngine::new(vec![rule]);
+
+        assert!(engine.evaluate(&at(0, 95.0)).is_empty());
+        // A dip below the threshold restarts the hold time
+        assert!(engine.evaluate(&at(20, 80.0)).is_empty());
+        assert!(engine.evaluate(&at(30, 95.0)).is_empty());
+        let events = engine.evaluate(&at(60, 96.0));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].state, AlertState::Firing);
+        assert_eq!(events[0].value, 96.0);
+
+        // Still firing: no repeated events, and 88% is within the hysteresis
+        assert!(engine.evaluate(&at(61, 97.0)).is_empty());
+        assert!(engine.evaluate(&at(62, 88.0)).is_empty());
+        let events = engine.evaluate(&at(63, 85.0));
+        assert_eq!(events[0].state, AlertState::Resolved);
+        assert_eq!(events[0].metric, "cpu.global");
+    }
+}
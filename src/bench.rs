@@ -0,0 +1,117 @@
+// Bench module - Collector timings
+// Reports how long each collector took on every tick (`--bench`)
+
+use std::time::Duration;
+use system_monitor::monitor::refresh::CollectorTiming;
+
+/// Name of the step that builds the snapshot from the refreshed data
+const SNAPSHOT: &str = "snapshot";
+
+/// Runs, total and slowest time of one collector
+#[derive(Debug, Default)]
+struct Stats {
+    runs: u32,
+    total: Duration,
+    max: Duration,
+}
+
+/// Collector timings of a whole run
+#[derive(Debug, Default)]
+pub struct Bench {
+    /// Collectors in the order they first ran
+    stats: Vec<(String, Stats)>,
+}
+
+impl Bench {
+    /// Add the timings of one tick and describe them on one line
+    ///
+    /// # Arguments
+    /// * `timings` - Collectors that ran during the refresh
+    /// * `snapshot` - Time spent building the snapshot
+    pub fn record(&mut self, timings: &[CollectorTiming], snapshot: Duration) -> String {
+        let steps: Vec<(String, Duration)> = timings
+            .iter()
+            .map(|timing| (timing.subsystem.to_string(), timing.duration))
+            .chain(std::iter::once((SNAPSHOT.to_string(), snapshot)))
+            .collect();
+
+        for (name, duration) in &steps {
+            let stats = match self.stats.iter().position(|(known, _)| known == name) {
+                Some(index) => &mut self.stats[index].1,
+                None => {
+                    self.stats.push((name.clone(), Stats::default()));
+                    &mut self.stats.last_mut().expect("just pushed").1
+                }
+            };
+            stats.runs += 1;
+            stats.total += *duration;
+            stats.max = stats.max.max(*duration);
+        }
+
+        let total: Duration = steps.iter().map(|(_, duration)| *duration).sum();
+        let parts: Vec<String> = steps
+            .iter()
+            .map(|(name, duration)| format!("{} {}", name, millis(*duration)))
+            .collect();
+        format!("bench: {}  (total {})", parts.join(", "), millis(total))
+    }
+
+    /// Runs, average and slowest time of every collector
+    pub fn summary(&self) -> String {
+        let mut text = format!(
+            "{:<10} {:>5} {:>10} {:>10}\n",
+            "collector", "runs", "avg", "max"
+        );
+        for (name, stats) in &self.stats {
+            text.push_str(&format!(
+                "{:<10} {:>5} {:>10} {:>10}\n",
+                name,
+                stats.runs,
+                millis(stats.total / stats.runs.max(1)),
+                millis(stats.max)
+            ));
+        }
+        text
+    }
+}
+
+/// Format a duration in milliseconds, e.g. "12.34ms"
+fn millis(duration: Duration) -> String {
+    format!("{:.2}ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use system_monitor::monitor::refresh::Subsystem;
+
+    #[test]
+    fn test_record_and_summary() {
+        let ms = Duration::from_millis;
+        let mut bench = Bench::default();
+
+        let tick = [
+            CollectorTiming {
+                subsystem: Subsystem::Cpu,
+                duration: ms(1),
+            },
+            CollectorTiming {
+                subsystem: Subsystem::Processes,
+                duration: ms(40),
+            },
+        ];
+        assert_eq!(
+            bench.record(&tick, ms(2)),
+            "bench: cpu 1.00ms, processes 40.00ms, snapshot 2.00ms  (total 43.00ms)"
+        );
+        // Processes are not due on the next tick
+        bench.record(&tick[..1], ms(4));
+
+        let summary = bench.summary();
+        let lines: Vec<&str> = summary.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].starts_with("cpu") && lines[1].contains("    2 "));
+        assert!(lines[2].starts_with("processes") && lines[2].contains("40.00ms"));
+        assert!(lines[3].starts_with("snapshot") && lines[3].ends_with("3.00ms     4.00ms"));
+    }
+}
@@ -0,0 +1,286 @@
+// Check module - Monitoring plugin
+// One measurement, one status line with perfdata, exit code 0-3 (`sysmon check`)
+
+use crate::cli::CheckArgs;
+use std::fmt;
+use std::thread;
+use system_monitor::{CpuInfo, DiskInfo, MemoryInfo, SystemMonitor};
+
+/// Plugin status, in order of severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Status {
+    /// Every value within its thresholds
+    Ok,
+    /// A value above its warning threshold
+    Warning,
+    /// A value above its critical threshold
+    Critical,
+    /// Invalid arguments or nothing to measure
+    Unknown,
+}
+
+impl Status {
+    /// Exit code expected by Nagios and Icinga
+    pub fn code(self) -> i32 {
+        match self {
+            Status::Ok => 0,
+            Status::Warning => 1,
+            Status::Critical => 2,
+            Status::Unknown => 3,
+        }
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Status::Ok => "OK",
+            Status::Warning => "WARNING",
+            Status::Critical => "CRITICAL",
+            Status::Unknown => "UNKNOWN",
+        })
+    }
+}
+
+/// Warning and critical threshold of one metric
+#[derive(Debug, Clone, Copy, Default)]
+struct Limits {
+    warn: Option<f64>,
+    crit: Option<f64>,
+}
+
+impl Limits {
+    fn new(name: &str, warn: Option<f64>, crit: Option<f64>) -> Result<Self, String> {
+        if let (Some(w), Some(c)) = (warn, crit) {
+            if w > c {
+                return Err(format!(
+                    "--{0}-warn ({1}) is above --{0}-crit ({2})",
+                    name, w, c
+                ));
+            }
+        }
+        Ok(Limits { warn, crit })
+    }
+
+    fn status(&self, value: f64) -> Status {
+        if self.crit.is_some_and(|crit| value > crit) {
+            Status::Critical
+        } else if self.warn.is_some_and(|warn| value > warn) {
+            Status::Warning
+        } else {
+            Status::Ok
+        }
+    }
+}
+
+/// One checked value in percent
+#[derive(Debug, Clone)]
+struct Measurement {
+    /// Name in the status text, e.g. "disk /var"
+    name: String,
+    /// Perfdata label, e.g. "/var"
+    label: String,
+    value: f64,
+    limits: Limits,
+}
+
+impl Measurement {
+    /// Perfdata entry: 'label'=value%;warn;crit;min;max
+    fn perfdata(&self) -> String {
+        let limit = |l: Option<f64>| l.map(|v| v.to_string()).unwrap_or_default();
+        format!(
+            "{}={:.1}%;{};{};0;100",
+            quote_label(&self.label),
+            self.value,
+            limit(self.limits.warn),
+            limit(self.limits.crit)
+        )
+    }
+}
+
+/// Quote a perfdata label if it contains spaces, quotes or '='
+fn quote_label(label: &str) -> String {
+    if label.contains([' ', '\'', '=']) {
+        format!("'{}'", label.replace('\'', "''"))
+    } else {
+        label.to_string()
+    }
+}
+
+/// Pick the measurements and thresholds to check
+fn measurements(
+    cpu: &CpuInfo,
+    memory: &MemoryInfo,
+    disks: &[DiskInfo],
+    args: &CheckArgs,
+) -> Result<Vec<Measurement>, String> {
+    let mut checked = vec![
+        Measurement {
+            name: "cpu".to_string(),
+            label: "cpu".to_string(),
+            value: cpu.global_usage as f64,
+            limits: Limits::new("cpu", args.cpu_warn, args.cpu_crit)?,
+        },
+        Measurement {
+            name: "mem".to_string(),
+            label: "mem".to_string(),
+            value: memory.percentage,
+            limits: Limits::new("mem", args.mem_warn, args.mem_crit)?,
+        },
+    ];
+
+    let disk_limits = Limits::new("disk", args.disk_warn, args.disk_crit)?;
+    for mount in &args.mounts {
+        if !disks.iter().any(|disk| &disk.mount_point == mount) {
+            return Err(format!("no disk mounted at {}", mount));
+        }
+    }
+    checked.extend(
+        disks
+            .iter()
+            .filter(|disk| args.mounts.is_empty() || args.mounts.contains(&disk.mount_point))
+            .map(|disk| Measurement {
+                name: format!("disk {}", disk.mount_point),
+                label: disk.mount_point.clone(),
+                value: disk.percentage,
+                limits: disk_limits,
+            }),
+    );
+
+    Ok(checked)
+}
+
+/// Overall status and the plugin output line
+fn report(checked: &[Measurement]) -> (Status, String) {
+    let status = checked
+        .iter()
+        .map(|m| m.limits.status(m.value))
+        .max()
+        .unwrap_or(Status::Ok);
+
+    let text: Vec<String> = checked
+        .iter()
+        .map(|m| match m.limits.status(m.value) {
+            Status::Ok => format!("{} {:.1}%", m.name, m.value),
+            level => format!("{} {:.1}% ({})", m.name, m.value, level),
+        })
+        .collect();
+    let perfdata: Vec<String> = checked.iter().map(Measurement::perfdata).collect();
+
+    (
+        status,
+        format!(
+            "SYSMON {} - {} | {}",
+            status,
+            text.join(", "),
+            perfdata.join(" ")
+        ),
+    )
+}
+
+/// Take one measurement and print the plugin output
+///
+/// Returns the plugin exit code (see [`Status::code`]).
+///
+/// # Arguments
+/// * `monitor` - System monitor instance
+/// * `args` - Thresholds and disks to check
+pub fn run(monitor: &mut SystemMonitor, args: &CheckArgs) -> i32 {
+    // CPU usage is measured between two refreshes
+    thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+    monitor.refresh();
+
+    let disks = monitor.disks_info();
+    match measurements(&monitor.cpu_info(), &monitor.memory_info(), &disks, args) {
+        Ok(checked) => {
+            let (status, line) = report(&checked);
+            println!("{}", line);
+            status.code()
+        }
+        Err(e) => {
+            println!("SYSMON {} - {}", Status::Unknown, e);
+            Status::Unknown.code()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn disk(mount_point: &str, percentage: f64) -> DiskInfo {
+        DiskInfo {
+            name: "sda1".to_string(),
+            mount_point: mount_point.to_string(),
+            total_gb: 100.0,
+            available_gb: 100.0 - percentage,
+            used_gb: percentage,
+            percentage,
+        }
+    }
+
+    fn check(args: &CheckArgs) -> Result<(Status, String), String> {
+        let cpu = CpuInfo {
+            global_usage: 42.0,
+            cores: Vec::new(),
+        };
+        let memory = MemoryInfo {
+            total_gb: 16.0,
+            used_gb: 8.0,
+            percentage: 50.0,
+        };
+        let disks = [disk("/", 70.0), disk("/mnt/my disk", 96.5)];
+        measurements(&cpu, &memory, &disks, args).map(|checked| report(&checked))
+    }
+
+    #[test]
+    fn test_report() {
+        let args = CheckArgs {
+            cpu_warn: Some(80.0),
+            cpu_crit: Some(95.0),
+            disk_warn: Some(85.0),
+            disk_crit: Some(95.0),
+            ..CheckArgs::default()
+        };
+        let (status, line) = check(&args).unwrap();
+        assert_eq!(status, Status::Critical);
+        assert_eq!(
+            line,
+            "SYSMON CRITICAL - cpu 42.0%, mem 50.0%, disk / 70.0%, disk /mnt/my disk 96.5% (CRITICAL) \
+             | cpu=42.0%;80;95;0;100 mem=50.0%;;;0;100 /=70.0%;85;95;0;100 '/mnt/my disk'=96.5%;85;95;0;100"
+        );
+
+        let args = CheckArgs {
+            mem_warn: Some(50.0),
+            disk_warn: Some(60.0),
+            mounts: vec!["/".to_string()],
+            ..CheckArgs::default()
+        };
+        let (status, line) = check(&args).unwrap();
+        // Only values above the threshold count
+        assert_eq!(status, Status::Warning);
+        assert!(line.starts_with("SYSMON WARNING - cpu 42.0%, mem 50.0%, disk / 70.0% (WARNING) |"));
+
+        assert_eq!(check(&CheckArgs::default()).unwrap().0, Status::Ok);
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        let args = CheckArgs {
+            cpu_warn: Some(90.0),
+            cpu_crit: Some(80.0),
+            ..CheckArgs::default()
+        };
+        assert_eq!(
+            check(&args).unwrap_err(),
+            "--cpu-warn (90) is above --cpu-crit (80)"
+        );
+
+        let args = CheckArgs {
+            mounts: vec!["/var".to_string()],
+            ..CheckArgs::default()
+        };
+        assert_eq!(check(&args).unwrap_err(), "no disk mounted at /var");
+        assert_eq!(Status::Unknown.code(), 3);
+    }
+}
@@ -1,7 +1,18 @@
 // CLI argument parsing module
 // This module handles command-line argument parsing using clap
 
-use clap::Parser;
+use crate::tui::replay::{MAX_SPEED, MIN_SPEED};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+use system_monitor::alert::{Metric, Rule};
+use system_monitor::display::formatter::parse_duration;
+use system_monitor::monitor::filter::Filter;
+use system_monitor::monitor::refresh::Subsystem;
+use system_monitor::monitor::signal::Signal;
+use system_monitor::output::influx::InfluxTarget;
 
 /// System Monitor - A lightweight system monitoring tool
 #[derive(Parser, Debug)]
@@ -13,20 +24,371 @@ use clap::Parser;
     long_about = None
 )]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Enable watch mode (continuous updates)
     #[arg(short, long)]
     pub watch: bool,
 
-    /// Update interval in seconds (default: 1, only works with --watch)
-    #[arg(short, long, default_value_t = 1)]
-    pub interval: u64,
+    /// Only show processes matching an expression, e.g. "name~nginx && cpu>20"
+    ///
+    /// Terms: name~REGEX, name=NAME, pid=N, user=NAME, user~REGEX, cpu>PERCENT,
+    /// mem>SIZE (KB/MB/GB). Combine with &&/and, ||/or and parentheses.
+    #[arg(long, value_name = "EXPR")]
+    pub filter: Option<Filter>,
+
+    /// Only report these network interfaces (comma-separated, * and ?
+    /// wildcards), e.g. "eth*,wl*"
+    #[arg(
+        long = "iface",
+        value_name = "PATTERNS",
+        value_delimiter = ',',
+        global = true
+    )]
+    pub ifaces: Vec<String>,
+
+    /// Leave these network interfaces out (comma-separated), e.g. "docker*"
+    ///
+    /// Loopback is always left out unless --iface names it, e.g. --iface lo.
+    #[arg(
+        long = "exclude-iface",
+        value_name = "PATTERNS",
+        value_delimiter = ',',
+        global = true
+    )]
+    pub exclude_ifaces: Vec<String>,
+
+    /// Alert rule checked on every tick (repeatable), e.g. "cpu.global > 90 for 30s",
+    /// "disk[/var] > 85" or "mem > 95"
+    ///
+    /// Syntax: METRIC OP VALUE [for DURATION] [clear VALUE]. Metrics: cpu,
+    /// cpu[N], mem, disk, disk[MOUNT], net.rx, net.tx (bytes/s, units KB/MB/GB).
+    /// Alerts resolve once the value passes the clear value (default: 5% of
+    /// the threshold back).
+    #[arg(long = "alert", value_name = "RULE")]
+    pub alerts: Vec<Rule>,
+
+    /// Shell command run when an alert fires or resolves, with the details in
+    /// SYSMON_ALERT_* environment variables
+    #[arg(long, value_name = "CMD")]
+    pub alert_command: Option<String>,
+
+    /// Append fired and resolved alerts to this file
+    #[arg(long, value_name = "FILE")]
+    pub alert_log: Option<PathBuf>,
+
+    /// Record every tick to the metric history (see `sysmon history`)
+    #[arg(long)]
+    pub history: bool,
+
+    /// Directory of the metric history (default:
+    /// $XDG_DATA_HOME/sysmon/history or ~/.local/share/sysmon/history)
+    #[arg(long, value_name = "DIR", global = true)]
+    pub history_dir: Option<PathBuf>,
+
+    /// Show every process as a parent/child tree (also added to JSON output)
+    #[arg(long)]
+    pub tree: bool,
+
+    /// Interactive full-screen mode with keyboard controls
+    #[arg(long, conflicts_with = "watch")]
+    pub tui: bool,
+
+    /// Update interval in seconds (default: 1, only works with --watch, --tui or record)
+    #[arg(short, long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub interval: Option<u64>,
+
+    /// Refresh a subsystem at most this often (repeatable), e.g. processes=5s
+    ///
+    /// Subsystems: cpu, memory, disks, network, processes. Between two
+    /// refreshes the last values are shown. Default: every tick.
+    #[arg(long = "refresh", value_name = "SUBSYSTEM=DURATION", value_parser = parse_refresh)]
+    pub refresh: Vec<(Subsystem, Duration)>,
+
+    /// Report how long each collector took on every tick (on stderr)
+    #[arg(long, conflicts_with = "tui")]
+    pub bench: bool,
+
+    /// Number of samples drawn as sparklines in watch and interactive mode
+    /// (default: 30, 0 turns them off)
+    #[arg(long, value_name = "N")]
+    pub sparkline_length: Option<usize>,
 
     /// Show detailed information (all CPU cores, more processes)
     #[arg(short, long)]
     pub detailed: bool,
+
+    /// Output format (default: text for the colored view, others for scripts
+    /// and collectors)
+    #[arg(short, long, value_enum, global = true)]
+    pub format: Option<OutputFormat>,
+
+    /// Color theme: default, solarized, high-contrast, monochrome or a
+    /// [themes.NAME] table from the configuration file
+    #[arg(long, value_name = "NAME", global = true)]
+    pub theme: Option<String>,
+
+    /// Read settings from this file instead of /etc/sysmon/config.toml and
+    /// $XDG_CONFIG_HOME/sysmon/config.toml
+    #[arg(long, value_name = "FILE", global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Append machine-readable output to a file instead of stdout
+    /// (JSON is written as one record per line, CSV headers are checked)
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Send InfluxDB line protocol to udp://host:port or tcp://host:port
+    /// instead of stdout (only with --format influx)
+    #[arg(long, value_name = "URL")]
+    pub influx_addr: Option<InfluxTarget>,
+
+    /// StatsD server to push metrics to (only with --format statsd)
+    #[arg(long, value_name = "HOST:PORT", default_value = "127.0.0.1:8125")]
+    pub statsd_addr: String,
+
+    /// Prefix for StatsD metric names
+    #[arg(long, value_name = "PREFIX", default_value = "sysmon")]
+    pub statsd_prefix: String,
+
+    /// Send DogStatsD tags (core, mount_point) instead of name segments
+    #[arg(long)]
+    pub dogstatsd: bool,
+
+    /// Extra DogStatsD tag added to every metric (repeatable, e.g. env:prod)
+    #[arg(long = "statsd-tag", value_name = "KEY:VALUE", requires = "dogstatsd")]
+    pub statsd_tags: Vec<String>,
+}
+
+/// Subcommands (running without one shows the system overview)
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Serve metrics in the Prometheus text format over HTTP
+    Serve {
+        /// Address to listen on
+        #[arg(short, long, default_value = "127.0.0.1:9101")]
+        listen: SocketAddr,
+
+        /// Minimum seconds between two system refreshes, however often it is scraped
+        #[arg(long, value_name = "SECONDS", default_value_t = 1)]
+        min_refresh: u64,
+    },
+
+    /// Show everything known about one process (text or --format json)
+    Proc {
+        /// Process ID
+        pid: u32,
+    },
+
+    /// Send a signal to every process whose name contains a pattern
+    Kill {
+        /// Substring of the process name to match (not empty)
+        #[arg(long = "match", value_name = "PATTERN", value_parser = parse_pattern)]
+        pattern: String,
+
+        /// Signal to send (name like TERM or KILL, or a number)
+        #[arg(short, long, default_value = "TERM")]
+        signal: Signal,
+
+        /// Only list the matching processes
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Signal every match even when more than 10 processes match
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Take one measurement as a Nagios/Icinga plugin
+    ///
+    /// Prints one status line with perfdata and exits with 0 (OK), 1 (WARNING),
+    /// 2 (CRITICAL) or 3 (UNKNOWN). A value above a threshold changes the status.
+    Check(CheckArgs),
+
+    /// Show recorded metrics (see --history)
+    ///
+    /// Raw samples cover the recent past, one-minute averages the time before.
+    History {
+        /// Start of the time range, as a duration ago (e.g. 30m, 2h, 7d)
+        #[arg(long, value_name = "DURATION", default_value = "1h", value_parser = parse_duration)]
+        since: Duration,
+
+        /// End of the time range, as a duration ago (default: now)
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+        until: Option<Duration>,
+
+        /// Metric to show (repeatable): cpu, mem, disk (the fullest),
+        /// disk[MOUNT], net.rx or net.tx (default: all of them)
+        #[arg(long = "metric", value_name = "METRIC")]
+        metrics: Vec<Metric>,
+    },
+
+    /// Record every snapshot to a session file until Ctrl+C
+    ///
+    /// Uses the watch interval and honors --tree. Replay the file with
+    /// `sysmon replay`.
+    Record {
+        /// Session file to create (overwritten if it exists)
+        file: PathBuf,
+    },
+
+    /// Play a recorded session back in the terminal view
+    ///
+    /// Keys: [space] pause, [left/right] seek 10s, [,/.] previous/next
+    /// snapshot, [home/end] start/end, [+/-] speed, [q] quit.
+    Replay {
+        /// Session file written by `sysmon record`
+        file: PathBuf,
+
+        /// Playback speed between 1/16x and 256x, e.g. 4x, 0.5x or 2
+        #[arg(long, value_name = "FACTOR", default_value = "1x", value_parser = parse_speed)]
+        speed: f64,
+    },
+
+    /// Compare two saved JSON snapshots (text or --format json)
+    ///
+    /// Shows processes that appeared, disappeared or grew, disks that filled
+    /// up and the network traffic in between. Save the snapshots with --tree
+    /// to compare every process instead of the top lists.
+    Diff {
+        /// Earlier snapshot (`sysmon --format json`, or the last line of a stream)
+        before: PathBuf,
+
+        /// Later snapshot
+        after: PathBuf,
+    },
+
+    /// List the configuration files sysmon reads
+    Config {
+        /// Print the settings merged from the command line, files and defaults
+        #[arg(long)]
+        print_effective: bool,
+    },
+}
+
+/// Thresholds of `sysmon check` (metrics without thresholds are only reported)
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct CheckArgs {
+    /// Warn above this global CPU usage (percent)
+    #[arg(long, value_name = "PERCENT")]
+    pub cpu_warn: Option<f64>,
+
+    /// Critical above this global CPU usage (percent)
+    #[arg(long, value_name = "PERCENT")]
+    pub cpu_crit: Option<f64>,
+
+    /// Warn above this memory usage (percent)
+    #[arg(long, value_name = "PERCENT")]
+    pub mem_warn: Option<f64>,
+
+    /// Critical above this memory usage (percent)
+    #[arg(long, value_name = "PERCENT")]
+    pub mem_crit: Option<f64>,
+
+    /// Warn above this usage of any checked disk (percent)
+    #[arg(long, value_name = "PERCENT")]
+    pub disk_warn: Option<f64>,
+
+    /// Critical above this usage of any checked disk (percent)
+    #[arg(long, value_name = "PERCENT")]
+    pub disk_crit: Option<f64>,
+
+    /// Only check the disk mounted here (repeatable; default: every disk)
+    #[arg(long = "mount", value_name = "PATH")]
+    pub mounts: Vec<String>,
+}
+
+/// Available output formats
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Colored terminal view
+    Text,
+    /// JSON snapshot (versioned schema)
+    Json,
+    /// CSV rows with a stable column layout
+    Csv,
+    /// InfluxDB line protocol
+    Influx,
+    /// StatsD datagrams pushed over UDP
+    Statsd,
 }
 
 /// Parse command-line arguments
 pub fn parse_args() -> Args {
     Args::parse()
 }
+
+/// Parse a refresh interval like "processes=5s"
+pub fn parse_refresh(s: &str) -> Result<(Subsystem, Duration), String> {
+    let (subsystem, interval) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected SUBSYSTEM=DURATION, got '{}'", s))?;
+    Ok((subsystem.parse()?, parse_duration(interval)?))
+}
+
+/// Parse a process name pattern, rejecting empty and blank ones
+pub fn parse_pattern(s: &str) -> Result<String, String> {
+    if s.trim().is_empty() {
+        Err("the pattern must not be empty".to_string())
+    } else {
+        Ok(s.to_string())
+    }
+}
+
+/// Parse a playback speed like "4x", "0.5x" or "2", between 1/16x and 256x
+pub fn parse_speed(s: &str) -> Result<f64, String> {
+    let factor = s.trim().trim_end_matches(['x', 'X']);
+    match factor.parse::<f64>() {
+        Ok(speed) if (MIN_SPEED..=MAX_SPEED).contains(&speed) => Ok(speed),
+        Ok(speed) if speed.is_finite() && speed > 0.0 => Err(format!(
+            "speed '{}' out of range ({}x to {}x)",
+            s, MIN_SPEED, MAX_SPEED
+        )),
+        _ => Err(format!("invalid speed '{}' (e.g. 4x, 0.5x)", s)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_speed() {
+        assert_eq!(parse_speed("4x"), Ok(4.0));
+        assert_eq!(parse_speed("0.5x"), Ok(0.5));
+        assert_eq!(parse_speed("2"), Ok(2.0));
+        assert_eq!(parse_speed("256x"), Ok(256.0));
+        assert!(parse_speed("0x").is_err());
+        assert!(parse_speed("1e-20x").is_err());
+        assert!(parse_speed("1000x").is_err());
+        assert!(parse_speed("infx").is_err());
+        assert!(parse_speed("fast").is_err());
+    }
+
+    #[test]
+    fn test_interval_not_zero() {
+        assert!(Args::try_parse_from(["sysmon", "--watch", "--interval", "0"]).is_err());
+        let args = Args::try_parse_from(["sysmon", "--watch", "--interval", "5"]).unwrap();
+        assert_eq!(args.interval, Some(5));
+    }
+
+    #[test]
+    fn test_kill_pattern_not_empty() {
+        assert!(Args::try_parse_from(["sysmon", "kill", "--match", ""]).is_err());
+        assert!(Args::try_parse_from(["sysmon", "kill", "--match", "  "]).is_err());
+        assert!(Args::try_parse_from(["sysmon", "kill", "--match", "nginx"]).is_ok());
+    }
+
+    #[test]
+    fn test_parse_refresh() {
+        assert_eq!(
+            parse_refresh("processes=5s"),
+            Ok((Subsystem::Processes, Duration::from_secs(5)))
+        );
+        assert!(parse_refresh("processes").is_err());
+        assert!(parse_refresh("load=5s").is_err());
+        assert!(parse_refresh("cpu=soon").is_err());
+    }
+}
@@ -0,0 +1,112 @@
+// Compare module - Snapshot diff
+// Loads two saved snapshots and prints what changed (`sysmon diff`)
+
+use crate::cli::OutputFormat;
+use crate::config::Settings;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use system_monitor::diff::SnapshotDiff;
+use system_monitor::display::formatter;
+use system_monitor::Snapshot;
+
+/// Compare two snapshot files and print the differences
+///
+/// Returns the process exit code: 0 on success, 1 if a file can't be read,
+/// 2 for an unsupported output format.
+///
+/// # Arguments
+/// * `settings` - Output format and style
+/// * `before` - File with the earlier snapshot
+/// * `after` - File with the later snapshot
+pub fn run(settings: &Settings, before: &Path, after: &Path) -> i32 {
+    if !matches!(settings.format, OutputFormat::Text | OutputFormat::Json) {
+        eprintln!("Error: sysmon diff supports --format text or json");
+        return 2;
+    }
+
+    let mut snapshots = Vec::new();
+    for path in [before, after] {
+        match load_snapshot(path) {
+            Ok(snapshot) => snapshots.push(snapshot),
+            Err(e) => {
+                eprintln!("Error reading {}: {}", path.display(), e);
+                return 1;
+            }
+        }
+    }
+    let (old, new) = (snapshots[0].schema_version, snapshots[1].schema_version);
+    if old != new {
+        eprintln!(
+            "Warning: the snapshots have schema versions {} and {}, some values \
+             (e.g. network totals) are not comparable",
+            old, new
+        );
+    }
+    let diff = SnapshotDiff::new(&snapshots[0], &snapshots[1]);
+
+    let mut out = io::stdout().lock();
+    let result = match settings.format {
+        OutputFormat::Json => serde_json::to_writer_pretty(&mut out, &diff)
+            .map_err(io::Error::from)
+            .and_then(|()| writeln!(out)),
+        _ => formatter::print_snapshot_diff(&mut out, &diff, &settings.style),
+    };
+
+    match result {
+        Ok(()) => 0,
+        // A closed pipe (e.g. `| head`) is a normal way to stop
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => 0,
+        Err(e) => {
+            eprintln!("Error writing output: {}", e);
+            1
+        }
+    }
+}
+
+/// Read a snapshot saved with `--format json`
+///
+/// NDJSON streams (`--watch --format json` or `--output`) are accepted too;
+/// their last record is used.
+fn load_snapshot(path: &Path) -> Result<Snapshot, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    parse_snapshot(&text)
+}
+
+fn parse_snapshot(text: &str) -> Result<Snapshot, String> {
+    serde_json::from_str(text)
+        .or_else(|e| {
+            let last = text.lines().rev().find(|line| !line.trim().is_empty());
+            serde_json::from_str(last.unwrap_or_default()).map_err(|_| e)
+        })
+        .map_err(|e| format!("not a JSON snapshot ({})", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_document_and_stream() {
+        let mut monitor = Default::default();
+        let snapshot = Snapshot::collect(&mut monitor, false);
+
+        let document = serde_json::to_string_pretty(&snapshot).unwrap();
+        let parsed = parse_snapshot(&document).unwrap();
+        assert_eq!(parsed.timestamp_ms, snapshot.timestamp_ms);
+
+        // The last record of a stream, with its sequence number
+        let mut stream = String::new();
+        for (seq, uptime) in [(0, 10), (1, 11)] {
+            let mut value = serde_json::to_value(&snapshot).unwrap();
+            value["seq"] = seq.into();
+            value["uptime_secs"] = uptime.into();
+            stream.push_str(&format!("{}\n", value));
+        }
+        assert_eq!(parse_snapshot(&stream).unwrap().uptime_secs, 11);
+
+        assert!(parse_snapshot("{\"cpu\": 1}")
+            .unwrap_err()
+            .starts_with("not a JSON snapshot"));
+    }
+}
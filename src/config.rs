@@ -0,0 +1,1166 @@
+// Configuration file module
+// Reads defaults from config.toml files and merges them with the command line
+
+use crate::cli::{Args, OutputFormat};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use system_monitor::alert::Rule;
+use system_monitor::display::formatter::parse_duration;
+use system_monitor::display::sparkline::{SparklineStyle, DEFAULT_SPARKLINE_LENGTH};
+use system_monitor::display::style::{
+    parse_color, Style, Theme, Thresholds, BUILTIN_THEMES, PROCESS_MEMORY_THRESHOLDS,
+};
+use system_monitor::history::Retention;
+use system_monitor::monitor::network::InterfaceFilter;
+use system_monitor::monitor::refresh::{RefreshPlan, Subsystem};
+use system_monitor::monitor::snapshot::TopCounts;
+use system_monitor::output::terminal::{Section, ViewOptions};
+
+/// System-wide configuration file
+pub const SYSTEM_CONFIG: &str = "/etc/sysmon/config.toml";
+
+/// Contents of one configuration file (every key is optional)
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Update interval in seconds
+    pub interval: Option<u64>,
+    /// Show all cores and more processes
+    pub detailed: Option<bool>,
+    /// Output format
+    pub format: Option<OutputFormat>,
+    /// Number of processes in the top CPU list
+    pub top_cpu: Option<usize>,
+    /// Number of processes in the top memory list
+    pub top_memory: Option<usize>,
+    /// Sections of the text view, in display order
+    pub sections: Option<Vec<Section>>,
+    /// Samples drawn as sparklines in watch and interactive mode (0 turns them off)
+    pub sparkline_length: Option<usize>,
+    /// Sparkline characters: block or braille
+    pub sparkline_style: Option<SparklineStyle>,
+    /// Name of a built-in theme or of a `[themes.NAME]` table
+    pub theme: Option<String>,
+    /// Values at which metrics change color
+    pub thresholds: Option<ThresholdsConfig>,
+    /// Custom themes by name
+    pub themes: Option<BTreeMap<String, ThemeConfig>>,
+    /// Alert rules and what to do when they fire
+    pub alerts: Option<AlertsConfig>,
+    /// Metric history recording
+    pub history: Option<HistoryConfig>,
+    /// Minimum time between two refreshes of each subsystem
+    pub refresh: Option<RefreshConfig>,
+    /// Network interfaces to report
+    pub network: Option<NetworkConfig>,
+}
+
+/// `[network]` table, e.g. `exclude_interfaces = ["lo", "docker*"]`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkConfig {
+    /// Only these interfaces (`*` and `?` wildcards)
+    pub interfaces: Option<Vec<String>>,
+    /// Never these interfaces
+    pub exclude_interfaces: Option<Vec<String>>,
+}
+
+/// `[refresh]` table, e.g. `processes = "5s"`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RefreshConfig {
+    /// CPU usage
+    pub cpu: Option<String>,
+    /// Memory usage
+    pub memory: Option<String>,
+    /// Disk usage
+    pub disks: Option<String>,
+    /// Network traffic
+    pub network: Option<String>,
+    /// Process list
+    pub processes: Option<String>,
+}
+
+impl RefreshConfig {
+    fn get(&self, subsystem: Subsystem) -> Option<&String> {
+        match subsystem {
+            Subsystem::Cpu => self.cpu.as_ref(),
+            Subsystem::Memory => self.memory.as_ref(),
+            Subsystem::Disks => self.disks.as_ref(),
+            Subsystem::Network => self.network.as_ref(),
+            Subsystem::Processes => self.processes.as_ref(),
+        }
+    }
+}
+
+/// `[history]` table
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HistoryConfig {
+    /// Record every tick in watch and interactive mode
+    pub record: Option<bool>,
+    /// Directory of the history files
+    pub dir: Option<PathBuf>,
+    /// How long raw samples are kept, e.g. "1h"
+    pub retention: Option<String>,
+    /// How long one-minute averages are kept, e.g. "30d"
+    pub rollup_retention: Option<String>,
+}
+
+/// `[alerts]` table
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlertsConfig {
+    /// Rules such as "cpu.global > 90 for 30s"
+    pub rules: Option<Vec<String>>,
+    /// Shell command run for every fired or resolved alert
+    pub command: Option<String>,
+    /// File that fired and resolved alerts are appended to
+    pub log: Option<PathBuf>,
+}
+
+/// `[thresholds]` table (keys missing here keep their default)
+///
+/// `warning` and `critical` apply to every percentage; the per-metric tables
+/// override them.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThresholdsConfig {
+    /// Warning from this percentage up
+    pub warning: Option<f32>,
+    /// Critical from this percentage up
+    pub critical: Option<f32>,
+    /// CPU usage in percent
+    pub cpu: Option<BandConfig>,
+    /// Memory usage in percent
+    pub memory: Option<BandConfig>,
+    /// Disk usage in percent
+    pub disk: Option<BandConfig>,
+    /// Memory of a single process in MB
+    pub process_memory: Option<BandConfig>,
+}
+
+/// Thresholds of one metric, e.g. `[thresholds.disk]`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BandConfig {
+    /// Warning from this value up
+    pub warning: Option<f32>,
+    /// Critical from this value up
+    pub critical: Option<f32>,
+}
+
+impl BandConfig {
+    fn merge(base: Option<Self>, over: Option<Self>) -> Option<Self> {
+        match (base, over) {
+            (Some(base), Some(over)) => Some(BandConfig {
+                warning: over.warning.or(base.warning),
+                critical: over.critical.or(base.critical),
+            }),
+            (base, over) => over.or(base),
+        }
+    }
+
+    /// Fill in missing values and validate
+    ///
+    /// # Arguments
+    /// * `band` - Configured values
+    /// * `defaults` - Values for missing keys
+    /// * `table` - Table name for error messages, e.g. "thresholds.disk"
+    fn resolve(
+        band: Option<Self>,
+        defaults: Thresholds,
+        table: &str,
+    ) -> Result<Thresholds, String> {
+        let band = band.unwrap_or_default();
+        let thresholds = Thresholds {
+            warning: band.warning.unwrap_or(defaults.warning),
+            critical: band.critical.unwrap_or(defaults.critical),
+        };
+        thresholds
+            .validate()
+            .map_err(|e| format!("[{}]: {}", table, e))?;
+        Ok(thresholds)
+    }
+}
+
+/// A custom theme, e.g. `[themes.ops]`
+///
+/// Colors are names like "red" or "bright cyan", "#rrggbb", or "none" for
+/// the terminal's default; missing colors come from `base`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeConfig {
+    /// Built-in theme providing the missing colors (default: "default")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    /// Values below the warning threshold
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ok: Option<String>,
+    /// Values between the warning and critical thresholds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    /// Values at or above the critical threshold
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub critical: Option<String>,
+    /// Section titles and table headers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heading: Option<String>,
+    /// Hints and unavailable values
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub muted: Option<String>,
+    /// Idle network traffic
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idle: Option<String>,
+}
+
+impl ThemeConfig {
+    /// Build the theme on top of its base
+    fn build(&self) -> Result<Theme, String> {
+        let base = self.base.as_deref().unwrap_or("default");
+        let mut theme = Theme::builtin(base).ok_or_else(|| {
+            format!(
+                "unknown base theme '{}' (built in: {})",
+                base,
+                BUILTIN_THEMES.join(", ")
+            )
+        })?;
+
+        let colors = [
+            ("ok", &self.ok, &mut theme.ok),
+            ("warning", &self.warning, &mut theme.warning),
+            ("critical", &self.critical, &mut theme.critical),
+            ("heading", &self.heading, &mut theme.heading),
+            ("muted", &self.muted, &mut theme.muted),
+            ("idle", &self.idle, &mut theme.idle),
+        ];
+        for (key, value, color) in colors {
+            if let Some(value) = value {
+                *color = parse_color(value).map_err(|e| format!("{}: {}", key, e))?;
+            }
+        }
+        Ok(theme)
+    }
+}
+
+/// Find a theme by name, custom themes first
+///
+/// # Arguments
+/// * `name` - Theme name
+/// * `themes` - Custom themes from the configuration files
+pub fn find_theme(name: &str, themes: &BTreeMap<String, ThemeConfig>) -> Result<Theme, String> {
+    match themes.get(name) {
+        Some(custom) => custom
+            .build()
+            .map_err(|e| format!("[themes.{}]: {}", name, e)),
+        None => Theme::builtin(name).ok_or_else(|| {
+            let mut known: Vec<&str> = BUILTIN_THEMES.to_vec();
+            known.extend(themes.keys().map(String::as_str));
+            format!("unknown theme '{}' (available: {})", name, known.join(", "))
+        }),
+    }
+}
+
+impl Config {
+    /// Parse a configuration file's content
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let config: Config = toml::from_str(content).map_err(|e| e.to_string())?;
+        if config.interval == Some(0) {
+            return Err("interval must be at least 1 second".to_string());
+        }
+        // Built-in names always mean the built-in theme
+        if let Some(name) = config
+            .themes
+            .iter()
+            .flat_map(|themes| themes.keys())
+            .find(|name| BUILTIN_THEMES.contains(&name.as_str()))
+        {
+            return Err(format!(
+                "[themes.{}]: '{}' is a built-in theme, use another name with base = \"{}\"",
+                name, name, name
+            ));
+        }
+        Ok(config)
+    }
+
+    /// Combine two files, keys set in `other` win
+    pub fn merge(self, other: Config) -> Config {
+        let thresholds = match (self.thresholds, other.thresholds) {
+            (Some(base), Some(over)) => Some(ThresholdsConfig {
+                warning: over.warning.or(base.warning),
+                critical: over.critical.or(base.critical),
+                cpu: BandConfig::merge(base.cpu, over.cpu),
+                memory: BandConfig::merge(base.memory, over.memory),
+                disk: BandConfig::merge(base.disk, over.disk),
+                process_memory: BandConfig::merge(base.process_memory, over.process_memory),
+            }),
+            (base, over) => over.or(base),
+        };
+
+        let alerts = match (self.alerts, other.alerts) {
+            (Some(base), Some(over)) => Some(AlertsConfig {
+                rules: over.rules.or(base.rules),
+                command: over.command.or(base.command),
+                log: over.log.or(base.log),
+            }),
+            (base, over) => over.or(base),
+        };
+
+        let history = match (self.history, other.history) {
+            (Some(base), Some(over)) => Some(HistoryConfig {
+                record: over.record.or(base.record),
+                dir: over.dir.or(base.dir),
+                retention: over.retention.or(base.retention),
+                rollup_retention: over.rollup_retention.or(base.rollup_retention),
+            }),
+            (base, over) => over.or(base),
+        };
+
+        let refresh = match (self.refresh, other.refresh) {
+            (Some(base), Some(over)) => Some(RefreshConfig {
+                cpu: over.cpu.or(base.cpu),
+                memory: over.memory.or(base.memory),
+                disks: over.disks.or(base.disks),
+                network: over.network.or(base.network),
+                processes: over.processes.or(base.processes),
+            }),
+            (base, over) => over.or(base),
+        };
+
+        let network = match (self.network, other.network) {
+            (Some(base), Some(over)) => Some(NetworkConfig {
+                interfaces: over.interfaces.or(base.interfaces),
+                exclude_interfaces: over.exclude_interfaces.or(base.exclude_interfaces),
+            }),
+            (base, over) => over.or(base),
+        };
+
+        // A theme defined in both files is replaced as a whole
+        let themes = match (self.themes, other.themes) {
+            (Some(mut base), Some(over)) => {
+                base.extend(over);
+                Some(base)
+            }
+            (base, over) => over.or(base),
+        };
+
+        Config {
+            interval: other.interval.or(self.interval),
+            detailed: other.detailed.or(self.detailed),
+            format: other.format.or(self.format),
+            top_cpu: other.top_cpu.or(self.top_cpu),
+            top_memory: other.top_memory.or(self.top_memory),
+            sections: other.sections.or(self.sections),
+            sparkline_length: other.sparkline_length.or(self.sparkline_length),
+            sparkline_style: other.sparkline_style.or(self.sparkline_style),
+            theme: other.theme.or(self.theme),
+            thresholds,
+            themes,
+            alerts,
+            history,
+            refresh,
+            network,
+        }
+    }
+}
+
+/// Per-user configuration file
+///
+/// `$XDG_CONFIG_HOME/sysmon/config.toml`, or `~/.config/sysmon/config.toml`
+/// when `XDG_CONFIG_HOME` is unset.
+pub fn user_config_path() -> Option<PathBuf> {
+    user_config_path_from(env::var_os("XDG_CONFIG_HOME"), env::var_os("HOME"))
+}
+
+fn user_config_path_from(
+    xdg_config_home: Option<OsString>,
+    home: Option<OsString>,
+) -> Option<PathBuf> {
+    // The XDG spec says to ignore relative paths
+    let base = match xdg_config_home.map(PathBuf::from) {
+        Some(dir) if dir.is_absolute() => dir,
+        _ => PathBuf::from(home.filter(|home| !home.is_empty())?).join(".config"),
+    };
+    Some(base.join("sysmon").join("config.toml"))
+}
+
+/// Default directory of the metric history
+///
+/// `$XDG_DATA_HOME/sysmon/history`, or `~/.local/share/sysmon/history` when
+/// `XDG_DATA_HOME` is unset.
+pub fn history_dir() -> Option<PathBuf> {
+    history_dir_from(env::var_os("XDG_DATA_HOME"), env::var_os("HOME"))
+}
+
+fn history_dir_from(xdg_data_home: Option<OsString>, home: Option<OsString>) -> Option<PathBuf> {
+    let base = match xdg_data_home.map(PathBuf::from) {
+        Some(dir) if dir.is_absolute() => dir,
+        _ => PathBuf::from(home.filter(|home| !home.is_empty())?)
+            .join(".local")
+            .join("share"),
+    };
+    Some(base.join("sysmon").join("history"))
+}
+
+/// Files to read, lowest priority first
+///
+/// # Arguments
+/// * `explicit` - File given with `--config`, replaces the default locations
+pub fn search_paths(explicit: Option<&Path>) -> Vec<PathBuf> {
+    match explicit {
+        Some(path) => vec![path.to_path_buf()],
+        None => std::iter::once(PathBuf::from(SYSTEM_CONFIG))
+            .chain(user_config_path())
+            .collect(),
+    }
+}
+
+/// Read and merge the configuration files
+///
+/// Missing default files are skipped; a missing `--config` file, unreadable
+/// files and invalid content (including unknown keys) are errors.
+/// Returns the merged configuration and the files that were read.
+///
+/// # Arguments
+/// * `explicit` - File given with `--config`, replaces the default locations
+pub fn load(explicit: Option<&Path>) -> Result<(Config, Vec<PathBuf>), String> {
+    let mut config = Config::default();
+    let mut loaded = Vec::new();
+
+    for path in search_paths(explicit) {
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound && explicit.is_none() => continue,
+            Err(e) => return Err(format!("cannot read {}: {}", path.display(), e)),
+        };
+        let file = Config::parse(&content)
+            .map_err(|e| format!("invalid configuration in {}: {}", path.display(), e))?;
+        config = config.merge(file);
+        loaded.push(path);
+    }
+
+    Ok((config, loaded))
+}
+
+/// Effective settings: command line, then configuration files, then defaults
+#[derive(Debug, Clone, Serialize)]
+pub struct Settings {
+    /// Update interval in seconds
+    pub interval: u64,
+    /// Show all cores and more processes
+    pub detailed: bool,
+    /// Output format
+    pub format: OutputFormat,
+    /// Number of processes in the top CPU list (default depends on `detailed`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_cpu: Option<usize>,
+    /// Number of processes in the top memory list (default depends on `detailed`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_memory: Option<usize>,
+    /// Sections of the text view, in display order
+    pub sections: Vec<Section>,
+    /// Samples drawn as sparklines in watch and interactive mode
+    pub sparkline_length: usize,
+    /// Sparkline characters
+    pub sparkline_style: SparklineStyle,
+    /// Name of the color theme
+    pub theme: String,
+    /// Values at which metrics change color
+    pub thresholds: ThresholdSettings,
+    /// Custom themes from the configuration files
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub themes: BTreeMap<String, ThemeConfig>,
+    /// Alert rules and actions
+    #[serde(skip_serializing_if = "AlertSettings::is_empty")]
+    pub alerts: AlertSettings,
+    /// Metric history recording
+    pub history: HistorySettings,
+    /// Minimum time between two refreshes of each subsystem
+    pub refresh: RefreshSettings,
+    /// Network interfaces to report
+    pub network: NetworkSettings,
+    /// Colors and thresholds of the text view
+    #[serde(skip)]
+    pub style: Style,
+}
+
+/// Effective alert rules and actions
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AlertSettings {
+    /// Rules checked on every tick
+    pub rules: Vec<Rule>,
+    /// Shell command run for every fired or resolved alert
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// File that fired and resolved alerts are appended to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log: Option<PathBuf>,
+}
+
+impl AlertSettings {
+    /// No rules and no actions configured
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty() && self.command.is_none() && self.log.is_none()
+    }
+
+    fn resolve(args: &Args, config: Option<&AlertsConfig>) -> Result<Self, String> {
+        let config = config.cloned().unwrap_or_default();
+
+        // Rules given on the command line replace the configured ones
+        let rules = if args.alerts.is_empty() {
+            config
+                .rules
+                .unwrap_or_default()
+                .iter()
+                .map(|rule| {
+                    rule.parse()
+                        .map_err(|e| format!("[alerts] rule '{}': {}", rule, e))
+                })
+                .collect::<Result<_, _>>()?
+        } else {
+            args.alerts.clone()
+        };
+
+        Ok(AlertSettings {
+            rules,
+            command: args.alert_command.clone().or(config.command),
+            log: args.alert_log.clone().or(config.log),
+        })
+    }
+}
+
+/// Effective history settings
+#[derive(Debug, Clone, Serialize)]
+pub struct HistorySettings {
+    /// Record every tick in watch and interactive mode
+    pub record: bool,
+    /// Directory of the history files (None without a home directory)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dir: Option<PathBuf>,
+    /// How long raw samples are kept
+    #[serde(serialize_with = "serialize_duration")]
+    pub retention: Duration,
+    /// How long one-minute averages are kept
+    #[serde(serialize_with = "serialize_duration")]
+    pub rollup_retention: Duration,
+}
+
+impl HistorySettings {
+    fn resolve(args: &Args, config: Option<&HistoryConfig>) -> Result<Self, String> {
+        let config = config.cloned().unwrap_or_default();
+        let defaults = Retention::default();
+        let duration = |value: Option<String>, key: &str, default: Duration| match value {
+            Some(value) => parse_duration(&value).map_err(|e| format!("[history] {}: {}", key, e)),
+            None => Ok(default),
+        };
+
+        Ok(HistorySettings {
+            // A flag can only switch recording on
+            record: args.history || config.record.unwrap_or(false),
+            dir: args.history_dir.clone().or(config.dir).or_else(history_dir),
+            retention: duration(config.retention, "retention", defaults.raw)?,
+            rollup_retention: duration(
+                config.rollup_retention,
+                "rollup_retention",
+                defaults.rollup,
+            )?,
+        })
+    }
+
+    /// How long both kinds of samples are kept
+    pub fn retention(&self) -> Retention {
+        Retention {
+            raw: self.retention,
+            rollup: self.rollup_retention,
+        }
+    }
+}
+
+/// Effective network interface patterns (loopback only when an include
+/// pattern names it)
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct NetworkSettings {
+    /// Only these interfaces (every one when empty)
+    pub interfaces: Vec<String>,
+    /// Never these interfaces
+    pub exclude_interfaces: Vec<String>,
+}
+
+impl NetworkSettings {
+    fn resolve(args: &Args, config: Option<&NetworkConfig>) -> Self {
+        let config = config.cloned().unwrap_or_default();
+        // Patterns given on the command line replace the configured ones
+        let patterns = |flags: &[String], configured: Option<Vec<String>>| {
+            if flags.is_empty() {
+                configured.unwrap_or_default()
+            } else {
+                flags.to_vec()
+            }
+        };
+
+        NetworkSettings {
+            interfaces: patterns(&args.ifaces, config.interfaces),
+            exclude_interfaces: patterns(&args.exclude_ifaces, config.exclude_interfaces),
+        }
+    }
+
+    /// Filter selecting the configured interfaces
+    pub fn filter(&self) -> InterfaceFilter {
+        InterfaceFilter::new(self.interfaces.clone(), self.exclude_interfaces.clone())
+    }
+}
+
+/// Effective refresh intervals (0s refreshes on every tick)
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RefreshSettings {
+    /// CPU usage
+    #[serde(serialize_with = "serialize_duration")]
+    pub cpu: Duration,
+    /// Memory usage
+    #[serde(serialize_with = "serialize_duration")]
+    pub memory: Duration,
+    /// Disk usage
+    #[serde(serialize_with = "serialize_duration")]
+    pub disks: Duration,
+    /// Network traffic
+    #[serde(serialize_with = "serialize_duration")]
+    pub network: Duration,
+    /// Process list
+    #[serde(serialize_with = "serialize_duration")]
+    pub processes: Duration,
+}
+
+impl RefreshSettings {
+    fn resolve(args: &Args, config: Option<&RefreshConfig>) -> Result<Self, String> {
+        let config = config.cloned().unwrap_or_default();
+        let interval = |subsystem: Subsystem| {
+            // The last flag for a subsystem wins over the file
+            match args.refresh.iter().rev().find(|(s, _)| *s == subsystem) {
+                Some((_, interval)) => Ok(*interval),
+                None => match config.get(subsystem) {
+                    Some(value) => {
+                        parse_duration(value).map_err(|e| format!("[refresh] {}: {}", subsystem, e))
+                    }
+                    None => Ok(Duration::ZERO),
+                },
+            }
+        };
+
+        Ok(RefreshSettings {
+            cpu: interval(Subsystem::Cpu)?,
+            memory: interval(Subsystem::Memory)?,
+            disks: interval(Subsystem::Disks)?,
+            network: interval(Subsystem::Network)?,
+            processes: interval(Subsystem::Processes)?,
+        })
+    }
+
+    /// Minimum time between two refreshes of a subsystem
+    pub fn interval(&self, subsystem: Subsystem) -> Duration {
+        match subsystem {
+            Subsystem::Cpu => self.cpu,
+            Subsystem::Memory => self.memory,
+            Subsystem::Disks => self.disks,
+            Subsystem::Network => self.network,
+            Subsystem::Processes => self.processes,
+        }
+    }
+
+    /// Refresh only these subsystems, each at its interval
+    pub fn plan(&self, subsystems: impl IntoIterator<Item = Subsystem>) -> RefreshPlan {
+        Subsystem::ALL
+            .into_iter()
+            .fold(RefreshPlan::only(subsystems), |plan, subsystem| {
+                plan.with_interval(subsystem, self.interval(subsystem))
+            })
+    }
+}
+
+/// Write a duration the way `parse_duration` reads it, e.g. "30d" or "90s"
+fn serialize_duration<S: serde::Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let secs = duration.as_secs();
+    let text = if duration.subsec_millis() != 0 {
+        format!("{}ms", duration.as_millis())
+    } else if secs == 0 {
+        "0s".to_string()
+    } else {
+        [(86400, "d"), (3600, "h"), (60, "m"), (1, "s")]
+            .iter()
+            .find(|(unit, _)| secs.is_multiple_of(*unit))
+            .map(|(unit, suffix)| format!("{}{}", secs / unit, suffix))
+            .unwrap_or_default()
+    };
+    serializer.serialize_str(&text)
+}
+
+/// Effective thresholds of every metric
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ThresholdSettings {
+    /// CPU usage in percent
+    pub cpu: Thresholds,
+    /// Memory usage in percent
+    pub memory: Thresholds,
+    /// Disk usage in percent
+    pub disk: Thresholds,
+    /// Memory of a single process in MB
+    pub process_memory: Thresholds,
+}
+
+impl Settings {
+    /// Merge the command line over the configuration files
+    pub fn resolve(args: &Args, config: &Config) -> Result<Self, String> {
+        let table = config.thresholds.clone().unwrap_or_default();
+        let percent = BandConfig::resolve(
+            Some(BandConfig {
+                warning: table.warning,
+                critical: table.critical,
+            }),
+            Thresholds::default(),
+            "thresholds",
+        )?;
+        let thresholds = ThresholdSettings {
+            cpu: BandConfig::resolve(table.cpu, percent, "thresholds.cpu")?,
+            memory: BandConfig::resolve(table.memory, percent, "thresholds.memory")?,
+            disk: BandConfig::resolve(table.disk, percent, "thresholds.disk")?,
+            process_memory: BandConfig::resolve(
+                table.process_memory,
+                PROCESS_MEMORY_THRESHOLDS,
+                "thresholds.process_memory",
+            )?,
+        };
+
+        let themes = config.themes.clone().unwrap_or_default();
+        let theme = args
+            .theme
+            .clone()
+            .or_else(|| config.theme.clone())
+            .unwrap_or_else(|| "default".to_string());
+        let style = Style {
+            theme: find_theme(&theme, &themes)?,
+            cpu: thresholds.cpu,
+            memory: thresholds.memory,
+            disk: thresholds.disk,
+            process_memory: thresholds.process_memory,
+        };
+
+        Ok(Settings {
+            interval: args.interval.or(config.interval).unwrap_or(1),
+            // A flag can only switch detailed mode on
+            detailed: args.detailed || config.detailed.unwrap_or(false),
+            format: args.format.or(config.format).unwrap_or(OutputFormat::Text),
+            top_cpu: config.top_cpu,
+            top_memory: config.top_memory,
+            sections: config
+                .sections
+                .clone()
+                .unwrap_or_else(|| Section::ALL.to_vec()),
+            sparkline_length: args
+                .sparkline_length
+                .or(config.sparkline_length)
+                .unwrap_or(DEFAULT_SPARKLINE_LENGTH),
+            sparkline_style: config.sparkline_style.unwrap_or_default(),
+            theme,
+            thresholds,
+            themes,
+            alerts: AlertSettings::resolve(args, config.alerts.as_ref())?,
+            history: HistorySettings::resolve(args, config.history.as_ref())?,
+            refresh: RefreshSettings::resolve(args, config.refresh.as_ref())?,
+            network: NetworkSettings::resolve(args, config.network.as_ref()),
+            style,
+        })
+    }
+
+    /// Length of the top process lists for a view
+    ///
+    /// # Arguments
+    /// * `detailed` - Whether the detailed view is shown (may differ from
+    ///   `self.detailed` after toggling it in interactive mode)
+    pub fn top_counts(&self, detailed: bool) -> TopCounts {
+        let defaults = TopCounts::for_view(detailed);
+        TopCounts {
+            cpu: self.top_cpu.unwrap_or(defaults.cpu),
+            memory: self.top_memory.unwrap_or(defaults.memory),
+        }
+    }
+
+    /// Subsystems the output, alerts and history need, at their intervals
+    ///
+    /// The text view only refreshes the sections it shows; CSV, StatsD,
+    /// alerts and the history don't need processes, JSON and InfluxDB need
+    /// everything.
+    ///
+    /// # Arguments
+    /// * `tree` - Whether the process tree is collected
+    pub fn refresh_plan(&self, tree: bool) -> RefreshPlan {
+        let metrics = [
+            Subsystem::Cpu,
+            Subsystem::Memory,
+            Subsystem::Disks,
+            Subsystem::Network,
+        ];
+        let mut needed: Vec<Subsystem> = match self.format {
+            OutputFormat::Json | OutputFormat::Influx => Subsystem::ALL.to_vec(),
+            OutputFormat::Csv | OutputFormat::Statsd => metrics.to_vec(),
+            OutputFormat::Text => self
+                .sections
+                .iter()
+                .filter_map(|section| match section {
+                    Section::Cpu => Some(Subsystem::Cpu),
+                    Section::Memory => Some(Subsystem::Memory),
+                    Section::Disk => Some(Subsystem::Disks),
+                    Section::Network => Some(Subsystem::Network),
+                    Section::Processes => Some(Subsystem::Processes),
+                    Section::Uptime => None,
+                })
+                .collect(),
+        };
+        if tree {
+            needed.push(Subsystem::Processes);
+        }
+        needed.extend(self.alerts.rules.iter().map(|rule| rule.metric.subsystem()));
+        if self.history.record {
+            needed.extend(metrics);
+        }
+        self.refresh.plan(needed)
+    }
+
+    /// Sections and style of the text view
+    pub fn view_options(&self) -> ViewOptions {
+        ViewOptions {
+            detailed: self.detailed,
+            sections: self.sections.clone(),
+            style: self.style.clone(),
+            sparkline_length: self.sparkline_length,
+            sparkline_style: self.sparkline_style,
+        }
+    }
+
+    /// The settings as a configuration file, top-N defaults filled in
+    pub fn to_toml(&self) -> Result<String, String> {
+        let top = self.top_counts(self.detailed);
+        let shown = Settings {
+            top_cpu: Some(top.cpu),
+            top_memory: Some(top.memory),
+            ..self.clone()
+        };
+        toml::to_string(&shown).map_err(|e| e.to_string())
+    }
+}
+
+/// Handle `sysmon config`
+///
+/// Lists the configuration files, and with `print_effective` also prints the
+/// merged settings as TOML.
+///
+/// # Arguments
+/// * `settings` - Effective settings
+/// * `searched` - Files sysmon looks for, lowest priority first
+/// * `loaded` - Files that were read
+/// * `print_effective` - Print the merged settings
+pub fn run(
+    settings: &Settings,
+    searched: &[PathBuf],
+    loaded: &[PathBuf],
+    print_effective: bool,
+) -> i32 {
+    if !print_effective {
+        for path in searched {
+            let state = if loaded.contains(path) {
+                "loaded"
+            } else {
+                "not found"
+            };
+            println!("{} ({})", path.display(), state);
+        }
+        return 0;
+    }
+
+    match settings.to_toml() {
+        Ok(toml) => {
+            println!("# Effective settings (command line > files > defaults)");
+            for path in loaded {
+                println!("# Read from {}", path.display());
+            }
+            print!("{}", toml);
+            0
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use colored::Color;
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::parse(
+            r#"
+            interval = 5
+            detailed = true
+            format = "json"
+            top_cpu = 8
+            sections = ["cpu", "memory"]
+
+            [thresholds]
+            critical = 90
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.interval, Some(5));
+        assert_eq!(config.format, Some(OutputFormat::Json));
+        assert_eq!(config.sections, Some(vec![Section::Cpu, Section::Memory]));
+        assert_eq!(config.thresholds.unwrap().critical, Some(90.0));
+    }
+
+    #[test]
+    fn test_unknown_keys_are_errors() {
+        let err = Config::parse("intervall = 5").unwrap_err();
+        assert!(err.contains("unknown field `intervall`"), "{}", err);
+
+        let err = Config::parse("[thresholds]\nwarn = 5").unwrap_err();
+        assert!(err.contains("unknown field `warn`"), "{}", err);
+
+        assert!(Config::parse("sections = [\"gpu\"]").is_err());
+        assert!(Config::parse("interval = 0").is_err());
+    }
+
+    #[test]
+    fn test_precedence() {
+        let system =
+            Config::parse("interval = 10\nformat = \"csv\"\n[thresholds]\nwarning = 50").unwrap();
+        let user = Config::parse("interval = 3\n[thresholds]\ncritical = 95").unwrap();
+        let config = system.merge(user);
+
+        let args = Args::parse_from(["sysmon"]);
+        let settings = Settings::resolve(&args, &config).unwrap();
+        assert_eq!(settings.interval, 3);
+        assert_eq!(settings.format, OutputFormat::Csv);
+        assert_eq!(settings.thresholds.cpu.warning, 50.0);
+        assert_eq!(settings.thresholds.cpu.critical, 95.0);
+
+        let args = Args::parse_from(["sysmon", "-i", "7", "-f", "json"]);
+        let settings = Settings::resolve(&args, &config).unwrap();
+        assert_eq!(settings.interval, 7);
+        assert_eq!(settings.format, OutputFormat::Json);
+    }
+
+    #[test]
+    fn test_top_counts_follow_detailed_unless_set() {
+        let args = Args::parse_from(["sysmon"]);
+        let settings = Settings::resolve(&args, &Config::parse("top_memory = 7").unwrap()).unwrap();
+
+        assert_eq!(settings.top_counts(false), TopCounts { cpu: 5, memory: 7 });
+        assert_eq!(settings.top_counts(true), TopCounts { cpu: 10, memory: 7 });
+    }
+
+    #[test]
+    fn test_user_config_path() {
+        let path = user_config_path_from(Some("/xdg".into()), Some("/home/me".into()));
+        assert_eq!(path, Some(PathBuf::from("/xdg/sysmon/config.toml")));
+
+        let path = user_config_path_from(Some("relative".into()), Some("/home/me".into()));
+        assert_eq!(
+            path,
+            Some(PathBuf::from("/home/me/.config/sysmon/config.toml"))
+        );
+
+        assert_eq!(user_config_path_from(None, None), None);
+    }
+
+    #[test]
+    fn test_per_metric_thresholds() {
+        let config = Config::parse(
+            "[thresholds]\nwarning = 40\n[thresholds.disk]\ncritical = 90\n\
+             [thresholds.process_memory]\nwarning = 1024",
+        )
+        .unwrap();
+        let settings = Settings::resolve(&Args::parse_from(["sysmon"]), &config).unwrap();
+
+        assert_eq!(settings.thresholds.cpu.warning, 40.0);
+        assert_eq!(settings.thresholds.cpu.critical, 70.0);
+        assert_eq!(settings.thresholds.disk.warning, 40.0);
+        assert_eq!(settings.thresholds.disk.critical, 90.0);
+        assert_eq!(settings.style.disk, settings.thresholds.disk);
+        assert_eq!(settings.thresholds.process_memory.warning, 1024.0);
+        assert_eq!(settings.thresholds.process_memory.critical, 2048.0);
+
+        let config = Config::parse("[thresholds.memory]\nwarning = 95").unwrap();
+        let err = Settings::resolve(&Args::parse_from(["sysmon"]), &config).unwrap_err();
+        assert!(err.starts_with("[thresholds.memory]"), "{}", err);
+    }
+
+    #[test]
+    fn test_themes() {
+        let config = Config::parse(
+            "theme = \"ops\"\n[themes.ops]\nbase = \"monochrome\"\ncritical = \"#ff0000\"",
+        )
+        .unwrap();
+        let settings = Settings::resolve(&Args::parse_from(["sysmon"]), &config).unwrap();
+        assert_eq!(settings.style.theme.ok, None);
+        assert_eq!(
+            settings.style.theme.critical,
+            Some(Color::TrueColor { r: 255, g: 0, b: 0 })
+        );
+
+        // The command line wins over the file
+        let args = Args::parse_from(["sysmon", "--theme", "solarized"]);
+        let settings = Settings::resolve(&args, &config).unwrap();
+        assert_eq!(settings.style.theme, Theme::builtin("solarized").unwrap());
+
+        let args = Args::parse_from(["sysmon", "--theme", "neon"]);
+        let err = Settings::resolve(&args, &config).unwrap_err();
+        assert!(err.contains("unknown theme 'neon'"), "{}", err);
+
+        let config = Config::parse("theme = \"x\"\n[themes.x]\nok = \"chartreuse\"").unwrap();
+        let err = Settings::resolve(&Args::parse_from(["sysmon"]), &config).unwrap_err();
+        assert!(err.starts_with("[themes.x]: ok: unknown color"), "{}", err);
+
+        assert!(Config::parse("[themes.solarized]\nok = \"red\"").is_err());
+        assert!(Config::parse("[themes.x]\nbackground = \"red\"").is_err());
+    }
+
+    #[test]
+    fn test_alerts() {
+        let config = Config::parse(
+            "[alerts]\nrules = [\"mem > 95\", \"disk[/var] > 85 for 1m\"]\ncommand = \"notify\"",
+        )
+        .unwrap();
+        let settings = Settings::resolve(&Args::parse_from(["sysmon"]), &config).unwrap();
+        let rules: Vec<String> = settings
+            .alerts
+            .rules
+            .iter()
+            .map(|r| r.to_string())
+            .collect();
+        assert_eq!(rules, ["mem > 95", "disk[/var] > 85 for 1m"]);
+        assert_eq!(settings.alerts.command.as_deref(), Some("notify"));
+
+        // Rules on the command line replace the configured ones
+        let args = Args::parse_from(["sysmon", "--alert", "cpu > 90", "--alert-log", "a.log"]);
+        let settings = Settings::resolve(&args, &config).unwrap();
+        assert_eq!(settings.alerts.rules.len(), 1);
+        assert_eq!(settings.alerts.command.as_deref(), Some("notify"));
+        assert_eq!(settings.alerts.log, Some(PathBuf::from("a.log")));
+
+        let config = Config::parse("[alerts]\nrules = [\"load > 3\"]").unwrap();
+        let err = Settings::resolve(&Args::parse_from(["sysmon"]), &config).unwrap_err();
+        assert!(err.starts_with("[alerts] rule 'load > 3'"), "{}", err);
+    }
+
+    #[test]
+    fn test_history() {
+        let path = history_dir_from(None, Some("/home/me".into()));
+        assert_eq!(
+            path,
+            Some(PathBuf::from("/home/me/.local/share/sysmon/history"))
+        );
+
+        let config = Config::parse(
+            "[history]\nrecord = true\ndir = \"/var/lib/sysmon\"\nrollup_retention = \"7d\"",
+        )
+        .unwrap();
+        let settings = Settings::resolve(&Args::parse_from(["sysmon"]), &config).unwrap();
+        assert!(settings.history.record);
+        assert_eq!(settings.history.dir, Some(PathBuf::from("/var/lib/sysmon")));
+        assert_eq!(settings.history.retention().raw, Duration::from_secs(3600));
+        assert_eq!(
+            settings.history.retention().rollup,
+            Duration::from_secs(7 * 86400)
+        );
+        // Durations are written back the way they are read
+        let toml = settings.to_toml().unwrap();
+        assert!(toml.contains("retention = \"1h\""), "{}", toml);
+        assert!(toml.contains("rollup_retention = \"7d\""), "{}", toml);
+
+        let args = Args::parse_from(["sysmon", "--history-dir", "/tmp/h"]);
+        let settings = Settings::resolve(&args, &config).unwrap();
+        assert_eq!(settings.history.dir, Some(PathBuf::from("/tmp/h")));
+
+        let config = Config::parse("[history]\nretention = \"soon\"").unwrap();
+        let err = Settings::resolve(&Args::parse_from(["sysmon"]), &config).unwrap_err();
+        assert!(err.starts_with("[history] retention:"), "{}", err);
+    }
+
+    #[test]
+    fn test_refresh() {
+        let config = Config::parse(
+            "sections = [\"cpu\", \"uptime\"]\n[refresh]\nprocesses = \"5s\"\ndisks = \"1m\"",
+        )
+        .unwrap();
+        let args = Args::parse_from(["sysmon", "--refresh", "processes=10s"]);
+        let settings = Settings::resolve(&args, &config).unwrap();
+        assert_eq!(settings.refresh.processes, Duration::from_secs(10));
+        assert_eq!(settings.refresh.disks, Duration::from_secs(60));
+        assert_eq!(settings.refresh.cpu, Duration::ZERO);
+
+        // The text view only refreshes what it shows
+        let plan = settings.refresh_plan(false);
+        assert!(plan.is_enabled(Subsystem::Cpu));
+        assert!(!plan.is_enabled(Subsystem::Memory));
+        assert!(!plan.is_enabled(Subsystem::Processes));
+        let plan = settings.refresh_plan(true);
+        assert_eq!(
+            plan.interval(Subsystem::Processes),
+            Some(Duration::from_secs(10))
+        );
+
+        // Alerts need their metrics, JSON needs everything
+        let args = Args::parse_from(["sysmon", "--alert", "disk > 90"]);
+        let settings = Settings::resolve(&args, &config).unwrap();
+        assert_eq!(
+            settings.refresh_plan(false).interval(Subsystem::Disks),
+            Some(Duration::from_secs(60))
+        );
+        let args = Args::parse_from(["sysmon", "--format", "json"]);
+        let plan = Settings::resolve(&args, &config)
+            .unwrap()
+            .refresh_plan(false);
+        assert!(Subsystem::ALL.iter().all(|s| plan.is_enabled(*s)));
+
+        let config = Config::parse("[refresh]\nload = \"5s\"");
+        assert!(config.is_err());
+        let config = Config::parse("[refresh]\ncpu = \"often\"").unwrap();
+        let err = Settings::resolve(&Args::parse_from(["sysmon"]), &config).unwrap_err();
+        assert!(err.starts_with("[refresh] cpu:"), "{}", err);
+    }
+
+    #[test]
+    fn test_network_interfaces() {
+        let config =
+            Config::parse("[network]\ninterfaces = [\"eth*\"]\nexclude_interfaces = [\"eth9\"]")
+                .unwrap();
+
+        // Each list from the command line replaces the configured one
+        let args = Args::parse_from(["sysmon", "--exclude-iface", "lo,docker*"]);
+        let settings = Settings::resolve(&args, &config).unwrap();
+        assert_eq!(settings.network.interfaces, ["eth*"]);
+        assert_eq!(settings.network.exclude_interfaces, ["lo", "docker*"]);
+        let filter = settings.network.filter();
+        assert!(filter.matches("eth9") && !filter.matches("wlan0"));
+
+        // Loopback is left out without patterns and with exclusions only
+        let settings =
+            Settings::resolve(&Args::parse_from(["sysmon"]), &Config::default()).unwrap();
+        assert!(!settings.network.filter().matches("lo"));
+        assert!(settings.network.filter().matches("eth0"));
+        let args = Args::parse_from(["sysmon", "--exclude-iface", "docker*"]);
+        let settings = Settings::resolve(&args, &Config::default()).unwrap();
+        assert!(!settings.network.filter().matches("lo"));
+    }
+}
@@ -0,0 +1,390 @@
+// Diff module - Snapshot comparison
+// What changed between two saved snapshots: processes, disks and network
+
+use crate::monitor::network::NetworkInfo;
+use crate::monitor::process::ProcessInfo;
+use crate::monitor::snapshot::Snapshot;
+use crate::monitor::tree::ProcessNode;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Smallest memory growth of a process worth reporting, in MB
+pub const MIN_MEMORY_GROWTH_MB: f64 = 1.0;
+
+/// Smallest CPU usage growth of a process worth reporting, in percent
+pub const MIN_CPU_GROWTH: f64 = 1.0;
+
+/// A value in both snapshots
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Change {
+    /// Value in the first snapshot
+    pub before: f64,
+    /// Value in the second snapshot
+    pub after: f64,
+    /// `after - before`
+    pub delta: f64,
+}
+
+impl Change {
+    /// Compare two values
+    pub fn new(before: f64, after: f64) -> Self {
+        Change {
+            before,
+            after,
+            delta: after - before,
+        }
+    }
+}
+
+/// A process found in both snapshots that uses more CPU or memory
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProcessChange {
+    /// Process ID
+    pub pid: u32,
+    /// Process name
+    pub name: String,
+    /// CPU usage in percent
+    pub cpu_usage: Change,
+    /// Resident memory in MB
+    pub memory_mb: Change,
+}
+
+/// A disk mounted in both snapshots whose usage grew
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiskChange {
+    /// Mount point
+    pub mount_point: String,
+    /// Used space in GB
+    pub used_gb: Change,
+    /// Used space in percent
+    pub percentage: Change,
+}
+
+/// Bytes moved by one network interface between the two snapshots
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InterfaceChange {
+    /// Interface name
+    pub name: String,
+    /// Bytes received
+    pub received: u64,
+    /// Bytes transmitted
+    pub transmitted: u64,
+    /// Whether a counter went backwards (e.g. after a reboot); the byte
+    /// counts are then unknown and reported as 0
+    pub counters_reset: bool,
+}
+
+impl InterfaceChange {
+    /// Compare the (received, transmitted) counters of an interface
+    fn new(name: &str, before: (u64, u64), after: (u64, u64)) -> Self {
+        let counters_reset = after.0 < before.0 || after.1 < before.1;
+        let moved = |before: u64, after: u64| if counters_reset { 0 } else { after - before };
+        InterfaceChange {
+            name: name.to_string(),
+            received: moved(before.0, after.0),
+            transmitted: moved(before.1, after.1),
+            counters_reset,
+        }
+    }
+}
+
+/// Bytes moved between the two snapshots
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NetworkChange {
+    /// Bytes received by the interfaces in both snapshots
+    pub received: u64,
+    /// Bytes transmitted by the interfaces in both snapshots
+    pub transmitted: u64,
+    /// Whether a counter of an interface went backwards (e.g. after a
+    /// reboot); the bytes of that interface are unknown and not counted
+    pub counters_reset: bool,
+    /// Interfaces in both snapshots, by name; interfaces attached or
+    /// removed in between are left out
+    pub interfaces: Vec<InterfaceChange>,
+}
+
+impl NetworkChange {
+    /// Compare the interfaces of two snapshots by name, or their totals
+    /// when one was saved without the list of interfaces
+    fn new(before: &NetworkInfo, after: &NetworkInfo) -> Self {
+        if before.interfaces.is_empty() || after.interfaces.is_empty() {
+            let total = InterfaceChange::new(
+                "",
+                (before.total_received, before.total_transmitted),
+                (after.total_received, after.total_transmitted),
+            );
+            return NetworkChange {
+                received: total.received,
+                transmitted: total.transmitted,
+                counters_reset: total.counters_reset,
+                interfaces: Vec::new(),
+            };
+        }
+
+        let interfaces: Vec<InterfaceChange> = after
+            .interfaces
+            .iter()
+            .filter_map(|interface| {
+                let previous = before
+                    .interfaces
+                    .iter()
+                    .find(|i| i.name == interface.name)?;
+                Some(InterfaceChange::new(
+                    &interface.name,
+                    (previous.total_received, previous.total_transmitted),
+                    (interface.total_received, interface.total_transmitted),
+                ))
+            })
+            .collect();
+        NetworkChange {
+            received: interfaces.iter().map(|i| i.received).sum(),
+            transmitted: interfaces.iter().map(|i| i.transmitted).sum(),
+            counters_reset: interfaces.iter().any(|i| i.counters_reset),
+            interfaces,
+        }
+    }
+}
+
+/// Everything that changed between two snapshots
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotDiff {
+    /// Collection time of the first snapshot (ms since the Unix epoch)
+    pub before_ms: u64,
+    /// Collection time of the second snapshot (ms since the Unix epoch)
+    pub after_ms: u64,
+    /// Whether both snapshots list every process (saved with `--tree`);
+    /// otherwise only the top process lists are compared and no process is
+    /// reported as appeared or disappeared
+    pub complete_process_lists: bool,
+    /// Global CPU usage in percent
+    pub cpu_usage: Change,
+    /// Memory usage in percent
+    pub memory_percentage: Change,
+    /// Processes only in the second snapshot, by PID
+    pub appeared: Vec<ProcessInfo>,
+    /// Processes only in the first snapshot, by PID
+    pub disappeared: Vec<ProcessInfo>,
+    /// Processes using more memory or CPU, largest memory growth first
+    pub grown: Vec<ProcessChange>,
+    /// Disks whose used space grew, fullest first
+    pub filled: Vec<DiskChange>,
+    /// Network traffic between the snapshots
+    pub network: NetworkChange,
+}
+
+impl SnapshotDiff {
+    /// Compare two snapshots
+    ///
+    /// Processes are matched by PID and name, so a reused PID counts as one
+    /// process disappearing and another one appearing.
+    ///
+    /// # Arguments
+    /// * `before` - The earlier snapshot
+    /// * `after` - The later snapshot
+    pub fn new(before: &Snapshot, after: &Snapshot) -> Self {
+        let complete = !before.process_tree.is_empty() && !after.process_tree.is_empty();
+        let old = processes(before);
+        let new = processes(after);
+
+        let (mut appeared, mut disappeared) = (Vec::new(), Vec::new());
+        if complete {
+            appeared = new
+                .values()
+                .filter(|p| !old.contains_key(&(p.pid, p.name.as_str())))
+                .map(|p| (*p).clone())
+                .collect();
+            disappeared = old
+                .values()
+                .filter(|p| !new.contains_key(&(p.pid, p.name.as_str())))
+                .map(|p| (*p).clone())
+                .collect();
+        }
+
+        let mut grown: Vec<ProcessChange> = new
+            .iter()
+            .filter_map(|(key, p)| {
+                let previous = old.get(key)?;
+                let change = ProcessChange {
+                    pid: p.pid,
+                    name: p.name.clone(),
+                    cpu_usage: Change::new(previous.cpu_usage as f64, p.cpu_usage as f64),
+                    memory_mb: Change::new(previous.memory_mb, p.memory_mb),
+                };
+                (change.memory_mb.delta >= MIN_MEMORY_GROWTH_MB
+                    || change.cpu_usage.delta >= MIN_CPU_GROWTH)
+                    .then_some(change)
+            })
+            .collect();
+        grown.sort_by(|a, b| {
+            b.memory_mb
+                .delta
+                .total_cmp(&a.memory_mb.delta)
+                .then(b.cpu_usage.delta.total_cmp(&a.cpu_usage.delta))
+        });
+
+        let mut filled: Vec<DiskChange> = after
+            .disks
+            .iter()
+            .filter_map(|disk| {
+                let previous = before
+                    .disks
+                    .iter()
+                    .find(|d| d.mount_point == disk.mount_point)?;
+                (disk.used_gb > previous.used_gb).then(|| DiskChange {
+                    mount_point: disk.mount_point.clone(),
+                    used_gb: Change::new(previous.used_gb, disk.used_gb),
+                    percentage: Change::new(previous.percentage, disk.percentage),
+                })
+            })
+            .collect();
+        filled.sort_by(|a, b| b.percentage.after.total_cmp(&a.percentage.after));
+
+        SnapshotDiff {
+            before_ms: before.timestamp_ms,
+            after_ms: after.timestamp_ms,
+            complete_process_lists: complete,
+            cpu_usage: Change::new(
+                before.cpu.global_usage as f64,
+                after.cpu.global_usage as f64,
+            ),
+            memory_percentage: Change::new(before.memory.percentage, after.memory.percentage),
+            appeared,
+            disappeared,
+            grown,
+            filled,
+            network: NetworkChange::new(&before.network, &after.network),
+        }
+    }
+
+    /// Seconds between the two snapshots (0 if they are out of order)
+    pub fn elapsed_secs(&self) -> f64 {
+        self.after_ms.saturating_sub(self.before_ms) as f64 / 1000.0
+    }
+}
+
+/// Every process of a snapshot by PID and name: the whole tree when it was
+/// collected, the top lists otherwise
+fn processes(snapshot: &Snapshot) -> BTreeMap<(u32, &str), &ProcessInfo> {
+    fn walk<'a>(nodes: &'a [ProcessNode], found: &mut Vec<&'a ProcessInfo>) {
+        for node in nodes {
+            found.push(&node.process);
+            walk(&node.children, found);
+        }
+    }
+
+    let mut found = Vec::new();
+    if snapshot.process_tree.is_empty() {
+        found.extend(&snapshot.top_processes_cpu);
+        found.extend(&snapshot.top_processes_memory);
+    } else {
+        walk(&snapshot.process_tree, &mut found);
+    }
+
+    // The same process can be in both top lists
+    let by_pid: HashMap<u32, &ProcessInfo> = found.into_iter().map(|p| (p.pid, p)).collect();
+    by_pid
+        .into_values()
+        .map(|p| ((p.pid, p.name.as_str()), p))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::network::InterfaceInfo;
+    use crate::monitor::snapshot::{sample_process as process, sample_snapshot};
+    use crate::monitor::tree::build_tree;
+
+    #[test]
+    fn test_process_changes() {
+        let mut before = sample_snapshot();
+        before.process_tree = build_tree(&[
+            process(1, "init", 0.0, 10.0),
+            process(42, "cargo", 5.0, 200.0),
+            process(50, "old", 1.0, 30.0),
+            process(60, "reused", 1.0, 30.0),
+        ]);
+        let mut after = sample_snapshot();
+        after.timestamp_ms += 60_000;
+        after.process_tree = build_tree(&[
+            process(1, "init", 0.0, 10.5),
+            process(42, "cargo", 30.0, 256.0),
+            process(60, "nginx", 2.0, 50.0),
+        ]);
+
+        let diff = SnapshotDiff::new(&before, &after);
+        assert!(diff.complete_process_lists);
+        assert_eq!(diff.elapsed_secs(), 60.0);
+        assert_eq!(
+            diff.appeared.iter().map(|p| &p.name).collect::<Vec<_>>(),
+            ["nginx"]
+        );
+        assert_eq!(
+            diff.disappeared.iter().map(|p| &p.name).collect::<Vec<_>>(),
+            ["old", "reused"]
+        );
+        // init grew by less than a megabyte
+        assert_eq!(diff.grown.len(), 1);
+        assert_eq!(diff.grown[0].memory_mb, Change::new(200.0, 256.0));
+        assert_eq!(diff.grown[0].cpu_usage.delta, 25.0);
+
+        // Without trees only the top lists are compared
+        let diff = SnapshotDiff::new(&sample_snapshot(), &sample_snapshot());
+        assert!(!diff.complete_process_lists);
+        assert!(diff.appeared.is_empty() && diff.disappeared.is_empty());
+    }
+
+    #[test]
+    fn test_disks_and_network() {
+        let before = sample_snapshot();
+        let mut after = sample_snapshot();
+        after.disks[0].used_gb = 70.0;
+        after.disks[0].percentage = 70.0;
+        after.network.interfaces[0].total_received += 4096;
+        after.network.interfaces[0].total_transmitted += 1024;
+
+        let diff = SnapshotDiff::new(&before, &after);
+        assert_eq!(diff.filled.len(), 1);
+        assert_eq!(diff.filled[0].mount_point, "/");
+        assert_eq!(diff.filled[0].used_gb.delta, 10.0);
+        assert_eq!(diff.network.received, 4096);
+        assert_eq!(diff.network.transmitted, 1024);
+        assert!(!diff.network.counters_reset);
+
+        // Counters start over after a reboot
+        let diff = SnapshotDiff::new(&after, &before);
+        assert!(diff.filled.is_empty());
+        assert!(diff.network.counters_reset);
+        assert_eq!(diff.network.received, 0);
+    }
+
+    #[test]
+    fn test_network_by_interface() {
+        let interface = |name: &str, total: u64| InterfaceInfo {
+            name: name.to_string(),
+            download_speed: 0.0,
+            upload_speed: 0.0,
+            total_received: total,
+            total_transmitted: total,
+        };
+        let mut before = sample_snapshot();
+        before.network =
+            NetworkInfo::from_interfaces(vec![interface("eth0", 1000), interface("wlan0", 5000)]);
+        let mut after = sample_snapshot();
+        after.network =
+            NetworkInfo::from_interfaces(vec![interface("eth0", 3000), interface("tun0", 100)]);
+
+        // wlan0 was removed and tun0 attached, neither is a reset
+        let diff = SnapshotDiff::new(&before, &after);
+        assert!(!diff.network.counters_reset);
+        assert_eq!(diff.network.received, 2000);
+        assert_eq!(diff.network.interfaces.len(), 1);
+        assert_eq!(diff.network.interfaces[0].name, "eth0");
+
+        // Snapshots saved without interfaces compare the totals
+        before.network.interfaces.clear();
+        let diff = SnapshotDiff::new(&before, &after);
+        assert!(diff.network.counters_reset);
+        assert!(diff.network.interfaces.is_empty());
+    }
+}
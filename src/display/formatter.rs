@@ -1,24 +1,23 @@
 // Formatter module - Visual output formatting utilities
 // Provides functions to display system information with colors
 
+use super::sparkline::Trends;
+use super::style::{paint, Style};
+use crate::diff::SnapshotDiff;
+use crate::monitor::cpu::CpuInfo;
+use crate::monitor::detail::ProcessDetail;
+use crate::monitor::disk::DiskInfo;
+use crate::monitor::memory::MemoryInfo;
 use crate::monitor::network::NetworkInfo;
-use crate::monitor::system::SystemMonitor;
+use crate::monitor::process::ProcessInfo;
+use crate::monitor::tree::{self, ProcessNode};
 use colored::*;
+use std::collections::HashSet;
 use std::io::{self, Write};
+use std::time::Duration;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-/// Get color based on percentage (0-100)
-fn get_color_for_percentage(percentage: f32) -> Color {
-    if percentage < 30.0 {
-        Color::Green
-    } else if percentage < 70.0 {
-        Color::Yellow
-    } else {
-        Color::Red
-    }
-}
-
 /// Clear the terminal screen
 /// Platform-specific implementation for best results
 pub fn clear_screen() {
@@ -44,108 +43,140 @@ pub fn clear_screen() {
 /// Print the header banner
 ///
 /// # Arguments
+/// * `out` - Destination writer
 /// * `watch_mode` - Whether watch mode is enabled
 /// * `interval` - Update interval in seconds (only shown in watch mode)
-pub fn print_header(watch_mode: bool, interval: u64) {
-    println!("╭─────────────────────────────────────────────────────────╮");
+pub fn print_header<W: Write>(out: &mut W, watch_mode: bool, interval: u64) -> io::Result<()> {
+    writeln!(
+        out,
+        "╭─────────────────────────────────────────────────────────╮"
+    )?;
     if watch_mode {
-        println!(
+        writeln!(
+            out,
             "│   System Monitor v{} (Watch)                    │",
             VERSION
-        );
-        println!(
+        )?;
+        writeln!(
+            out,
             "│   Updating every {} second(s)...                       │",
             interval
-        );
-        println!("│   Press Ctrl+C to exit                              │");
+        )?;
+        writeln!(
+            out,
+            "│   Press Ctrl+C to exit                              │"
+        )?;
     } else {
-        println!(
+        writeln!(
+            out,
             "│   System Monitor v{}                              │",
             VERSION
-        );
+        )?;
     }
-    println!("╰─────────────────────────────────────────────────────────╯");
-    println!();
+    writeln!(
+        out,
+        "╰─────────────────────────────────────────────────────────╯"
+    )?;
+    writeln!(out)
 }
 
 /// Print CPU information with visual bars and colors
 ///
 /// # Arguments
-/// * `monitor` - System monitor instance
+/// * `out` - Destination writer
+/// * `cpu_info` - CPU usage (global and per core)
 /// * `detailed` - If true, shows all cores; if false, shows top 3
-pub fn print_cpu_info(monitor: &SystemMonitor, detailed: bool) {
-    let cpu_info = monitor.cpu_info();
-    let cpu_count = monitor.cpu_count();
+/// * `style` - Colors and thresholds
+/// * `trends` - Recent values, drawn as a sparkline after the bar (watch mode)
+pub fn print_cpu_info<W: Write>(
+    out: &mut W,
+    cpu_info: &CpuInfo,
+    detailed: bool,
+    style: &Style,
+    trends: Option<&Trends>,
+) -> io::Result<()> {
     let bar = create_bar(cpu_info.global_usage, 20);
-    let color = get_color_for_percentage(cpu_info.global_usage);
-
-    println!(
-        "CPU:  {} ({} cores)  {}",
-        format!("{:.1}%", cpu_info.global_usage).color(color).bold(),
-        cpu_count,
-        bar
-    );
+    let color = style.color_for(&style.cpu, cpu_info.global_usage);
+    let trend = trends.map(|t| trend_suffix(&t.percent_line(&t.cpu), color));
+
+    writeln!(
+        out,
+        "CPU:  {} ({} cores)  {}{}",
+        paint(&format!("{:.1}%", cpu_info.global_usage), color).bold(),
+        cpu_info.cores.len(),
+        bar,
+        trend.unwrap_or_default()
+    )?;
 
     if detailed {
         // Show all cores
         for core in &cpu_info.cores {
             let core_bar = create_bar(core.usage, 15);
-            let core_color = get_color_for_percentage(core.usage);
-            println!(
+            let core_color = style.color_for(&style.cpu, core.usage);
+            writeln!(
+                out,
                 "  Core {:2}:  {}  {}",
                 core.index,
-                format!("{:5.1}%", core.usage).color(core_color),
+                paint(&format!("{:5.1}%", core.usage), core_color),
                 core_bar
-            );
+            )?;
         }
     } else {
         // Show only top 3 busiest cores
-        let top_cores = monitor.top_cpu_cores(3);
+        let top_cores = cpu_info.top_cores(3);
         if !top_cores.is_empty() {
-            print!("  Top 3:");
-            for (index, usage) in top_cores {
-                let core_color = get_color_for_percentage(usage);
-                print!(
+            write!(out, "  Top 3:")?;
+            for core in top_cores {
+                let core_color = style.color_for(&style.cpu, core.usage);
+                write!(
+                    out,
                     " Core {} ({})",
-                    index,
-                    format!("{:.0}%", usage).color(core_color)
-                );
+                    core.index,
+                    paint(&format!("{:.0}%", core.usage), core_color)
+                )?;
             }
-            println!();
+            writeln!(out)?;
         }
     }
-    println!();
+    writeln!(out)
 }
 
 /// Print memory information with visual bar and colors
-pub fn print_memory_info(monitor: &SystemMonitor) {
-    let mem_info = monitor.memory_info();
+///
+/// `trends` adds a sparkline of the recent usage after the bar.
+pub fn print_memory_info<W: Write>(
+    out: &mut W,
+    mem_info: &MemoryInfo,
+    style: &Style,
+    trends: Option<&Trends>,
+) -> io::Result<()> {
     let bar = create_bar(mem_info.percentage as f32, 20);
-    let color = get_color_for_percentage(mem_info.percentage as f32);
+    let color = style.color_for(&style.memory, mem_info.percentage as f32);
+    let trend = trends.map(|t| trend_suffix(&t.percent_line(&t.memory), color));
 
-    println!(
-        "Memory:  {}/{:.2} GB ({})  {}",
-        format!("{:.2}", mem_info.used_gb).color(color).bold(),
+    writeln!(
+        out,
+        "Memory:  {}/{:.2} GB ({})  {}{}",
+        paint(&format!("{:.2}", mem_info.used_gb), color).bold(),
         mem_info.total_gb,
-        format!("{:.1}%", mem_info.percentage).color(color),
-        bar
-    );
-    println!();
+        paint(&format!("{:.1}%", mem_info.percentage), color),
+        bar,
+        trend.unwrap_or_default()
+    )?;
+    writeln!(out)
 }
 
 /// Print disk usage information with colors
-pub fn print_disk_info(monitor: &SystemMonitor) {
-    let disks = monitor.disks_info();
-
+pub fn print_disk_info<W: Write>(out: &mut W, disks: &[DiskInfo], style: &Style) -> io::Result<()> {
     if disks.is_empty() {
-        return;
+        return Ok(());
     }
 
-    println!("{}", "Disk Usage:".bright_cyan().bold());
+    writeln!(out, "{}", style.heading("Disk Usage:"))?;
 
     for disk in disks {
         let bar = create_bar(disk.percentage as f32, 15);
-        let color = get_color_for_percentage(disk.percentage as f32);
+        let color = style.color_for(&style.disk, disk.percentage as f32);
 
         let mount = if disk.mount_point.len() > 8 {
             format!("{}...", &disk.mount_point[..5])
@@ -153,176 +184,567 @@ pub fn print_disk_info(monitor: &SystemMonitor) {
             disk.mount_point.clone()
         };
 
-        println!(
+        writeln!(
+            out,
             "  {:8} {:6.1}/{:6.1} GB ({})  {}",
             mount,
             disk.used_gb,
             disk.total_gb,
-            format!("{:5.1}%", disk.percentage).color(color),
+            paint(&format!("{:5.1}%", disk.percentage), color),
             bar
-        );
+        )?;
     }
-    println!();
+    writeln!(out)
 }
 
 /// Print network statistics with colors
-pub fn print_network_info(monitor: &mut SystemMonitor) {
-    let net_info = monitor.network_info();
-
-    println!("{}", "Network:".bright_cyan().bold());
+///
+/// # Arguments
+/// * `out` - Destination writer
+/// * `net_info` - Traffic of the selected interfaces
+/// * `detailed` - If true, adds a row per interface
+/// * `style` - Colors and thresholds
+/// * `trends` - Recent speeds, drawn as sparklines each scaled to its peak
+pub fn print_network_info<W: Write>(
+    out: &mut W,
+    net_info: &NetworkInfo,
+    detailed: bool,
+    style: &Style,
+    trends: Option<&Trends>,
+) -> io::Result<()> {
+    writeln!(out, "{}", style.heading("Network:"))?;
 
     // Download speed
-    let (dl_value, dl_unit) = NetworkInfo::format_speed(net_info.download_speed);
+    let (dl_value, dl_unit) = NetworkInfo::format_speed(net_info.selected_download_speed);
     let dl_color = if dl_value > 10.0 {
-        Color::Green
+        style.theme.ok
     } else {
-        Color::White
+        style.theme.idle
     };
-    println!(
-        "  ↓ Download: {}",
-        format!("{:.1} {}", dl_value, dl_unit)
-            .color(dl_color)
-            .bold()
-    );
+    let dl_text = format!("{:.1} {}", dl_value, dl_unit);
+    match trends {
+        Some(t) => writeln!(
+            out,
+            "  ↓ Download: {}{}",
+            paint(&format!("{:<10}", dl_text), dl_color).bold(),
+            trend_suffix(&t.rate_line(&t.download), dl_color)
+        )?,
+        None => writeln!(out, "  ↓ Download: {}", paint(&dl_text, dl_color).bold())?,
+    }
 
     // Upload speed
-    let (ul_value, ul_unit) = NetworkInfo::format_speed(net_info.upload_speed);
+    let (ul_value, ul_unit) = NetworkInfo::format_speed(net_info.selected_upload_speed);
     let ul_color = if ul_value > 1.0 {
-        Color::Green
+        style.theme.ok
     } else {
-        Color::White
+        style.theme.idle
     };
-    println!(
-        "  ↑ Upload:   {}",
-        format!("{:.1} {}", ul_value, ul_unit)
-            .color(ul_color)
-            .bold()
-    );
+    let ul_text = format!("{:.1} {}", ul_value, ul_unit);
+    match trends {
+        Some(t) => writeln!(
+            out,
+            "  ↑ Upload:   {}{}",
+            paint(&format!("{:<10}", ul_text), ul_color).bold(),
+            trend_suffix(&t.rate_line(&t.upload), ul_color)
+        )?,
+        None => writeln!(out, "  ↑ Upload:   {}", paint(&ul_text, ul_color).bold())?,
+    }
 
     // Total received
-    let (rx_value, rx_unit) = NetworkInfo::format_bytes(net_info.total_received);
-    println!("  Total RX:   {:.2} {}", rx_value, rx_unit);
+    let (rx_value, rx_unit) = NetworkInfo::format_bytes(net_info.selected_received);
+    writeln!(out, "  Total RX:   {:.2} {}", rx_value, rx_unit)?;
 
     // Total transmitted
-    let (tx_value, tx_unit) = NetworkInfo::format_bytes(net_info.total_transmitted);
-    println!("  Total TX:   {:.2} {}", tx_value, tx_unit);
+    let (tx_value, tx_unit) = NetworkInfo::format_bytes(net_info.selected_transmitted);
+    writeln!(out, "  Total TX:   {:.2} {}", tx_value, tx_unit)?;
+
+    if detailed {
+        for interface in &net_info.interfaces {
+            let (dl_value, dl_unit) = NetworkInfo::format_speed(interface.download_speed);
+            let (ul_value, ul_unit) = NetworkInfo::format_speed(interface.upload_speed);
+            let (rx_value, rx_unit) = NetworkInfo::format_bytes(interface.total_received);
+            let (tx_value, tx_unit) = NetworkInfo::format_bytes(interface.total_transmitted);
+            let color = if interface.download_speed > 0.0 || interface.upload_speed > 0.0 {
+                style.theme.ok
+            } else {
+                style.theme.idle
+            };
+            let dl_text = format!("{:.1} {}", dl_value, dl_unit);
+            let ul_text = format!("{:.1} {}", ul_value, ul_unit);
+            let rx_text = format!("{:.2} {}", rx_value, rx_unit);
+            writeln!(
+                out,
+                "  {:<12} ↓ {} ↑ {} RX {:<10} TX {:.2} {}",
+                truncate_string(&interface.name, 12),
+                paint(&format!("{:<11}", dl_text), color),
+                paint(&format!("{:<11}", ul_text), color),
+                rx_text,
+                tx_value,
+                tx_unit
+            )?;
+        }
+    }
 
-    println!();
+    writeln!(out)
 }
 
 /// Print top processes by CPU usage with colors
 ///
 /// # Arguments
-/// * `monitor` - System monitor instance
-/// * `detailed` - If true, shows 10 processes; if false, shows 5
-pub fn print_top_processes_cpu(monitor: &SystemMonitor, detailed: bool) {
-    let count = if detailed { 10 } else { 5 };
-    let processes = monitor.top_processes_by_cpu(count);
-
+/// * `out` - Destination writer
+/// * `processes` - Processes already sorted by CPU usage
+/// * `style` - Colors and thresholds
+pub fn print_top_processes_cpu<W: Write>(
+    out: &mut W,
+    processes: &[ProcessInfo],
+    style: &Style,
+) -> io::Result<()> {
     if processes.is_empty() {
-        return;
+        return Ok(());
     }
 
-    println!(
+    writeln!(
+        out,
         "{}",
-        format!("Top {} Processes (by CPU):", count)
-            .bright_cyan()
-            .bold()
-    );
+        style.heading(&format!("Top {} Processes (by CPU):", processes.len()))
+    )?;
 
     for (i, proc) in processes.iter().enumerate() {
-        let mem_str = if proc.memory_mb >= 1024.0 {
-            format!("{:.1} GB", proc.memory_mb / 1024.0)
-        } else {
-            format!("{:.0} MB", proc.memory_mb)
-        };
+        let mem_str = format_memory_mb(proc.memory_mb);
 
-        let cpu_color = get_color_for_percentage(proc.cpu_usage);
+        let cpu_color = style.color_for(&style.cpu, proc.cpu_usage);
 
-        println!(
+        writeln!(
+            out,
             "  {:2}. {:20}  PID {:5}  {}  {:>8}",
             i + 1,
             truncate_string(&proc.name, 20),
             proc.pid,
-            format!("{:5.1}%", proc.cpu_usage).color(cpu_color),
+            paint(&format!("{:5.1}%", proc.cpu_usage), cpu_color),
             mem_str
-        );
+        )?;
     }
-    println!();
+    writeln!(out)
 }
 
 /// Print top processes by memory usage with colors
 ///
 /// # Arguments
-/// * `monitor` - System monitor instance
-/// * `detailed` - If true, shows 5 processes; if false, shows 3
-pub fn print_top_processes_memory(monitor: &SystemMonitor, detailed: bool) {
-    let count = if detailed { 5 } else { 3 };
-    let processes = monitor.top_processes_by_memory(count);
-
+/// * `out` - Destination writer
+/// * `processes` - Processes already sorted by memory usage
+/// * `style` - Colors and thresholds
+pub fn print_top_processes_memory<W: Write>(
+    out: &mut W,
+    processes: &[ProcessInfo],
+    style: &Style,
+) -> io::Result<()> {
     if processes.is_empty() {
-        return;
+        return Ok(());
     }
 
-    println!(
+    writeln!(
+        out,
         "{}",
-        format!("Top {} Processes (by Memory):", count)
-            .bright_cyan()
-            .bold()
-    );
+        style.heading(&format!("Top {} Processes (by Memory):", processes.len()))
+    )?;
 
     for (i, proc) in processes.iter().enumerate() {
-        let mem_str = if proc.memory_mb >= 1024.0 {
-            format!("{:.1} GB", proc.memory_mb / 1024.0)
-        } else {
-            format!("{:.0} MB", proc.memory_mb)
-        };
+        let mem_str = format_memory_mb(proc.memory_mb);
 
-        let mem_color = if proc.memory_mb >= 2048.0 {
-            Color::Red
-        } else if proc.memory_mb >= 512.0 {
-            Color::Yellow
-        } else {
-            Color::Green
-        };
+        let mem_color = style.color_for(&style.process_memory, proc.memory_mb as f32);
 
-        println!(
+        writeln!(
+            out,
             "  {:2}. {:20}  PID {:5}  {:5.1}%  {}",
             i + 1,
             truncate_string(&proc.name, 20),
             proc.pid,
             proc.cpu_usage,
-            mem_str.color(mem_color).bold()
-        );
+            paint(&mem_str, mem_color).bold()
+        )?;
     }
-    println!();
+    writeln!(out)
+}
+
+/// Print every process as a tree, with subtree totals for parents
+///
+/// # Arguments
+/// * `out` - Destination writer
+/// * `nodes` - Root nodes of the process tree
+/// * `style` - Colors and thresholds
+pub fn print_process_tree<W: Write>(
+    out: &mut W,
+    nodes: &[ProcessNode],
+    style: &Style,
+) -> io::Result<()> {
+    if nodes.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(out, "{}", style.heading("Process Tree:"))?;
+    writeln!(
+        out,
+        "  {:>7}  {:>6}  {:>8}  {:>7}  {:>8}  NAME",
+        "PID", "CPU%", "MEM", "TREE%", "TREE MEM"
+    )?;
+
+    for row in tree::flatten(nodes, &HashSet::new()) {
+        let process = &row.node.process;
+
+        // Totals only differ from the process values when it has children
+        let (total_cpu, total_mem) = if row.node.children.is_empty() {
+            (String::new(), String::new())
+        } else {
+            (
+                format!("{:.1}%", row.node.total_cpu_usage),
+                format_memory_mb(row.node.total_memory_mb),
+            )
+        };
+
+        writeln!(
+            out,
+            "  {:>7}  {}  {:>8}  {:>7}  {:>8}  {}{}",
+            process.pid,
+            paint(
+                &format!("{:5.1}%", process.cpu_usage),
+                style.color_for(&style.cpu, process.cpu_usage)
+            ),
+            format_memory_mb(process.memory_mb),
+            total_cpu,
+            total_mem,
+            style.muted(&row.prefix),
+            process.name
+        )?;
+    }
+    writeln!(out)
+}
+
+/// Print everything known about one process
+///
+/// # Arguments
+/// * `out` - Destination writer
+/// * `detail` - Process details
+/// * `style` - Colors and thresholds
+pub fn print_process_detail<W: Write>(
+    out: &mut W,
+    detail: &ProcessDetail,
+    style: &Style,
+) -> io::Result<()> {
+    const MB: f64 = 1_048_576.0; // 1024^2
+    let unavailable = || style.muted("unavailable").to_string();
+
+    writeln!(
+        out,
+        "{}",
+        style.heading(&format!("Process {}: {}", detail.pid, detail.name))
+    )?;
+
+    let command = if detail.command_line.is_empty() {
+        unavailable()
+    } else {
+        detail.command_line.join(" ")
+    };
+    let environment = if detail.environment_vars == 0 {
+        unavailable()
+    } else {
+        format!(
+            "{} variables ({:.1} KB)",
+            detail.environment_vars,
+            detail.environment_bytes as f64 / 1024.0
+        )
+    };
+    let cpu_time = match detail.cpu_time_secs {
+        Some(secs) => format!("{} total", format_duration(secs as u64)),
+        None => unavailable(),
+    };
+
+    let rows = [
+        ("Command", command),
+        (
+            "Executable",
+            detail.executable.clone().unwrap_or_else(unavailable),
+        ),
+        (
+            "Working dir",
+            detail.working_directory.clone().unwrap_or_else(unavailable),
+        ),
+        ("Environment", environment),
+        ("User", detail.user.clone().unwrap_or_else(unavailable)),
+        (
+            "Parent PID",
+            detail
+                .parent_pid
+                .map(|pid| pid.to_string())
+                .unwrap_or_else(unavailable),
+        ),
+        ("State", detail.state.clone()),
+        (
+            "Threads",
+            detail
+                .threads
+                .map(|n| n.to_string())
+                .unwrap_or_else(unavailable),
+        ),
+        (
+            "Started",
+            format!(
+                "{} (running for {})",
+                format_utc(detail.start_time),
+                format_duration(detail.run_time_secs)
+            ),
+        ),
+        (
+            "CPU",
+            format!(
+                "{}  {}",
+                paint(
+                    &format!("{:.1}% now", detail.cpu_usage),
+                    style.color_for(&style.cpu, detail.cpu_usage)
+                ),
+                cpu_time
+            ),
+        ),
+        (
+            "Memory",
+            format!(
+                "{} resident, {} virtual",
+                format_memory_mb(detail.resident_memory_bytes as f64 / MB),
+                format_memory_mb(detail.virtual_memory_bytes as f64 / MB)
+            ),
+        ),
+        (
+            "Open files",
+            detail
+                .open_files
+                .map(|n| n.to_string())
+                .unwrap_or_else(unavailable),
+        ),
+    ];
+
+    for (label, value) in rows {
+        writeln!(out, "  {:12} {}", format!("{}:", label), value)?;
+    }
+    writeln!(out)
+}
+
+/// Print what changed between two snapshots
+///
+/// # Arguments
+/// * `out` - Destination writer
+/// * `diff` - Comparison of the two snapshots
+/// * `style` - Colors and thresholds
+pub fn print_snapshot_diff<W: Write>(
+    out: &mut W,
+    diff: &SnapshotDiff,
+    style: &Style,
+) -> io::Result<()> {
+    let none = || style.muted("  none").to_string();
+
+    writeln!(
+        out,
+        "{}",
+        style.heading(&format!(
+            "Snapshot Diff: {} → {} ({})",
+            format_utc(diff.before_ms / 1000),
+            format_utc(diff.after_ms / 1000),
+            format_duration(diff.elapsed_secs() as u64)
+        ))
+    )?;
+    for (label, change, thresholds) in [
+        ("CPU:", diff.cpu_usage, &style.cpu),
+        ("Memory:", diff.memory_percentage, &style.memory),
+    ] {
+        writeln!(
+            out,
+            "  {:8}{:.1}% → {} ({:+.1})",
+            label,
+            change.before,
+            paint(
+                &format!("{:.1}%", change.after),
+                style.color_for(thresholds, change.after as f32)
+            ),
+            // Keep tiny drops from showing as "-0.0"
+            (change.delta * 10.0).round() / 10.0 + 0.0
+        )?;
+    }
+    writeln!(out)?;
+
+    if diff.complete_process_lists {
+        writeln!(
+            out,
+            "{}",
+            style.heading(&format!("Processes Appeared ({}):", diff.appeared.len()))
+        )?;
+        for proc in &diff.appeared {
+            writeln!(
+                out,
+                "  {} {:20}  PID {:5}  {:5.1}%  {:>8}",
+                paint("+", style.theme.ok).bold(),
+                truncate_string(&proc.name, 20),
+                proc.pid,
+                proc.cpu_usage,
+                format_memory_mb(proc.memory_mb)
+            )?;
+        }
+        if diff.appeared.is_empty() {
+            writeln!(out, "{}", none())?;
+        }
+
+        writeln!(
+            out,
+            "{}",
+            style.heading(&format!(
+                "Processes Disappeared ({}):",
+                diff.disappeared.len()
+            ))
+        )?;
+        for proc in &diff.disappeared {
+            writeln!(
+                out,
+                "  {} {:20}  PID {:5}  {:5.1}%  {:>8}",
+                paint("-", style.theme.critical).bold(),
+                truncate_string(&proc.name, 20),
+                proc.pid,
+                proc.cpu_usage,
+                format_memory_mb(proc.memory_mb)
+            )?;
+        }
+        if diff.disappeared.is_empty() {
+            writeln!(out, "{}", none())?;
+        }
+    } else {
+        writeln!(
+            out,
+            "{}",
+            style.muted(
+                "Only the top processes were compared; save both snapshots with --tree \
+                 to see processes that appeared or disappeared"
+            )
+        )?;
+    }
+
+    writeln!(
+        out,
+        "{}",
+        style.heading(&format!("Processes Grown ({}):", diff.grown.len()))
+    )?;
+    for change in &diff.grown {
+        let memory_delta = format!("+{}", format_memory_mb(change.memory_mb.delta.max(0.0)));
+        writeln!(
+            out,
+            "    {:20}  PID {:5}  CPU {:5.1}% → {}  Memory {} → {} ({})",
+            truncate_string(&change.name, 20),
+            change.pid,
+            change.cpu_usage.before,
+            paint(
+                &format!("{:5.1}%", change.cpu_usage.after),
+                style.color_for(&style.cpu, change.cpu_usage.after as f32)
+            ),
+            format_memory_mb(change.memory_mb.before),
+            format_memory_mb(change.memory_mb.after),
+            paint(&memory_delta, style.theme.warning)
+        )?;
+    }
+    if diff.grown.is_empty() {
+        writeln!(out, "{}", none())?;
+    }
+    writeln!(out)?;
+
+    writeln!(
+        out,
+        "{}",
+        style.heading(&format!("Disks Filled ({}):", diff.filled.len()))
+    )?;
+    for disk in &diff.filled {
+        writeln!(
+            out,
+            "  {:12} {:5.1}% → {}  ({})",
+            disk.mount_point,
+            disk.percentage.before,
+            paint(
+                &format!("{:5.1}%", disk.percentage.after),
+                style.color_for(&style.disk, disk.percentage.after as f32)
+            ),
+            paint(
+                &format!("+{:.1} GB", disk.used_gb.delta),
+                style.theme.warning
+            )
+        )?;
+    }
+    if diff.filled.is_empty() {
+        writeln!(out, "{}", none())?;
+    }
+    writeln!(out)?;
+
+    writeln!(out, "{}", style.heading("Network:"))?;
+    let reset: Vec<&str> = diff
+        .network
+        .interfaces
+        .iter()
+        .filter(|i| i.counters_reset)
+        .map(|i| i.name.as_str())
+        .collect();
+    if diff.network.counters_reset && reset.len() == diff.network.interfaces.len() {
+        writeln!(
+            out,
+            "{}",
+            style.muted("  Counters were reset (e.g. by a reboot), traffic unknown")
+        )?;
+        return Ok(());
+    }
+    if !reset.is_empty() {
+        let note = format!("  Counters of {} were reset, not counted", reset.join(", "));
+        writeln!(out, "{}", style.muted(&note))?;
+    }
+    let elapsed = diff.elapsed_secs();
+    for (label, bytes) in [
+        ("RX:", diff.network.received),
+        ("TX:", diff.network.transmitted),
+    ] {
+        let (value, unit) = NetworkInfo::format_bytes(bytes);
+        write!(out, "  {:4}{:.2} {}", label, value, unit)?;
+        if elapsed > 0.0 {
+            let (speed, speed_unit) = NetworkInfo::format_speed(bytes as f64 / elapsed);
+            write!(
+                out,
+                " {}",
+                style.muted(&format!("({:.1} {} average)", speed, speed_unit))
+            )?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
 }
 
 /// Print system uptime
-pub fn print_uptime(monitor: &SystemMonitor) {
-    let uptime = monitor.uptime();
+pub fn print_uptime<W: Write>(out: &mut W, uptime: u64) -> io::Result<()> {
     let days = uptime / 86400;
     let hours = (uptime % 86400) / 3600;
     let minutes = (uptime % 3600) / 60;
 
-    println!(
+    writeln!(
+        out,
         "Uptime: {} days, {} hours, {} minutes",
         days, hours, minutes
-    );
+    )
 }
 
 /// Print the footer
 ///
 /// # Arguments
+/// * `out` - Destination writer
 /// * `watch_mode` - Whether watch mode is enabled
-pub fn print_footer(watch_mode: bool) {
+/// * `style` - Colors of the hint
+pub fn print_footer<W: Write>(out: &mut W, watch_mode: bool, style: &Style) -> io::Result<()> {
     if !watch_mode {
-        println!();
-        println!(
+        writeln!(out)?;
+        writeln!(
+            out,
             "{}",
-            "Run with --watch for continuous monitoring".bright_black()
-        );
+            style.muted("Run with --watch for continuous monitoring")
+        )?;
     }
+    Ok(())
 }
 
 /// Create a visual progress bar
@@ -340,10 +762,107 @@ fn create_bar(percentage: f32, width: usize) -> String {
     format!("[{}{}]", "█".repeat(filled), "░".repeat(empty))
 }
 
-/// Truncate string to max length
-fn truncate_string(s: &str, max_len: usize) -> String {
-    if s.len() > max_len {
-        format!("{}...", &s[..max_len - 3])
+/// Sparkline appended to a metric line (two spaces, then the graph)
+fn trend_suffix(line: &str, color: Option<Color>) -> String {
+    format!("  {}", paint(line, color))
+}
+
+/// Format a process memory size as MB, or GB from 1024 MB up
+pub fn format_memory_mb(memory_mb: f64) -> String {
+    if memory_mb >= 1024.0 {
+        format!("{:.1} GB", memory_mb / 1024.0)
+    } else {
+        format!("{:.0} MB", memory_mb)
+    }
+}
+
+/// Format a duration as e.g. "2d 3h 4m", "3h 4m 5s" or "45s"
+pub fn format_duration(secs: u64) -> String {
+    let days = secs / 86400;
+    let hours = (secs % 86400) / 3600;
+    let minutes = (secs % 3600) / 60;
+    let seconds = secs % 60;
+
+    if days > 0 {
+        format!("{}d {}h {}m", days, hours, minutes)
+    } else if hours > 0 {
+        format!("{}h {}m {}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+/// Parse a duration like "30s", "5m", "1h30m", "2d" or "500ms"
+///
+/// A bare number is taken as seconds.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Err("empty duration".to_string());
+    }
+    if let Ok(secs) = s.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let invalid = || format!("invalid duration '{}' (e.g. 30s, 5m, 1h30m, 2d)", s);
+    let mut total = Duration::ZERO;
+    let mut rest = s;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let unit_len = rest[digits..]
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len() - digits);
+        let number: u64 = rest[..digits].parse().map_err(|_| invalid())?;
+        let unit = match &rest[digits..digits + unit_len] {
+            "ms" => Duration::from_millis(1),
+            "s" => Duration::from_secs(1),
+            "m" => Duration::from_secs(60),
+            "h" => Duration::from_secs(3600),
+            "d" => Duration::from_secs(86400),
+            _ => return Err(invalid()),
+        };
+        total += unit * u32::try_from(number).map_err(|_| invalid())?;
+        rest = &rest[digits + unit_len..];
+    }
+    Ok(total)
+}
+
+/// Format seconds since the Unix epoch as "YYYY-MM-DD HH:MM:SS UTC"
+pub fn format_utc(epoch_secs: u64) -> String {
+    let days = (epoch_secs / 86400) as i64;
+    let secs = epoch_secs % 86400;
+
+    // Civil date from day count (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        secs / 3600,
+        (secs % 3600) / 60,
+        secs % 60
+    )
+}
+
+/// Truncate string to max length (in characters), ending with "..."
+pub fn truncate_string(s: &str, max_len: usize) -> String {
+    if s.chars().count() > max_len {
+        let kept: String = s.chars().take(max_len.saturating_sub(3)).collect();
+        format!("{}...", kept)
     } else {
         s.to_string()
     }
@@ -352,6 +871,7 @@ fn truncate_string(s: &str, max_len: usize) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::display::style::Thresholds;
 
     #[test]
     fn test_create_bar_full() {
@@ -373,16 +893,99 @@ mod tests {
 
     #[test]
     fn test_get_color_green() {
-        assert_eq!(get_color_for_percentage(20.0), Color::Green);
+        let style = Style::default();
+        assert_eq!(style.color_for(&style.cpu, 20.0), Some(Color::Green));
     }
 
     #[test]
     fn test_get_color_yellow() {
-        assert_eq!(get_color_for_percentage(50.0), Color::Yellow);
+        let style = Style::default();
+        assert_eq!(style.color_for(&style.cpu, 50.0), Some(Color::Yellow));
     }
 
     #[test]
     fn test_get_color_red() {
-        assert_eq!(get_color_for_percentage(85.0), Color::Red);
+        let style = Style::default();
+        assert_eq!(style.color_for(&style.cpu, 85.0), Some(Color::Red));
+    }
+
+    #[test]
+    fn test_disk_thresholds_override() {
+        let style = Style {
+            disk: Thresholds {
+                warning: 80.0,
+                critical: 90.0,
+            },
+            ..Style::default()
+        };
+        assert_eq!(style.color_for(&style.disk, 75.0), Some(Color::Green));
+        assert_eq!(style.color_for(&style.memory, 75.0), Some(Color::Red));
+    }
+
+    #[test]
+    fn test_truncate_string_multibyte() {
+        assert_eq!(truncate_string("short", 20), "short");
+        assert_eq!(truncate_string("ééééééééé", 6), "ééé...");
+    }
+
+    #[test]
+    fn test_format_memory_mb() {
+        assert_eq!(format_memory_mb(256.0), "256 MB");
+        assert_eq!(format_memory_mb(1536.0), "1.5 GB");
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(45), "45s");
+        assert_eq!(format_duration(3725), "1h 2m 5s");
+        assert_eq!(format_duration(2 * 86400 + 3 * 3600 + 240), "2d 3h 4m");
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("2d"), Ok(Duration::from_secs(172_800)));
+        assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("5 minutes").is_err());
+        assert!(parse_duration("m").is_err());
+    }
+
+    #[test]
+    fn test_format_utc() {
+        assert_eq!(format_utc(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_utc(1_700_000_000), "2023-11-14 22:13:20 UTC");
+        assert_eq!(format_utc(951_782_400), "2000-02-29 00:00:00 UTC");
+    }
+
+    #[test]
+    fn test_print_snapshot_diff() {
+        let before = crate::monitor::snapshot::sample_snapshot();
+        let mut after = before.clone();
+        after.timestamp_ms += 90_000;
+        after.disks[0].used_gb = 62.5;
+        after.disks[0].percentage = 62.5;
+        after.network.interfaces[0].total_received += 90 * 1024;
+        after.top_processes_cpu[0].memory_mb = 300.0;
+
+        let mut out = Vec::new();
+        print_snapshot_diff(
+            &mut out,
+            &SnapshotDiff::new(&before, &after),
+            &Style::default(),
+        )
+        .unwrap();
+        let text = String::from_utf8(out).unwrap();
+
+        assert!(text.contains("(1m 30s)"));
+        assert!(text.contains("Only the top processes were compared"));
+        assert!(text.contains("Processes Grown (1):"));
+        assert!(text.contains("256 MB → 300 MB"));
+        assert!(text.contains("Disks Filled (1):"));
+        assert!(text.contains("+2.5 GB"));
+        assert!(text.contains("RX: 90.00 KB"));
+        assert!(text.contains("(1.0 KB/s average)"));
     }
 }
@@ -1,4 +1,9 @@
 // Monitor module - System information gathering
 // This module contains submodules for different system metrics
 
+/// Section printers for the colored terminal view
 pub mod formatter;
+/// Recent values drawn as sparklines
+pub mod sparkline;
+/// Colors and usage thresholds of the terminal view
+pub mod style;
@@ -0,0 +1,194 @@
+// Sparkline module - Recent values as a mini graph
+// Ring buffers of the last samples, drawn with block or braille characters
+
+use crate::monitor::snapshot::Snapshot;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Samples kept by default in watch and interactive mode
+pub const DEFAULT_SPARKLINE_LENGTH: usize = 30;
+
+/// Block characters from lowest to highest
+const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Braille dots of the left and right column, from the bottom up
+const BRAILLE_LEFT: [u32; 4] = [0x40, 0x04, 0x02, 0x01];
+const BRAILLE_RIGHT: [u32; 4] = [0x80, 0x20, 0x10, 0x08];
+
+/// Characters used to draw sparklines
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SparklineStyle {
+    /// One sample per character, eight levels (▁▂▃▄▅▆▇█)
+    #[default]
+    Block,
+    /// Two samples per character, four levels (⣀⣤⣶⣿)
+    Braille,
+}
+
+/// Ring buffer of the most recent values of one metric
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trend {
+    values: VecDeque<f64>,
+    capacity: usize,
+}
+
+impl Trend {
+    /// Create an empty buffer keeping up to `capacity` values
+    pub fn new(capacity: usize) -> Self {
+        Trend {
+            values: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Add a value, dropping the oldest one when full
+    pub fn push(&mut self, value: f64) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.values.len() == self.capacity {
+            self.values.pop_front();
+        }
+        self.values.push_back(value);
+    }
+
+    /// Values from oldest to newest
+    pub fn values(&self) -> impl Iterator<Item = f64> + '_ {
+        self.values.iter().copied()
+    }
+
+    /// Number of kept values
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether no value was recorded yet
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+/// Recent global CPU, memory and network values of a watch session
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trends {
+    /// Global CPU usage in percent
+    pub cpu: Trend,
+    /// Memory usage in percent
+    pub memory: Trend,
+    /// Download speed in bytes per second
+    pub download: Trend,
+    /// Upload speed in bytes per second
+    pub upload: Trend,
+    /// Characters used to draw the sparklines
+    pub style: SparklineStyle,
+}
+
+impl Trends {
+    /// Create empty buffers
+    ///
+    /// # Arguments
+    /// * `length` - Number of samples kept per metric
+    /// * `style` - Characters used to draw the sparklines
+    pub fn new(length: usize, style: SparklineStyle) -> Self {
+        Trends {
+            cpu: Trend::new(length),
+            memory: Trend::new(length),
+            download: Trend::new(length),
+            upload: Trend::new(length),
+            style,
+        }
+    }
+
+    /// Add the values of a snapshot
+    pub fn record(&mut self, snapshot: &Snapshot) {
+        self.cpu.push(snapshot.cpu.global_usage as f64);
+        self.memory.push(snapshot.memory.percentage);
+        self.download.push(snapshot.network.selected_download_speed);
+        self.upload.push(snapshot.network.selected_upload_speed);
+    }
+
+    /// Draw a percentage trend (scaled to 0-100%)
+    pub fn percent_line(&self, trend: &Trend) -> String {
+        sparkline(&trend.values().collect::<Vec<_>>(), 100.0, self.style)
+    }
+
+    /// Draw a rate trend (scaled to its own peak)
+    pub fn rate_line(&self, trend: &Trend) -> String {
+        let values: Vec<f64> = trend.values().collect();
+        let peak = values.iter().copied().fold(0.0, f64::max);
+        sparkline(&values, peak, self.style)
+    }
+}
+
+/// Draw values as a sparkline, newest on the right
+///
+/// # Arguments
+/// * `values` - Values from oldest to newest
+/// * `max` - Value drawn at full height (values are clamped to 0..=max)
+/// * `style` - Block or braille characters
+pub fn sparkline(values: &[f64], max: f64, style: SparklineStyle) -> String {
+    let level = |value: f64, levels: usize| -> usize {
+        if max <= 0.0 || !value.is_finite() {
+            return 0;
+        }
+        let fraction = (value / max).clamp(0.0, 1.0);
+        (fraction * (levels - 1) as f64).round() as usize
+    };
+
+    match style {
+        SparklineStyle::Block => values
+            .iter()
+            .map(|&value| BLOCKS[level(value, BLOCKS.len())])
+            .collect(),
+        SparklineStyle::Braille => {
+            // Bars 1-4 dots high; a leading odd sample gets its own cell
+            let height = |value: f64| 1 + level(value, 4);
+            let dots = |column: &[u32; 4], height: usize| -> u32 { column[..height].iter().sum() };
+
+            let (first, rest) = values.split_at(values.len() % 2);
+            first
+                .iter()
+                .map(|&value| dots(&BRAILLE_RIGHT, height(value)))
+                .chain(rest.chunks(2).map(|pair| {
+                    dots(&BRAILLE_LEFT, height(pair[0])) | dots(&BRAILLE_RIGHT, height(pair[1]))
+                }))
+                .map(|bits| char::from_u32(0x2800 + bits).unwrap_or(' '))
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_buffer() {
+        let mut trend = Trend::new(3);
+        for value in [1.0, 2.0, 3.0, 4.0] {
+            trend.push(value);
+        }
+        assert_eq!(trend.values().collect::<Vec<_>>(), [2.0, 3.0, 4.0]);
+        assert_eq!(trend.len(), 3);
+
+        let mut off = Trend::new(0);
+        off.push(1.0);
+        assert!(off.is_empty());
+    }
+
+    #[test]
+    fn test_sparkline() {
+        let values = [0.0, 50.0, 100.0, 150.0];
+        assert_eq!(sparkline(&values, 100.0, SparklineStyle::Block), "▁▅██");
+        assert_eq!(sparkline(&values, 0.0, SparklineStyle::Block), "▁▁▁▁");
+
+        // Two samples per cell: lowest|three dots, full|full
+        assert_eq!(sparkline(&values, 100.0, SparklineStyle::Braille), "⣰⣿");
+        // An odd sample on the left gets its own cell
+        assert_eq!(
+            sparkline(&values[1..], 100.0, SparklineStyle::Braille),
+            "⢰⣿"
+        );
+    }
+}
@@ -0,0 +1,291 @@
+// Style module - Colors of the terminal view
+// Themes and the usage thresholds that decide when values turn yellow or red
+
+use colored::{Color, ColoredString, Colorize};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Values at which a metric changes color
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Thresholds {
+    /// Values from here up are shown as a warning
+    pub warning: f32,
+    /// Values from here up are shown as critical
+    pub critical: f32,
+}
+
+impl Default for Thresholds {
+    /// 30% and 70%
+    fn default() -> Self {
+        Thresholds {
+            warning: 30.0,
+            critical: 70.0,
+        }
+    }
+}
+
+impl Thresholds {
+    /// Check that the warning threshold is not above the critical one
+    pub fn validate(&self) -> Result<(), String> {
+        if self.warning > self.critical {
+            return Err(format!(
+                "warning threshold ({}) is above the critical threshold ({})",
+                self.warning, self.critical
+            ));
+        }
+        Ok(())
+    }
+
+    /// Band a value falls into
+    pub fn level(&self, value: f32) -> Level {
+        if value < self.warning {
+            Level::Ok
+        } else if value < self.critical {
+            Level::Warning
+        } else {
+            Level::Critical
+        }
+    }
+}
+
+/// Severity of a value relative to its thresholds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    /// Below the warning threshold
+    Ok,
+    /// Between the warning and critical thresholds
+    Warning,
+    /// At or above the critical threshold
+    Critical,
+}
+
+/// Colors for every role in the terminal view
+///
+/// `None` leaves text in the terminal's default color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    /// Values below the warning threshold, active network traffic
+    pub ok: Option<Color>,
+    /// Values between the warning and critical thresholds, prompts
+    pub warning: Option<Color>,
+    /// Values at or above the critical threshold
+    pub critical: Option<Color>,
+    /// Section titles and table headers
+    pub heading: Option<Color>,
+    /// Hints, tree branches and unavailable values
+    pub muted: Option<Color>,
+    /// Idle network traffic
+    pub idle: Option<Color>,
+}
+
+/// Names of the built-in themes
+pub const BUILTIN_THEMES: [&str; 4] = ["default", "solarized", "high-contrast", "monochrome"];
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            ok: Some(Color::Green),
+            warning: Some(Color::Yellow),
+            critical: Some(Color::Red),
+            heading: Some(Color::BrightCyan),
+            muted: Some(Color::BrightBlack),
+            idle: Some(Color::White),
+        }
+    }
+}
+
+impl Theme {
+    /// Look up a built-in theme (see [`BUILTIN_THEMES`])
+    pub fn builtin(name: &str) -> Option<Theme> {
+        let rgb = |r, g, b| Some(Color::TrueColor { r, g, b });
+
+        match name {
+            "default" => Some(Theme::default()),
+            "solarized" => Some(Theme {
+                ok: rgb(0x85, 0x99, 0x00),
+                warning: rgb(0xb5, 0x89, 0x00),
+                critical: rgb(0xdc, 0x32, 0x2f),
+                heading: rgb(0x26, 0x8b, 0xd2),
+                muted: rgb(0x58, 0x6e, 0x75),
+                idle: rgb(0x93, 0xa1, 0xa1),
+            }),
+            "high-contrast" => Some(Theme {
+                ok: Some(Color::BrightGreen),
+                warning: Some(Color::BrightYellow),
+                critical: Some(Color::BrightRed),
+                heading: Some(Color::BrightWhite),
+                muted: Some(Color::White),
+                idle: Some(Color::BrightWhite),
+            }),
+            "monochrome" => Some(Theme {
+                ok: None,
+                warning: None,
+                critical: None,
+                heading: None,
+                muted: None,
+                idle: None,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Color of a severity level
+    pub fn level(&self, level: Level) -> Option<Color> {
+        match level {
+            Level::Ok => self.ok,
+            Level::Warning => self.warning,
+            Level::Critical => self.critical,
+        }
+    }
+}
+
+/// Parse a color name ("bright cyan"), "#rrggbb", or "none" for the default
+pub fn parse_color(s: &str) -> Result<Option<Color>, String> {
+    let s = s.trim();
+    if s.eq_ignore_ascii_case("none") {
+        return Ok(None);
+    }
+
+    if let Some(hex) = s.strip_prefix('#') {
+        let channel = |i: usize| {
+            hex.get(i..i + 2)
+                .and_then(|c| u8::from_str_radix(c, 16).ok())
+        };
+        return match (hex.len(), channel(0), channel(2), channel(4)) {
+            (6, Some(r), Some(g), Some(b)) => Ok(Some(Color::TrueColor { r, g, b })),
+            _ => Err(format!("invalid color '{}', expected #rrggbb", s)),
+        };
+    }
+
+    Color::from_str(s).map(Some).map_err(|()| {
+        format!(
+            "unknown color '{}' (use a name like \"red\" or \"bright cyan\", \"#rrggbb\" or \"none\")",
+            s
+        )
+    })
+}
+
+/// Color text, or leave it in the terminal's default color
+pub fn paint(text: &str, color: Option<Color>) -> ColoredString {
+    match color {
+        Some(color) => text.color(color),
+        None => text.normal(),
+    }
+}
+
+/// How the terminal view colors values
+#[derive(Debug, Clone, PartialEq)]
+pub struct Style {
+    /// Colors of the view
+    pub theme: Theme,
+    /// Thresholds for CPU usage (global, per core and per process) in percent
+    pub cpu: Thresholds,
+    /// Thresholds for memory usage in percent
+    pub memory: Thresholds,
+    /// Thresholds for disk usage in percent
+    pub disk: Thresholds,
+    /// Thresholds for the memory of a single process in MB
+    pub process_memory: Thresholds,
+}
+
+/// Default thresholds for the memory of a single process (512 MB and 2 GB)
+pub const PROCESS_MEMORY_THRESHOLDS: Thresholds = Thresholds {
+    warning: 512.0,
+    critical: 2048.0,
+};
+
+impl Default for Style {
+    fn default() -> Self {
+        Style {
+            theme: Theme::default(),
+            cpu: Thresholds::default(),
+            memory: Thresholds::default(),
+            disk: Thresholds::default(),
+            process_memory: PROCESS_MEMORY_THRESHOLDS,
+        }
+    }
+}
+
+impl Style {
+    /// Color for a value measured against some thresholds
+    ///
+    /// # Arguments
+    /// * `thresholds` - One of the style's thresholds (e.g. `&style.disk`)
+    /// * `value` - Measured value
+    pub fn color_for(&self, thresholds: &Thresholds, value: f32) -> Option<Color> {
+        self.theme.level(thresholds.level(value))
+    }
+
+    /// Format text as a section title
+    pub fn heading(&self, text: &str) -> ColoredString {
+        paint(text, self.theme.heading).bold()
+    }
+
+    /// Format text as a hint or unavailable value
+    pub fn muted(&self, text: &str) -> ColoredString {
+        paint(text, self.theme.muted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_thresholds() {
+        let thresholds = Thresholds {
+            warning: 80.0,
+            critical: 95.0,
+        };
+        assert_eq!(thresholds.level(50.0), Level::Ok);
+        assert_eq!(thresholds.level(80.0), Level::Warning);
+        assert_eq!(thresholds.level(99.0), Level::Critical);
+
+        assert!(thresholds.validate().is_ok());
+        let inverted = Thresholds {
+            warning: 90.0,
+            critical: 50.0,
+        };
+        assert!(inverted.validate().is_err());
+    }
+
+    #[test]
+    fn test_builtin_themes() {
+        for name in BUILTIN_THEMES {
+            assert!(Theme::builtin(name).is_some(), "{}", name);
+        }
+        assert_eq!(
+            Theme::builtin("monochrome").unwrap().level(Level::Critical),
+            None
+        );
+        assert_eq!(Theme::builtin("nope"), None);
+
+        let style = Style {
+            theme: Theme::builtin("monochrome").unwrap(),
+            ..Style::default()
+        };
+        assert_eq!(style.heading("Disk Usage:").fgcolor, None);
+        assert_eq!(
+            paint("12%", style.color_for(&style.disk, 12.0)).fgcolor,
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("Bright Cyan"), Ok(Some(Color::BrightCyan)));
+        assert_eq!(parse_color("none"), Ok(None));
+        assert_eq!(
+            parse_color("#268bd2"),
+            Ok(Some(Color::TrueColor {
+                r: 0x26,
+                g: 0x8b,
+                b: 0xd2
+            }))
+        );
+        assert!(parse_color("#12345").is_err());
+        assert!(parse_color("#gg0000").is_err());
+        assert!(parse_color("chartreuse").is_err());
+    }
+}
@@ -0,0 +1,491 @@
+// History module - Local metric storage
+// Append-only NDJSON files: raw samples and one-minute averages, each with its
+// own retention
+
+use crate::alert::Metric;
+use crate::monitor::snapshot::Snapshot;
+use crate::output::Sink;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// File with one sample per tick
+pub const RAW_FILE: &str = "raw.ndjson";
+/// File with one averaged sample per minute
+pub const ROLLUP_FILE: &str = "1m.ndjson";
+
+/// Length of a rollup bucket in milliseconds
+const ROLLUP_MS: u64 = 60_000;
+
+/// How often old samples are dropped while recording
+const COMPACT_EVERY_MS: u64 = 10 * 60_000;
+
+/// Metrics recorded for one point in time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sample {
+    /// Time in milliseconds since the Unix epoch (start of the minute for rollups)
+    #[serde(rename = "t")]
+    pub timestamp_ms: u64,
+    /// Global CPU usage in percent
+    pub cpu: f32,
+    /// Memory usage in percent
+    pub mem: f64,
+    /// Usage of every disk in percent, by mount point
+    pub disk: BTreeMap<String, f64>,
+    /// Download speed in bytes per second
+    pub rx: f64,
+    /// Upload speed in bytes per second
+    pub tx: f64,
+}
+
+impl Sample {
+    /// Keep the recorded metrics of a snapshot
+    pub fn from_snapshot(snapshot: &Snapshot) -> Self {
+        Sample {
+            timestamp_ms: snapshot.timestamp_ms,
+            cpu: snapshot.cpu.global_usage,
+            mem: snapshot.memory.percentage,
+            disk: snapshot
+                .disks
+                .iter()
+                .map(|disk| (disk.mount_point.clone(), disk.percentage))
+                .collect(),
+            rx: snapshot.network.selected_download_speed,
+            tx: snapshot.network.selected_upload_speed,
+        }
+    }
+
+    /// Value of a metric (None for per-core CPU, which is not recorded, and
+    /// unknown mount points)
+    pub fn value(&self, metric: &Metric) -> Option<f64> {
+        match metric {
+            Metric::CpuGlobal => Some(self.cpu as f64),
+            Metric::CpuCore(_) => None,
+            Metric::Memory => Some(self.mem),
+            Metric::Disk(Some(mount)) => self.disk.get(mount).copied(),
+            Metric::Disk(None) => self.disk.values().copied().max_by(f64::total_cmp),
+            Metric::NetRx => Some(self.rx),
+            Metric::NetTx => Some(self.tx),
+        }
+    }
+}
+
+/// How long samples are kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retention {
+    /// Raw samples (one per tick)
+    pub raw: Duration,
+    /// One-minute averages
+    pub rollup: Duration,
+}
+
+impl Default for Retention {
+    /// One hour of raw samples and 30 days of one-minute averages
+    fn default() -> Self {
+        Retention {
+            raw: Duration::from_secs(3600),
+            rollup: Duration::from_secs(30 * 86400),
+        }
+    }
+}
+
+/// Running average of the samples in one minute
+#[derive(Debug, Clone)]
+struct Rollup {
+    bucket_ms: u64,
+    count: u32,
+    cpu: f64,
+    mem: f64,
+    disk: BTreeMap<String, (f64, u32)>,
+    rx: f64,
+    tx: f64,
+}
+
+impl Rollup {
+    fn new(bucket_ms: u64) -> Self {
+        Rollup {
+            bucket_ms,
+            count: 0,
+            cpu: 0.0,
+            mem: 0.0,
+            disk: BTreeMap::new(),
+            rx: 0.0,
+            tx: 0.0,
+        }
+    }
+
+    fn add(&mut self, sample: &Sample) {
+        self.count += 1;
+        self.cpu += sample.cpu as f64;
+        self.mem += sample.mem;
+        for (mount, usage) in &sample.disk {
+            let (sum, count) = self.disk.entry(mount.clone()).or_default();
+            *sum += usage;
+            *count += 1;
+        }
+        self.rx += sample.rx;
+        self.tx += sample.tx;
+    }
+
+    fn average(&self) -> Sample {
+        let n = self.count.max(1) as f64;
+        Sample {
+            timestamp_ms: self.bucket_ms,
+            cpu: (self.cpu / n) as f32,
+            mem: self.mem / n,
+            disk: self
+                .disk
+                .iter()
+                .map(|(mount, (sum, count))| (mount.clone(), sum / *count as f64))
+                .collect(),
+            rx: self.rx / n,
+            tx: self.tx / n,
+        }
+    }
+}
+
+/// Directory holding the history files
+#[derive(Debug, Clone)]
+pub struct HistoryStore {
+    dir: PathBuf,
+}
+
+impl HistoryStore {
+    /// Use the history files in `dir`
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        HistoryStore { dir: dir.into() }
+    }
+
+    /// Directory of the history files
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn raw_path(&self) -> PathBuf {
+        self.dir.join(RAW_FILE)
+    }
+
+    fn rollup_path(&self) -> PathBuf {
+        self.dir.join(ROLLUP_FILE)
+    }
+
+    /// Samples between `from_ms` and `to_ms` (inclusive), oldest first
+    ///
+    /// Raw samples are used where they exist; one-minute averages cover the
+    /// time before the oldest raw sample.
+    pub fn query(&self, from_ms: u64, to_ms: u64) -> io::Result<Vec<Sample>> {
+        let in_range = |s: &Sample| s.timestamp_ms >= from_ms && s.timestamp_ms <= to_ms;
+
+        let raw = read_samples(&self.raw_path())?;
+        let raw_start = raw.first().map_or(u64::MAX, |s| s.timestamp_ms);
+
+        let mut samples: Vec<Sample> = latest_per_bucket(read_samples(&self.rollup_path())?)
+            .into_iter()
+            .filter(|s| s.timestamp_ms + ROLLUP_MS <= raw_start && in_range(s))
+            .collect();
+        samples.extend(raw.into_iter().filter(in_range));
+        Ok(samples)
+    }
+
+    /// Drop samples older than the retention, relative to `now_ms`
+    pub fn compact(&self, retention: Retention, now_ms: u64) -> io::Result<()> {
+        let cutoff = |keep: Duration| now_ms.saturating_sub(keep.as_millis() as u64);
+        compact_file(&self.raw_path(), cutoff(retention.raw), false)?;
+        compact_file(&self.rollup_path(), cutoff(retention.rollup), true)
+    }
+}
+
+/// Read every sample of a file, skipping lines that don't parse (e.g. a line
+/// cut short by a crash); a missing file has no samples
+fn read_samples(path: &Path) -> io::Result<Vec<Sample>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut samples = Vec::new();
+    for line in BufReader::new(file).lines() {
+        if let Ok(sample) = serde_json::from_str::<Sample>(&line?) {
+            samples.push(sample);
+        }
+    }
+    samples.sort_by_key(|s| s.timestamp_ms);
+    Ok(samples)
+}
+
+/// Keep the last average of every minute
+///
+/// A recorder restarted within a minute writes that minute again, averaged
+/// over the samples of both runs, see [`Recorder::open`].
+fn latest_per_bucket(samples: Vec<Sample>) -> Vec<Sample> {
+    let mut kept: Vec<Sample> = Vec::with_capacity(samples.len());
+    for sample in samples {
+        match kept.last_mut() {
+            Some(last) if last.timestamp_ms == sample.timestamp_ms => *last = sample,
+            _ => kept.push(sample),
+        }
+    }
+    kept
+}
+
+/// Rewrite a file without the samples before `cutoff_ms`
+///
+/// # Arguments
+/// * `path` - History file
+/// * `cutoff_ms` - Time of the oldest sample to keep
+/// * `rollups` - Whether the file holds averages, of which only the last
+///   one of every minute is kept
+fn compact_file(path: &Path, cutoff_ms: u64, rollups: bool) -> io::Result<()> {
+    let mut samples = read_samples(path)?;
+    let count = samples.len();
+    if rollups {
+        samples = latest_per_bucket(samples);
+    }
+    if samples.len() == count && samples.first().is_none_or(|s| s.timestamp_ms >= cutoff_ms) {
+        return Ok(());
+    }
+
+    // Write a new file and swap it in, so a crash never loses the old one
+    let tmp = path.with_extension("tmp");
+    let mut out = BufWriter::new(File::create(&tmp)?);
+    for sample in samples.iter().filter(|s| s.timestamp_ms >= cutoff_ms) {
+        serde_json::to_writer(&mut out, sample)?;
+        writeln!(out)?;
+    }
+    out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    fs::rename(&tmp, path)
+}
+
+/// Appends every snapshot to the history and maintains the rollups
+pub struct Recorder {
+    store: HistoryStore,
+    retention: Retention,
+    raw: File,
+    rollup: Option<Rollup>,
+    last_compaction_ms: u64,
+}
+
+impl Recorder {
+    /// Open (or create) the history in a directory
+    ///
+    /// The average of the minute of the last raw sample starts from the
+    /// raw samples of that minute, so recording again within it writes one
+    /// average of both runs, which replaces the one of the previous run.
+    ///
+    /// # Arguments
+    /// * `store` - History directory, created if missing
+    /// * `retention` - How long raw samples and averages are kept
+    pub fn open(store: HistoryStore, retention: Retention) -> io::Result<Self> {
+        fs::create_dir_all(store.dir())?;
+        let raw = open_append(&store.raw_path())?;
+
+        let samples = read_samples(&store.raw_path())?;
+        let rollup = samples.last().map(|last| {
+            let bucket = last.timestamp_ms - last.timestamp_ms % ROLLUP_MS;
+            let mut rollup = Rollup::new(bucket);
+            for sample in samples.iter().filter(|s| s.timestamp_ms >= bucket) {
+                rollup.add(sample);
+            }
+            rollup
+        });
+
+        Ok(Recorder {
+            store,
+            retention,
+            raw,
+            rollup,
+            last_compaction_ms: 0,
+        })
+    }
+
+    /// Append the average of the current minute to the rollup file
+    fn flush_rollup(&mut self) -> io::Result<()> {
+        if let Some(rollup) = self.rollup.take() {
+            let mut file = open_append(&self.store.rollup_path())?;
+            write_line(&mut file, &rollup.average())?;
+        }
+        Ok(())
+    }
+}
+
+/// Open a history file for appending, after dropping a last line left
+/// unterminated by a crash (the next sample would be glued onto it)
+fn open_append(path: &Path) -> io::Result<File> {
+    drop_partial_line(path)?;
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// Cut a file back to its last newline
+fn drop_partial_line(path: &Path) -> io::Result<()> {
+    const CHUNK: u64 = 4096;
+
+    let mut file = match OpenOptions::new().read(true).write(true).open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+
+    // Search backwards, a chunk at a time, for the end of the last full line
+    let mut end = file.metadata()?.len();
+    let mut buf = [0u8; CHUNK as usize];
+    let mut complete = true;
+    while end > 0 {
+        let start = end.saturating_sub(CHUNK);
+        let chunk = &mut buf[..(end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(chunk)?;
+        match chunk.iter().rposition(|&b| b == b'\n') {
+            Some(i) if complete && start + i as u64 + 1 == end => return Ok(()),
+            Some(i) => return file.set_len(start + i as u64 + 1),
+            None => {
+                complete = false;
+                end = start;
+            }
+        }
+    }
+    file.set_len(0)
+}
+
+/// Write a sample as one complete line
+fn write_line(out: &mut File, sample: &Sample) -> io::Result<()> {
+    let mut line = serde_json::to_vec(sample)?;
+    line.push(b'\n');
+    out.write_all(&line)
+}
+
+impl Sink for Recorder {
+    fn emit(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        let sample = Sample::from_snapshot(snapshot);
+        write_line(&mut self.raw, &sample)?;
+
+        let bucket = sample.timestamp_ms - sample.timestamp_ms % ROLLUP_MS;
+        if self.rollup.as_ref().is_some_and(|r| r.bucket_ms != bucket) {
+            self.flush_rollup()?;
+        }
+        self.rollup
+            .get_or_insert_with(|| Rollup::new(bucket))
+            .add(&sample);
+
+        if sample.timestamp_ms >= self.last_compaction_ms + COMPACT_EVERY_MS {
+            self.store.compact(self.retention, sample.timestamp_ms)?;
+            self.last_compaction_ms = sample.timestamp_ms;
+            // Compaction may have replaced the raw file
+            self.raw = open_append(&self.store.raw_path())?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.flush_rollup()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::snapshot::sample_snapshot_at as at;
+
+    fn temp_store(name: &str) -> HistoryStore {
+        let dir =
+            std::env::temp_dir().join(format!("sysmon-history-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        HistoryStore::new(dir)
+    }
+
+    #[test]
+    fn test_records_and_rolls_up() {
+        let store = temp_store("rollup");
+        let mut recorder = Recorder::open(store.clone(), Retention::default()).unwrap();
+        // Two samples in one minute, one in the next
+        let start = 1_700_000_040;
+        for (secs, cpu) in [(start, 10.0), (start + 10, 30.0), (start + 60, 50.0)] {
+            recorder.emit(&at(secs, cpu)).unwrap();
+        }
+        recorder.finish().unwrap();
+
+        let rollups = read_samples(&store.rollup_path()).unwrap();
+        assert_eq!(rollups.len(), 2);
+        assert_eq!(rollups[0].timestamp_ms % ROLLUP_MS, 0);
+        assert_eq!(rollups[0].cpu, 20.0);
+        assert_eq!(rollups[1].cpu, 50.0);
+
+        let samples = store.query(0, u64::MAX).unwrap();
+        assert_eq!(samples.len(), 3);
+        assert_eq!(samples[2].value(&Metric::CpuGlobal), Some(50.0));
+        assert_eq!(
+            samples[0].value(&Metric::Disk(Some("/".to_string()))),
+            Some(60.0)
+        );
+        assert_eq!(samples[0].value(&Metric::CpuCore(0)), None);
+
+        fs::remove_dir_all(store.dir()).unwrap();
+    }
+
+    #[test]
+    fn test_restart_within_a_minute() {
+        let store = temp_store("restart");
+        let start = 1_700_000_040;
+        for (secs, cpu) in [(start, 10.0), (start + 10, 30.0)] {
+            let mut recorder = Recorder::open(store.clone(), Retention::default()).unwrap();
+            recorder.emit(&at(secs, cpu)).unwrap();
+            recorder.finish().unwrap();
+        }
+
+        // Both runs wrote the minute, the second one with both samples
+        let rollups = read_samples(&store.rollup_path()).unwrap();
+        assert_eq!(rollups.len(), 2);
+        let latest = latest_per_bucket(rollups);
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].cpu, 20.0);
+
+        store.compact(Retention::default(), start * 1000).unwrap();
+        assert_eq!(read_samples(&store.rollup_path()).unwrap(), latest);
+
+        fs::remove_dir_all(store.dir()).unwrap();
+    }
+
+    #[test]
+    fn test_retention_and_tiers() {
+        let store = temp_store("retention");
+        fs::create_dir_all(store.dir()).unwrap();
+        let hour = 3_600_000;
+        let now = 100 * 86_400_000;
+
+        // Averages every hour for two days, raw samples for the last 30 minutes
+        let mut rollups = open_append(&store.rollup_path()).unwrap();
+        for t in (now - 48 * hour..now).step_by(hour as usize) {
+            write_line(&mut rollups, &Sample::from_snapshot(&at(t / 1000, 5.0))).unwrap();
+        }
+        let mut raw = open_append(&store.raw_path()).unwrap();
+        for t in (now - hour / 2..now).step_by(60_000) {
+            write_line(&mut raw, &Sample::from_snapshot(&at(t / 1000, 90.0))).unwrap();
+        }
+        // A line cut short by a crash is skipped, and the next sample
+        // recorded after a restart goes on a line of its own
+        raw.write_all(b"{\"t\":12").unwrap();
+        drop(raw);
+        let mut recorder = Recorder::open(store.clone(), Retention::default()).unwrap();
+        recorder.emit(&at((now - 30_000) / 1000, 90.0)).unwrap();
+        drop(recorder);
+
+        let retention = Retention {
+            raw: Duration::from_secs(3600),
+            rollup: Duration::from_secs(86_400),
+        };
+        store.compact(retention, now).unwrap();
+        assert_eq!(read_samples(&store.rollup_path()).unwrap().len(), 24);
+
+        let samples = store.query(now - 2 * hour, now).unwrap();
+        let cpu: Vec<f32> = samples.iter().map(|s| s.cpu).collect();
+        // Two hourly averages before the raw samples take over
+        assert_eq!(cpu[..2], [5.0, 5.0]);
+        assert!(cpu[2..].iter().all(|&c| c == 90.0));
+        assert_eq!(cpu.len(), 2 + 30 + 1);
+
+        fs::remove_dir_all(store.dir()).unwrap();
+    }
+}
@@ -0,0 +1,87 @@
+// Kill module
+// Sends a signal to processes matched by name (`sysmon kill`)
+
+use colored::*;
+use system_monitor::monitor::signal::{self, Signal};
+use system_monitor::SystemMonitor;
+
+/// Most processes signalled without `--all`
+pub const MAX_MATCHES: usize = 10;
+
+/// Signal every process whose name contains `pattern`
+///
+/// Returns the process exit code: 0 if every signal was delivered (or, in
+/// dry-run mode, if something matched), 1 otherwise. More than
+/// [`MAX_MATCHES`] processes are only signalled with `all`.
+///
+/// # Arguments
+/// * `pattern` - Substring of the process name
+/// * `signal` - Signal to send
+/// * `dry_run` - Only list what would be signalled
+/// * `all` - Signal every match, however many there are
+pub fn run(
+    monitor: &SystemMonitor,
+    pattern: &str,
+    signal: Signal,
+    dry_run: bool,
+    all: bool,
+) -> i32 {
+    let mut matched = signal::matching_processes(monitor.processes(), pattern);
+    matched.sort_by_key(|process| process.pid);
+
+    if matched.is_empty() {
+        eprintln!("No processes match '{}'", pattern);
+        return 1;
+    }
+
+    if dry_run {
+        println!(
+            "Would send {} to {} process(es):",
+            signal.to_string().bold(),
+            matched.len()
+        );
+        for process in &matched {
+            println!("  PID {:>7}  {}", process.pid, process.name);
+        }
+        return 0;
+    }
+
+    if matched.len() > MAX_MATCHES && !all {
+        eprintln!(
+            "{} processes match '{}'; check them with --dry-run and add --all to signal them all",
+            matched.len(),
+            pattern
+        );
+        return 1;
+    }
+
+    let mut failed = 0;
+    for process in &matched {
+        match signal::send_signal(process.pid, signal) {
+            Ok(()) => println!(
+                "{} Sent {} to {} (PID {})",
+                "✓".green(),
+                signal,
+                process.name,
+                process.pid
+            ),
+            Err(e) => {
+                failed += 1;
+                eprintln!(
+                    "{} Failed to send {} to {} (PID {}): {}",
+                    "✗".red(),
+                    signal,
+                    process.name,
+                    process.pid,
+                    e
+                );
+            }
+        }
+    }
+
+    if failed > 0 {
+        1
+    } else {
+        0
+    }
+}
@@ -0,0 +1,57 @@
+//! # system_monitor
+//!
+//! Cross-platform system metrics collection, the library behind the `sysmon`
+//! command-line tool.
+//!
+//! [`SystemMonitor`] is the entry point: refresh it, then query CPU, memory,
+//! disk, network and process information, or collect everything at once as
+//! a [`Snapshot`]. Snapshots can be handed to any [`output::Sink`] (colored
+//! terminal view, JSON, CSV, InfluxDB, StatsD, Prometheus).
+//!
+//! ## Example
+//!
+//! ```
+//! use system_monitor::{Snapshot, SystemMonitor};
+//!
+//! let mut monitor = SystemMonitor::new();
+//! monitor.refresh();
+//!
+//! let cpu = monitor.cpu_info();
+//! println!("CPU: {:.1}% over {} cores", cpu.global_usage, cpu.cores.len());
+//!
+//! let snapshot = Snapshot::collect(&mut monitor, false);
+//! println!("{} disks", snapshot.disks.len());
+//! ```
+//!
+//! ## Stability
+//!
+//! The types re-exported at the crate root follow semantic versioning:
+//! fields and methods are not removed or changed within a major version.
+//! Minor versions may add fields to the data structs, so code building them
+//! with struct literals (e.g. hand-built snapshots for testing a sink) may
+//! have to set the new fields after an upgrade.
+
+#![warn(missing_docs)]
+
+/// Threshold alert rules evaluated on every snapshot
+pub mod alert;
+/// Comparison of two snapshots
+pub mod diff;
+/// Colored terminal formatting helpers
+pub mod display;
+/// Recorded metric history with retention and one-minute averages
+pub mod history;
+/// System information gathering
+pub mod monitor;
+/// Snapshot sinks (terminal view, machine-readable formats, network senders)
+pub mod output;
+
+pub use monitor::cpu::{CoreInfo, CpuInfo};
+pub use monitor::detail::ProcessDetail;
+pub use monitor::disk::DiskInfo;
+pub use monitor::memory::MemoryInfo;
+pub use monitor::network::{InterfaceInfo, NetworkInfo};
+pub use monitor::process::ProcessInfo;
+pub use monitor::snapshot::Snapshot;
+pub use monitor::system::SystemMonitor;
+pub use monitor::tree::ProcessNode;
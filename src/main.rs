@@ -2,21 +2,76 @@
 // Author: SoftDryzz
 // License: MIT
 
+mod bench;
+mod check;
 mod cli;
-mod display;
-mod monitor;
+mod compare;
+mod config;
+mod kill;
+mod proc;
+mod query;
+mod serve;
+mod tui;
+mod watchdog;
 
-use display::formatter;
-use monitor::system::SystemMonitor;
+use bench::Bench;
+use cli::{Command, OutputFormat};
+use std::io;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use system_monitor::display::formatter::format_utc;
+use system_monitor::history::{HistoryStore, Recorder};
+use system_monitor::monitor::refresh::{RefreshPlan, Subsystem};
+use system_monitor::monitor::snapshot::SCHEMA_VERSION;
+use system_monitor::output::csv::CsvSink;
+use system_monitor::output::influx::{InfluxSender, InfluxSink};
+use system_monitor::output::json::JsonSink;
+use system_monitor::output::recording::{load_recording, RecordingSink};
+use system_monitor::output::statsd::{StatsdConfig, StatsdSender};
+use system_monitor::output::terminal::TerminalSink;
+use system_monitor::output::{self, Sink};
+use system_monitor::{Snapshot, SystemMonitor};
+use watchdog::Watchdog;
 
 fn main() {
-    // Parse CLI arguments
+    // Parse CLI arguments and merge them over the configuration files
     let args = cli::parse_args();
 
+    // Plugin checks only take their thresholds from the command line
+    if let Some(Command::Check(check_args)) = &args.command {
+        let plan = RefreshPlan::only([Subsystem::Cpu, Subsystem::Memory, Subsystem::Disks]);
+        std::process::exit(check::run(&mut SystemMonitor::with_plan(plan), check_args));
+    }
+
+    let (file_config, loaded) = config::load(args.config.as_deref()).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(2);
+    });
+    let settings = config::Settings::resolve(&args, &file_config).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(2);
+    });
+
+    if let Some(Command::Config { print_effective }) = args.command {
+        let searched = config::search_paths(args.config.as_deref());
+        std::process::exit(config::run(&settings, &searched, &loaded, print_effective));
+    }
+
+    if let Some(Command::History {
+        since,
+        until,
+        metrics,
+    }) = &args.command
+    {
+        std::process::exit(query::run(&settings, *since, *until, metrics));
+    }
+
+    if let Some(Command::Diff { before, after }) = &args.command {
+        std::process::exit(compare::run(&settings, before, after));
+    }
+
     // Setup Ctrl+C handler
     let running = Arc::new(AtomicBool::new(true));
     let r = running.clone();
@@ -26,71 +81,319 @@ fn main() {
     })
     .expect("Error setting Ctrl+C handler");
 
-    // Create system monitor instance
-    let mut monitor = SystemMonitor::new();
+    // Replays only read the session file, never the live system
+    if let Some(Command::Replay { file, speed }) = &args.command {
+        std::process::exit(replay(file, *speed, &settings, &running));
+    }
+
+    // Create system monitor instance, only refreshing what the command needs
+    let mut monitor = SystemMonitor::with_plan(refresh_plan(&args, &settings));
+    monitor.set_process_filter(args.filter.clone());
+    monitor.set_interface_filter(settings.network.filter());
+
+    if let Some(Command::Serve {
+        listen,
+        min_refresh,
+    }) = args.command
+    {
+        let min_refresh = Duration::from_secs(min_refresh);
+        if let Err(e) = serve::run(&mut monitor, listen, min_refresh, &running) {
+            eprintln!("Error running exporter on {}: {}", listen, e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Command::Proc { pid }) = args.command {
+        std::process::exit(proc::run(
+            &mut monitor,
+            pid,
+            settings.format,
+            &settings.view_options().style,
+        ));
+    }
+
+    if let Some(Command::Kill {
+        pattern,
+        signal,
+        dry_run,
+        all,
+    }) = &args.command
+    {
+        std::process::exit(kill::run(&monitor, pattern, *signal, *dry_run, *all));
+    }
+
+    let mut watchdog = Watchdog::new(&settings.alerts).unwrap_or_else(|e| {
+        eprintln!("Error opening alert log: {}", e);
+        std::process::exit(2);
+    });
+
+    let mut recorder = open_recorder(&settings.history).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
 
-    if args.watch {
-        // Watch mode: continuous updates
-        watch_mode(&mut monitor, args.interval, args.detailed, running);
-    } else {
-        // Single snapshot mode
-        single_snapshot(&mut monitor, args.detailed);
+    if let Some(Command::Record { file }) = &args.command {
+        let mut sink = RecordingSink::create(file).unwrap_or_else(|e| {
+            eprintln!("Error creating {}: {}", file.display(), e);
+            std::process::exit(1);
+        });
+        eprintln!(
+            "Recording to {} every {}s, press Ctrl+C to stop",
+            file.display(),
+            settings.interval
+        );
+        let result = run(
+            &mut monitor,
+            &args,
+            &settings,
+            &running,
+            &mut sink,
+            watchdog.as_mut(),
+            recorder.as_mut(),
+        );
+        if let Err(e) = result {
+            eprintln!("Error writing {}: {}", file.display(), e);
+            std::process::exit(1);
+        }
+        eprintln!("Recorded {} snapshots to {}", sink.frames(), file.display());
+        return;
+    }
+
+    if args.tui {
+        if settings.format != OutputFormat::Text || args.output.is_some() {
+            eprintln!("Error: --tui only supports the text format");
+            std::process::exit(2);
+        }
+        if let Err(e) = tui::run(
+            &mut monitor,
+            &settings,
+            args.tree,
+            &running,
+            watchdog.as_mut(),
+            recorder.as_mut(),
+        ) {
+            eprintln!("Error running interactive mode: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if args.output.is_some() && matches!(settings.format, OutputFormat::Text | OutputFormat::Statsd)
+    {
+        eprintln!("Error: --output requires --format json, csv or influx");
+        std::process::exit(2);
+    }
+
+    if args.influx_addr.is_some() && settings.format != OutputFormat::Influx {
+        eprintln!("Error: --influx-addr requires --format influx");
+        std::process::exit(2);
+    }
+
+    let result = build_sink(&args, &settings).and_then(|mut sink| {
+        run(
+            &mut monitor,
+            &args,
+            &settings,
+            &running,
+            sink.as_mut(),
+            watchdog.as_mut(),
+            recorder.as_mut(),
+        )
+    });
+
+    if let Err(e) = result {
+        // A closed pipe (e.g. `| head`) is a normal way to stop
+        if e.kind() != io::ErrorKind::BrokenPipe {
+            eprintln!("Error writing output: {}", e);
+            std::process::exit(1);
+        }
     }
 }
 
-/// Display a single snapshot of system information
-fn single_snapshot(monitor: &mut SystemMonitor, detailed: bool) {
-    monitor.refresh();
-
-    formatter::print_header(false, 0);
-    formatter::print_cpu_info(monitor, detailed);
-    formatter::print_memory_info(monitor);
-    formatter::print_disk_info(monitor);
-    formatter::print_network_info(monitor);
-    formatter::print_top_processes_cpu(monitor, detailed);
-    formatter::print_top_processes_memory(monitor, detailed);
-    formatter::print_uptime(monitor);
-    formatter::print_footer(false);
+/// Create the sink for the selected output format
+fn build_sink(args: &cli::Args, settings: &config::Settings) -> io::Result<Box<dyn Sink>> {
+    let sink: Box<dyn Sink> = match settings.format {
+        OutputFormat::Text => {
+            let sink = TerminalSink::new(io::stdout(), settings.view_options());
+            if args.watch {
+                Box::new(sink.watching(settings.interval))
+            } else {
+                Box::new(sink)
+            }
+        }
+        OutputFormat::Json => {
+            // Streams and files get one record per line, stdout snapshots are pretty
+            let stream = args.watch || args.output.is_some();
+            let out = output::open_output(args.output.as_deref())?;
+            Box::new(JsonSink::new(out, stream))
+        }
+        OutputFormat::Csv => Box::new(CsvSink::new(args.output.clone())),
+        OutputFormat::Influx => match &args.influx_addr {
+            Some(target) => Box::new(InfluxSink::Network(InfluxSender::new(target.clone()))),
+            None => Box::new(InfluxSink::Writer(output::open_output(
+                args.output.as_deref(),
+            )?)),
+        },
+        OutputFormat::Statsd => {
+            let config = StatsdConfig {
+                prefix: args.statsd_prefix.clone(),
+                dogstatsd: args.dogstatsd,
+                tags: args.statsd_tags.clone(),
+            };
+            Box::new(StatsdSender::connect(&args.statsd_addr, config)?)
+        }
+    };
+    Ok(sink)
 }
 
-/// Continuously monitor and display system information
-fn watch_mode(
-    monitor: &mut SystemMonitor,
-    interval: u64,
-    detailed: bool,
-    running: Arc<AtomicBool>,
-) {
-    // Initial display
-    formatter::clear_screen();
+/// Subsystems the command needs, at their configured intervals
+fn refresh_plan(args: &cli::Args, settings: &config::Settings) -> RefreshPlan {
+    match args.command {
+        Some(Command::Serve { .. }) => settings.refresh.plan([
+            Subsystem::Cpu,
+            Subsystem::Memory,
+            Subsystem::Disks,
+            Subsystem::Network,
+        ]),
+        Some(Command::Proc { .. } | Command::Kill { .. }) => {
+            RefreshPlan::only([Subsystem::Processes])
+        }
+        // Recordings are replayed with any sections, the process table can
+        // be opened at any time
+        Some(Command::Record { .. }) => settings.refresh.plan(Subsystem::ALL),
+        _ if args.tui => settings.refresh.plan(Subsystem::ALL),
+        _ => settings.refresh_plan(args.tree),
+    }
+}
 
+/// Open the metric history when recording is enabled
+fn open_recorder(settings: &config::HistorySettings) -> Result<Option<Recorder>, String> {
+    if !settings.record {
+        return Ok(None);
+    }
+    let dir = settings
+        .dir
+        .clone()
+        .ok_or("no history directory, use --history-dir or [history] dir")?;
+    Recorder::open(HistoryStore::new(&dir), settings.retention())
+        .map(Some)
+        .map_err(|e| format!("cannot open history in {}: {}", dir.display(), e))
+}
+
+/// Load a session file and play it back
+///
+/// Returns the process exit code.
+fn replay(
+    file: &std::path::Path,
+    speed: f64,
+    settings: &config::Settings,
+    running: &AtomicBool,
+) -> i32 {
+    let frames = match load_recording(file) {
+        Ok(frames) if frames.is_empty() => {
+            eprintln!("Error: {} contains no snapshots", file.display());
+            return 1;
+        }
+        Ok(frames) => frames,
+        Err(e) => {
+            eprintln!("Error reading {}: {}", file.display(), e);
+            return 1;
+        }
+    };
+
+    if let Some(frame) = frames.iter().find(|f| f.schema_version != SCHEMA_VERSION) {
+        eprintln!(
+            "Warning: {} was recorded with schema version {} (current: {}), some \
+             values (e.g. network totals) differ in meaning",
+            file.display(),
+            frame.schema_version,
+            SCHEMA_VERSION
+        );
+    }
+
+    match tui::replay::run(frames, speed, settings, running) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Error replaying {}: {}", file.display(), e);
+            1
+        }
+    }
+}
+
+/// Refresh, collect and emit snapshots until done
+///
+/// Runs once, or once per interval in watch mode and while recording a
+/// session, until Ctrl+C. Alert rules
+/// are checked after every snapshot, which is also recorded to the history
+/// when enabled. Attached and removed devices are reported on stderr.
+fn run(
+    monitor: &mut SystemMonitor,
+    args: &cli::Args,
+    settings: &config::Settings,
+    running: &AtomicBool,
+    sink: &mut dyn Sink,
+    mut watchdog: Option<&mut Watchdog>,
+    mut recorder: Option<&mut Recorder>,
+) -> io::Result<()> {
+    let watch = args.watch || matches!(args.command, Some(Command::Record { .. }));
+    let mut bench = args.bench.then(Bench::default);
     while running.load(Ordering::SeqCst) {
         // Refresh system information
         monitor.refresh();
 
-        // Clear screen and move cursor to top
-        formatter::clear_screen();
-
-        // Display information
-        formatter::print_header(true, interval);
-        formatter::print_cpu_info(monitor, detailed);
-        formatter::print_memory_info(monitor);
-        formatter::print_disk_info(monitor);
-        formatter::print_network_info(monitor);
-        formatter::print_top_processes_cpu(monitor, detailed);
-        formatter::print_top_processes_memory(monitor, detailed);
-        formatter::print_uptime(monitor);
-        formatter::print_footer(true);
-
-        // Wait for interval (but check running flag more frequently)
-        let sleep_iterations = interval * 10; // Check every 100ms
-        for _ in 0..sleep_iterations {
-            if !running.load(Ordering::SeqCst) {
-                break;
+        let collect_start = Instant::now();
+        let mut snapshot = Snapshot::collect_top(monitor, settings.top_counts(settings.detailed));
+        if args.tree {
+            snapshot = snapshot.with_process_tree(monitor);
+        }
+        if let Some(bench) = &mut bench {
+            eprintln!(
+                "{}",
+                bench.record(monitor.refresh_timings(), collect_start.elapsed())
+            );
+        }
+        for event in monitor.device_events() {
+            eprintln!("{} {}", format_utc(snapshot.timestamp_ms / 1000), event);
+        }
+        sink.emit(&snapshot)?;
+        if let Some(recorder) = recorder.as_deref_mut() {
+            recorder.emit(&snapshot)?;
+        }
+
+        if let Some(watchdog) = watchdog.as_deref_mut() {
+            let events = watchdog.check(&snapshot);
+            // Without a command or log file, alerts go to stderr
+            if !watchdog.has_actions() {
+                for event in &events {
+                    eprintln!("{}", watchdog::describe(event));
+                }
             }
-            thread::sleep(Duration::from_millis(100));
         }
+
+        if !watch {
+            break;
+        }
+        wait_interval(settings.interval, running);
     }
 
     // Clean exit
-    println!("\nExiting System Monitor...");
+    if let Some(bench) = bench.filter(|_| watch) {
+        eprint!("\n{}", bench.summary());
+    }
+    if let Some(recorder) = recorder {
+        recorder.finish()?;
+    }
+    sink.finish()
+}
+
+/// Wait for the update interval (but check running flag more frequently)
+fn wait_interval(interval: u64, running: &AtomicBool) {
+    let sleep_iterations = interval * 10; // Check every 100ms
+    for _ in 0..sleep_iterations {
+        if !running.load(Ordering::SeqCst) {
+            break;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
 }
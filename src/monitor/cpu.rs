@@ -1,19 +1,24 @@
 // CPU monitoring module
 // Provides CPU usage information per core and globally
 
+use serde::{Deserialize, Serialize};
 use sysinfo::System;
 
 /// CPU information structure
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CpuInfo {
+    /// Usage over all cores in percent (0-100)
     pub global_usage: f32,
+    /// Per-core usage, ordered by core index
     pub cores: Vec<CoreInfo>,
 }
 
 /// Individual CPU core information
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CoreInfo {
+    /// Core index, starting at 0
     pub index: usize,
+    /// Usage in percent (0-100)
     pub usage: f32,
 }
 
@@ -37,4 +42,15 @@ impl CpuInfo {
             cores,
         }
     }
+
+    /// Get the N busiest cores, sorted by usage (descending)
+    pub fn top_cores(&self, n: usize) -> Vec<CoreInfo> {
+        let mut cores = self.cores.clone();
+
+        // Sort by usage (descending)
+        cores.sort_by(|a, b| b.usage.partial_cmp(&a.usage).unwrap());
+
+        // Take top N
+        cores.into_iter().take(n).collect()
+    }
 }
@@ -0,0 +1,179 @@
+// Process detail module
+// Everything known about a single process (command line, state, CPU time, ...)
+
+use serde::Serialize;
+use sysinfo::{Pid, Users};
+
+/// Detailed information about one process
+///
+/// Fields the OS does not expose for this process (e.g. another user's
+/// working directory) are `None` or empty.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessDetail {
+    /// Process ID
+    pub pid: u32,
+    /// Parent process ID
+    pub parent_pid: Option<u32>,
+    /// Process name
+    pub name: String,
+    /// Name of the user owning the process
+    pub user: Option<String>,
+    /// Full command line, one entry per argument
+    pub command_line: Vec<String>,
+    /// Path of the executable
+    pub executable: Option<String>,
+    /// Current working directory
+    pub working_directory: Option<String>,
+    /// Number of environment variables
+    pub environment_vars: usize,
+    /// Total size of the environment in bytes
+    pub environment_bytes: usize,
+    /// Scheduler state (e.g. "Runnable", "Sleeping", "Zombie")
+    pub state: String,
+    /// Number of threads
+    pub threads: Option<u64>,
+    /// Start time in seconds since the Unix epoch
+    pub start_time: u64,
+    /// Seconds since the process started
+    pub run_time_secs: u64,
+    /// Current CPU usage in percent
+    pub cpu_usage: f32,
+    /// CPU time used since start (user + system) in seconds
+    pub cpu_time_secs: Option<f64>,
+    /// Virtual memory size in bytes
+    pub virtual_memory_bytes: u64,
+    /// Resident memory in bytes
+    pub resident_memory_bytes: u64,
+    /// Number of open file descriptors
+    pub open_files: Option<u64>,
+}
+
+impl ProcessDetail {
+    /// Create process details from sysinfo Process
+    ///
+    /// # Arguments
+    /// * `pid` - Process ID
+    /// * `process` - sysinfo process entry
+    /// * `users` - User list used to resolve the owner name
+    pub fn new(pid: Pid, process: &sysinfo::Process, users: &Users) -> Self {
+        let lossy = |s: &std::ffi::OsStr| s.to_string_lossy().to_string();
+        let stat = procfs::read_stat(pid.as_u32());
+
+        ProcessDetail {
+            pid: pid.as_u32(),
+            parent_pid: process.parent().map(|parent| parent.as_u32()),
+            name: lossy(process.name()),
+            user: process
+                .user_id()
+                .and_then(|uid| users.get_user_by_id(uid))
+                .map(|user| user.name().to_string()),
+            command_line: process.cmd().iter().map(|arg| lossy(arg)).collect(),
+            executable: process.exe().map(|path| path.display().to_string()),
+            working_directory: process.cwd().map(|path| path.display().to_string()),
+            environment_vars: process.environ().len(),
+            environment_bytes: process.environ().iter().map(|var| var.len()).sum(),
+            state: process.status().to_string(),
+            threads: stat
+                .map(|stat| stat.threads)
+                .or_else(|| process.tasks().map(|tasks| tasks.len() as u64)),
+            start_time: process.start_time(),
+            run_time_secs: process.run_time(),
+            cpu_usage: process.cpu_usage(),
+            cpu_time_secs: stat.map(|stat| stat.cpu_time_secs),
+            virtual_memory_bytes: process.virtual_memory(),
+            resident_memory_bytes: process.memory(),
+            open_files: procfs::count_open_files(pid.as_u32()),
+        }
+    }
+}
+
+/// Values read from /proc/<pid>/stat
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+struct Stat {
+    cpu_time_secs: f64,
+    threads: u64,
+}
+
+/// Parse the fields of /proc/<pid>/stat that sysinfo does not expose
+///
+/// # Arguments
+/// * `content` - File content
+/// * `ticks_per_second` - Clock ticks per second (`sysconf(_SC_CLK_TCK)`)
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_stat(content: &str, ticks_per_second: f64) -> Option<Stat> {
+    // The name (field 2) is in parentheses and may itself contain spaces
+    // or parentheses, so start after the last ')'
+    let rest = &content[content.rfind(')')? + 1..];
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+
+    // Field numbers from proc(5), minus the 3 fields up to and including state
+    let utime: f64 = fields.get(14 - 3)?.parse().ok()?;
+    let stime: f64 = fields.get(15 - 3)?.parse().ok()?;
+    let threads = fields.get(20 - 3)?.parse().ok()?;
+
+    Some(Stat {
+        cpu_time_secs: (utime + stime) / ticks_per_second,
+        threads,
+    })
+}
+
+#[cfg(target_os = "linux")]
+mod procfs {
+    use super::{parse_stat, Stat};
+    use std::fs;
+
+    pub fn read_stat(pid: u32) -> Option<Stat> {
+        let content = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+        // SAFETY: sysconf has no memory safety requirements
+        let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+        if ticks <= 0 {
+            return None;
+        }
+        parse_stat(&content, ticks as f64)
+    }
+
+    /// Count the entries of /proc/<pid>/fd (None without permission)
+    pub fn count_open_files(pid: u32) -> Option<u64> {
+        let entries = fs::read_dir(format!("/proc/{}/fd", pid)).ok()?;
+        Some(entries.count() as u64)
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod procfs {
+    use super::Stat;
+
+    pub fn read_stat(_pid: u32) -> Option<Stat> {
+        None
+    }
+
+    pub fn count_open_files(_pid: u32) -> Option<u64> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stat() {
+        // Name with a space and a parenthesis; utime=250, stime=50, 7 threads
+        let content = "1234 (my (app) x) S 1 1234 1234 0 -1 4194560 500 0 0 0 \
+                       250 50 0 0 20 0 7 0 12345 1000000 200 18446744073709551615";
+        let stat = parse_stat(content, 100.0).unwrap();
+
+        assert_eq!(stat.cpu_time_secs, 3.0);
+        assert_eq!(stat.threads, 7);
+        assert_eq!(parse_stat("garbage", 100.0), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_own_process_details() {
+        let stat = procfs::read_stat(std::process::id()).unwrap();
+        assert!(stat.threads >= 1);
+        assert!(procfs::count_open_files(std::process::id()).unwrap() >= 3);
+    }
+}
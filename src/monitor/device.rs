@@ -0,0 +1,115 @@
+// Device module
+// Disks and network interfaces that were attached or removed between two
+// scans of the device lists
+
+use serde::Serialize;
+use std::fmt;
+use std::time::Duration;
+
+/// Minimum time between two scans for attached or removed devices
+///
+/// Refreshes in between only update the known devices, which is much
+/// cheaper than listing them again.
+pub const DEVICE_SCAN_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Kind of device
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceKind {
+    /// A mounted disk, identified by its mount point
+    Disk,
+    /// A network interface, identified by its name
+    Network,
+}
+
+/// What happened to a device
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceChange {
+    /// The device showed up
+    Attached,
+    /// The device is gone
+    Removed,
+}
+
+/// A device that was attached or removed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeviceEvent {
+    /// Disk or network interface
+    pub kind: DeviceKind,
+    /// Attached or removed
+    pub change: DeviceChange,
+    /// Mount point of a disk, name of a network interface
+    pub name: String,
+}
+
+impl fmt::Display for DeviceEvent {
+    /// e.g. "disk /mnt/usb attached" or "network interface wlan0 removed"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = match self.kind {
+            DeviceKind::Disk => "disk",
+            DeviceKind::Network => "network interface",
+        };
+        let change = match self.change {
+            DeviceChange::Attached => "attached",
+            DeviceChange::Removed => "removed",
+        };
+        write!(f, "{} {} {}", kind, self.name, change)
+    }
+}
+
+/// Compare the devices of two scans, removed ones first
+///
+/// # Arguments
+/// * `kind` - Kind of the listed devices
+/// * `before` - Names found by the previous scan
+/// * `after` - Names found by this scan
+pub(crate) fn compare(kind: DeviceKind, before: &[String], after: &[String]) -> Vec<DeviceEvent> {
+    let event = |change, name: &String| DeviceEvent {
+        kind,
+        change,
+        name: name.clone(),
+    };
+
+    before
+        .iter()
+        .filter(|name| !after.contains(name))
+        .map(|name| event(DeviceChange::Removed, name))
+        .chain(
+            after
+                .iter()
+                .filter(|name| !before.contains(name))
+                .map(|name| event(DeviceChange::Attached, name)),
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_compare() {
+        let events = compare(
+            DeviceKind::Disk,
+            &names(&["/", "/mnt/usb", "/home"]),
+            &names(&["/", "/home", "/mnt/backup"]),
+        );
+        assert_eq!(
+            events.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+            ["disk /mnt/usb removed", "disk /mnt/backup attached"]
+        );
+
+        // Reordered lists are no change
+        let interfaces = names(&["lo", "eth0"]);
+        assert!(compare(DeviceKind::Network, &interfaces, &names(&["eth0", "lo"])).is_empty());
+        assert_eq!(
+            compare(DeviceKind::Network, &interfaces, &names(&["lo"]))[0].to_string(),
+            "network interface eth0 removed"
+        );
+    }
+}
@@ -1,17 +1,23 @@
 // Disk monitoring module
 // Provides disk usage information for all mounted drives
 
+use serde::{Deserialize, Serialize};
 use sysinfo::Disks;
 
 /// Individual disk information
-#[derive(Debug, Clone)]
-#[allow(dead_code)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiskInfo {
+    /// Device name (e.g. /dev/sda1)
     pub name: String,
+    /// Mount point (e.g. / or C:\)
     pub mount_point: String,
+    /// Total space in GB
     pub total_gb: f64,
+    /// Available space in GB
     pub available_gb: f64,
+    /// Used space in GB
     pub used_gb: f64,
+    /// Used space in percent (0-100)
     pub percentage: f64,
 }
 
@@ -40,10 +46,8 @@ impl DiskInfo {
     }
 }
 
-/// Get information about all disks
-pub fn get_disks_info() -> Vec<DiskInfo> {
-    let disks = Disks::new_with_refreshed_list();
-
+/// Get information about all disks of a refreshed disk list
+pub fn get_disks_info(disks: &Disks) -> Vec<DiskInfo> {
     disks
         .iter()
         .filter(|disk| {
@@ -0,0 +1,393 @@
+// Filter module
+// Parses and evaluates process filter expressions like "name~nginx && cpu>20"
+
+use super::process::ProcessInfo;
+use regex::Regex;
+use std::str::FromStr;
+
+/// How a numeric field is compared to the filter value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    /// `>`
+    Greater,
+    /// `>=`
+    GreaterOrEqual,
+    /// `<`
+    Less,
+    /// `<=`
+    LessOrEqual,
+    /// `=`
+    Equal,
+}
+
+impl Comparison {
+    pub(crate) fn holds(self, value: f64, limit: f64) -> bool {
+        match self {
+            Comparison::Greater => value > limit,
+            Comparison::GreaterOrEqual => value >= limit,
+            Comparison::Less => value < limit,
+            Comparison::LessOrEqual => value <= limit,
+            Comparison::Equal => value == limit,
+        }
+    }
+}
+
+/// A parsed process filter
+///
+/// Terms:
+/// - `name~REGEX` / `name=NAME` (a bare word is a name regex)
+/// - `pid=N`
+/// - `user=NAME` / `user~REGEX`
+/// - `cpu>20` (percent, also `>=`, `<`, `<=`, `=`)
+/// - `mem>500MB` (units KB, MB, GB; MB if omitted)
+///
+/// Terms combine with `&&`/`and` and `||`/`or` (`and` binds tighter) and
+/// can be grouped with parentheses. Values with spaces or parentheses can
+/// be double-quoted: `name~"(nginx|php-fpm)"`.
+#[derive(Debug, Clone)]
+pub enum Filter {
+    /// Name matches a regular expression
+    NameMatches(Regex),
+    /// Name is exactly this
+    NameIs(String),
+    /// Process ID is exactly this
+    Pid(u32),
+    /// Owner name matches a regular expression
+    UserMatches(Regex),
+    /// Owner name is exactly this
+    UserIs(String),
+    /// CPU usage in percent compared to a limit
+    Cpu(Comparison, f64),
+    /// Memory in MB compared to a limit
+    Memory(Comparison, f64),
+    /// Both filters match
+    And(Box<Filter>, Box<Filter>),
+    /// Either filter matches
+    Or(Box<Filter>, Box<Filter>),
+}
+
+impl Filter {
+    /// Whether a process passes the filter
+    pub fn matches(&self, process: &ProcessInfo) -> bool {
+        match self {
+            Filter::NameMatches(re) => re.is_match(&process.name),
+            Filter::NameIs(name) => process.name == *name,
+            Filter::Pid(pid) => process.pid == *pid,
+            Filter::UserMatches(re) => process.user.as_deref().is_some_and(|u| re.is_match(u)),
+            Filter::UserIs(user) => process.user.as_deref() == Some(user.as_str()),
+            Filter::Cpu(cmp, limit) => cmp.holds(process.cpu_usage as f64, *limit),
+            Filter::Memory(cmp, limit) => cmp.holds(process.memory_mb, *limit),
+            Filter::And(a, b) => a.matches(process) && b.matches(process),
+            Filter::Or(a, b) => a.matches(process) || b.matches(process),
+        }
+    }
+
+    /// Keep only the processes that pass the filter
+    pub fn apply(&self, processes: Vec<ProcessInfo>) -> Vec<ProcessInfo> {
+        processes.into_iter().filter(|p| self.matches(p)).collect()
+    }
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(s)?;
+        if tokens.is_empty() {
+            return Err("empty filter".to_string());
+        }
+
+        let mut parser = Parser { tokens, pos: 0 };
+        let filter = parser.or_expr()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(filter),
+            Some(token) => Err(format!("unexpected {}", token.describe())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    And,
+    Or,
+    Open,
+    Close,
+    Term(String),
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::And => "'&&'".to_string(),
+            Token::Or => "'||'".to_string(),
+            Token::Open => "'('".to_string(),
+            Token::Close => "')'".to_string(),
+            Token::Term(term) => format!("'{}'", term),
+        }
+    }
+}
+
+/// Split an expression into operators, parentheses and terms
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            _ if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '&' | '|' => {
+                chars.next();
+                if chars.next() != Some(c) {
+                    return Err(format!("expected '{}{}'", c, c));
+                }
+                tokens.push(if c == '&' { Token::And } else { Token::Or });
+            }
+            _ => {
+                let mut term = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' {
+                        break;
+                    }
+                    // "||" and "&&" end a term, a single '|' belongs to a regex
+                    if c == '&' || c == '|' {
+                        let mut ahead = chars.clone();
+                        ahead.next();
+                        if ahead.peek() == Some(&c) {
+                            break;
+                        }
+                    }
+                    chars.next();
+                    if c == '"' {
+                        loop {
+                            match chars.next() {
+                                Some('"') => break,
+                                Some(inner) => term.push(inner),
+                                None => return Err("unterminated quote".to_string()),
+                            }
+                        }
+                    } else {
+                        term.push(c);
+                    }
+                }
+
+                tokens.push(match term.to_ascii_lowercase().as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    _ => Token::Term(term),
+                });
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent parser over the tokens
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, expected: &Token) -> bool {
+        if self.tokens.get(self.pos) == Some(expected) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or_expr(&mut self) -> Result<Filter, String> {
+        let mut left = self.and_expr()?;
+        while self.eat(&Token::Or) {
+            let right = self.and_expr()?;
+            left = Filter::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn and_expr(&mut self) -> Result<Filter, String> {
+        let mut left = self.primary()?;
+        while self.eat(&Token::And) {
+            let right = self.primary()?;
+            left = Filter::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn primary(&mut self) -> Result<Filter, String> {
+        match self.next() {
+            Some(Token::Open) => {
+                let inner = self.or_expr()?;
+                if !self.eat(&Token::Close) {
+                    return Err("missing ')'".to_string());
+                }
+                Ok(inner)
+            }
+            Some(Token::Term(term)) => parse_term(&term),
+            Some(token) => Err(format!("unexpected {}", token.describe())),
+            None => Err("unexpected end of filter".to_string()),
+        }
+    }
+}
+
+/// Parse a single `field<op>value` term
+fn parse_term(term: &str) -> Result<Filter, String> {
+    let Some(op_start) = term.find(['~', '=', '>', '<']) else {
+        // A bare word searches names
+        return regex(term).map(Filter::NameMatches);
+    };
+
+    let field = term[..op_start].to_ascii_lowercase();
+    let rest = &term[op_start..];
+    let (op, value) = [">=", "<=", ">", "<", "=", "~"]
+        .iter()
+        .find_map(|op| rest.strip_prefix(op).map(|value| (*op, value)))
+        .expect("term contains an operator");
+
+    if value.is_empty() {
+        return Err(format!("missing value in '{}'", term));
+    }
+
+    match (field.as_str(), op) {
+        ("name", "~") => regex(value).map(Filter::NameMatches),
+        ("name", "=") => Ok(Filter::NameIs(value.to_string())),
+        ("user", "~") => regex(value).map(Filter::UserMatches),
+        ("user", "=") => Ok(Filter::UserIs(value.to_string())),
+        ("pid", "=") => value
+            .parse()
+            .map(Filter::Pid)
+            .map_err(|_| format!("invalid PID '{}'", value)),
+        ("cpu", _) if op != "~" => {
+            let limit = value
+                .trim_end_matches('%')
+                .parse()
+                .map_err(|_| format!("invalid CPU percentage '{}'", value))?;
+            Ok(Filter::Cpu(comparison(op), limit))
+        }
+        ("mem", _) if op != "~" => Ok(Filter::Memory(comparison(op), parse_megabytes(value)?)),
+        ("name" | "user" | "pid" | "cpu" | "mem", _) => {
+            Err(format!("operator '{}' not supported for {}", op, field))
+        }
+        _ => Err(format!(
+            "unknown field '{}' (expected name, pid, user, cpu or mem)",
+            field
+        )),
+    }
+}
+
+fn comparison(op: &str) -> Comparison {
+    match op {
+        ">" => Comparison::Greater,
+        ">=" => Comparison::GreaterOrEqual,
+        "<" => Comparison::Less,
+        "<=" => Comparison::LessOrEqual,
+        _ => Comparison::Equal,
+    }
+}
+
+fn regex(pattern: &str) -> Result<Regex, String> {
+    Regex::new(pattern).map_err(|e| format!("invalid regex '{}': {}", pattern, e))
+}
+
+/// Parse a memory size like "500MB", "1.5GB" or "200" (MB) into MB
+fn parse_megabytes(value: &str) -> Result<f64, String> {
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid memory size '{}'", value))?;
+    let factor = match unit.to_ascii_uppercase().as_str() {
+        "KB" | "K" => 1.0 / 1024.0,
+        "" | "MB" | "M" => 1.0,
+        "GB" | "G" => 1024.0,
+        _ => return Err(format!("unknown memory unit '{}' (use KB, MB or GB)", unit)),
+    };
+    Ok(number * factor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::snapshot::sample_process;
+
+    fn process(name: &str, pid: u32, user: &str, cpu: f32, memory_mb: f64) -> ProcessInfo {
+        ProcessInfo {
+            user: Some(user.to_string()),
+            ..sample_process(pid, name, cpu, memory_mb)
+        }
+    }
+
+    fn matches(expr: &str, process: &ProcessInfo) -> bool {
+        expr.parse::<Filter>().unwrap().matches(process)
+    }
+
+    #[test]
+    fn test_single_terms() {
+        let nginx = process("nginx", 80, "www", 25.0, 600.0);
+
+        assert!(matches("name~ngi", &nginx));
+        assert!(matches("nginx", &nginx));
+        assert!(!matches("name=ngin", &nginx));
+        assert!(matches("pid=80", &nginx));
+        assert!(matches("user=www", &nginx));
+        assert!(matches("user~^w+$", &nginx));
+        assert!(matches("cpu>20", &nginx));
+        assert!(!matches("cpu<=20", &nginx));
+        assert!(matches("mem>500MB", &nginx));
+        assert!(matches("mem<1GB", &nginx));
+        assert!(!matches("mem>614400K", &nginx));
+    }
+
+    #[test]
+    fn test_boolean_combinations() {
+        let nginx = process("nginx", 80, "www", 25.0, 600.0);
+        let bash = process("bash", 1, "root", 0.0, 5.0);
+
+        let filter: Filter = "name~nginx && cpu>20 || user=root".parse().unwrap();
+        assert!(filter.matches(&nginx));
+        assert!(filter.matches(&bash));
+
+        let filter: Filter = "name~nginx and (cpu>50 or mem>500MB)".parse().unwrap();
+        assert!(filter.matches(&nginx));
+        assert!(!filter.matches(&bash));
+
+        let filter: Filter = r#"name~"(nginx|php)" AND user=www"#.parse().unwrap();
+        assert!(filter.matches(&nginx));
+        // A single '|' stays inside the regex
+        assert!(matches("name~bash|nginx", &bash));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!("".parse::<Filter>().is_err());
+        assert!("cpu>abc".parse::<Filter>().is_err());
+        assert!("mem>5TB".parse::<Filter>().is_err());
+        assert!("size>5".parse::<Filter>().is_err());
+        assert!("pid>5".parse::<Filter>().is_err());
+        assert!("(name~a".parse::<Filter>().is_err());
+        assert!("name~a &&".parse::<Filter>().is_err());
+        assert!("name~[".parse::<Filter>().is_err());
+        assert!("a & b".parse::<Filter>().is_err());
+    }
+}
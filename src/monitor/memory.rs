@@ -1,13 +1,17 @@
 // Memory monitoring module
 // Provides RAM usage information
 
+use serde::{Deserialize, Serialize};
 use sysinfo::System;
 
 /// Memory information structure
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryInfo {
+    /// Total physical memory in GB
     pub total_gb: f64,
+    /// Used physical memory in GB
     pub used_gb: f64,
+    /// Used memory in percent (0-100)
     pub percentage: f64,
 }
 
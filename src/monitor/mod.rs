@@ -1,9 +1,29 @@
 // Monitor module - System information gathering
 // This module contains submodules for different system metrics
 
+/// CPU usage per core and globally
 pub mod cpu;
+/// Detailed information about a single process
+pub mod detail;
+/// Attached and removed disks and network interfaces
+pub mod device;
+/// Disk usage of mounted drives
 pub mod disk;
+/// Process filter expressions
+pub mod filter;
+/// RAM usage
 pub mod memory;
+/// Network traffic and speeds
 pub mod network;
+/// Running processes
 pub mod process;
+/// Per-subsystem refresh plans and collector timings
+pub mod refresh;
+/// Sending signals to processes
+pub mod signal;
+/// Complete point-in-time snapshots
+pub mod snapshot;
+/// `SystemMonitor` facade
 pub mod system;
+/// Parent/child process hierarchy
+pub mod tree;
@@ -1,19 +1,82 @@
 // Network monitoring module
-// Provides network traffic information
+// Provides network traffic information, in total and per interface
 
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::time::Duration;
 use sysinfo::Networks;
 
+/// Shortest time over which network speeds are computed
+///
+/// Refreshes sooner than that after the last measurement leave the counters
+/// alone, so the traffic is counted by the next, longer window.
+pub const MINIMUM_NETWORK_UPDATE_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Names of the loopback interface on Linux and macOS, see [`is_loopback`]
+pub const LOOPBACK_INTERFACES: [&str; 2] = ["lo", "lo0"];
+
+/// Whether an interface is loopback
+///
+/// Known by name on Linux and macOS, otherwise by having only loopback
+/// addresses, like "Loopback Pseudo-Interface 1" on Windows.
+///
+/// # Arguments
+/// * `name` - Interface name
+/// * `addresses` - Addresses of the interface
+pub fn is_loopback(name: &str, addresses: &[IpAddr]) -> bool {
+    LOOPBACK_INTERFACES.contains(&name)
+        || (!addresses.is_empty() && addresses.iter().all(|addr| addr.is_loopback()))
+}
+
 /// Network traffic information
-#[derive(Debug, Clone)]
+///
+/// The totals and speeds add up every interface, loopback included. The
+/// `selected_` ones only add up the interfaces selected by an
+/// [`InterfaceFilter`], which are the ones listed in `interfaces`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetworkInfo {
-    pub download_speed: f64,    // Bytes per second
-    pub upload_speed: f64,      // Bytes per second
-    pub total_received: u64,    // Total bytes received
-    pub total_transmitted: u64, // Total bytes transmitted
+    /// Download speed in bytes per second
+    pub download_speed: f64,
+    /// Upload speed in bytes per second
+    pub upload_speed: f64,
+    /// Total bytes received
+    pub total_received: u64,
+    /// Total bytes transmitted
+    pub total_transmitted: u64,
+    /// Download speed of the selected interfaces in bytes per second
+    #[serde(default)]
+    pub selected_download_speed: f64,
+    /// Upload speed of the selected interfaces in bytes per second
+    #[serde(default)]
+    pub selected_upload_speed: f64,
+    /// Total bytes received by the selected interfaces
+    #[serde(default)]
+    pub selected_received: u64,
+    /// Total bytes transmitted by the selected interfaces
+    #[serde(default)]
+    pub selected_transmitted: u64,
+    /// Traffic of each selected interface, by name
+    #[serde(default)]
+    pub interfaces: Vec<InterfaceInfo>,
+}
+
+/// Traffic of one network interface
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InterfaceInfo {
+    /// Interface name (e.g. eth0)
+    pub name: String,
+    /// Download speed in bytes per second
+    pub download_speed: f64,
+    /// Upload speed in bytes per second
+    pub upload_speed: f64,
+    /// Total bytes received
+    pub total_received: u64,
+    /// Total bytes transmitted
+    pub total_transmitted: u64,
 }
 
 impl NetworkInfo {
-    /// Create network info from current state
+    /// Create network info from current state, every interface selected
     pub fn new(
         download_speed: f64,
         upload_speed: f64,
@@ -25,6 +88,25 @@ impl NetworkInfo {
             upload_speed,
             total_received,
             total_transmitted,
+            selected_download_speed: download_speed,
+            selected_upload_speed: upload_speed,
+            selected_received: total_received,
+            selected_transmitted: total_transmitted,
+            interfaces: Vec::new(),
+        }
+    }
+
+    /// Add up the traffic of some interfaces, all of them selected
+    pub fn from_interfaces(interfaces: Vec<InterfaceInfo>) -> Self {
+        let totals = Self::new(
+            interfaces.iter().map(|i| i.download_speed).sum(),
+            interfaces.iter().map(|i| i.upload_speed).sum(),
+            interfaces.iter().map(|i| i.total_received).sum(),
+            interfaces.iter().map(|i| i.total_transmitted).sum(),
+        );
+        NetworkInfo {
+            interfaces,
+            ..totals
         }
     }
 
@@ -68,21 +150,209 @@ impl NetworkInfo {
     }
 }
 
-/// Get network statistics
+/// Which network interfaces are reported and counted in the totals
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InterfaceFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl InterfaceFilter {
+    /// Select interfaces by name with `*` and `?` wildcards
+    ///
+    /// Loopback is never selected unless an include pattern is exactly its
+    /// name, e.g. "lo"; wildcards such as "*" don't bring it back.
+    ///
+    /// # Arguments
+    /// * `include` - Only these interfaces (every one when empty)
+    /// * `exclude` - Never these interfaces, even if included
+    pub fn new(include: Vec<String>, exclude: Vec<String>) -> Self {
+        InterfaceFilter { include, exclude }
+    }
+
+    /// Whether an interface is selected, telling loopback by its name only
+    pub fn matches(&self, name: &str) -> bool {
+        self.matches_interface(name, is_loopback(name, &[]))
+    }
+
+    /// Whether an interface is selected
+    ///
+    /// # Arguments
+    /// * `name` - Interface name
+    /// * `loopback` - Whether it is loopback, see [`is_loopback`]
+    pub fn matches_interface(&self, name: &str, loopback: bool) -> bool {
+        if loopback && !self.include.iter().any(|p| p == name) {
+            return false;
+        }
+        (self.include.is_empty() || self.include.iter().any(|p| glob_match(p, name)))
+            && !self.exclude.iter().any(|p| glob_match(p, name))
+    }
+}
+
+/// Match a name against a pattern where `*` is any text and `?` any character
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    // Position after the last `*` in both, to retry from when a match fails
+    let mut star: Option<(usize, usize)> = None;
+    let (mut p, mut n) = (0, 0);
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((after_star, matched)) => {
+                    // Let the `*` swallow one more character
+                    p = after_star;
+                    n = matched + 1;
+                    star = Some((after_star, n));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Get network statistics of a refreshed interface list
+///
+/// Speeds are the bytes moved since the previous refresh of `networks`
+/// divided by `elapsed_secs` (0 when no time has passed). Interfaces that
+/// were just attached have moved nothing yet, so they don't cause a spike.
 ///
-/// Note: Speed calculation requires two measurements with a time interval.
-/// This function returns current totals, speed should be calculated by the caller.
-pub fn get_network_info() -> NetworkInfo {
-    let networks = Networks::new_with_refreshed_list();
+/// # Arguments
+/// * `networks` - Refreshed interface list
+/// * `elapsed_secs` - Seconds since the previous refresh
+/// * `filter` - Interfaces to report and add up
+pub fn get_network_info(
+    networks: &Networks,
+    elapsed_secs: f64,
+    filter: &InterfaceFilter,
+) -> NetworkInfo {
+    let speed = |bytes: u64| {
+        if elapsed_secs > 0.0 {
+            bytes as f64 / elapsed_secs
+        } else {
+            0.0
+        }
+    };
+
+    let (mut every, mut selected) = (Vec::new(), Vec::new());
+    for (name, network) in networks {
+        let interface = InterfaceInfo {
+            name: name.clone(),
+            download_speed: speed(network.received()),
+            upload_speed: speed(network.transmitted()),
+            total_received: network.total_received(),
+            total_transmitted: network.total_transmitted(),
+        };
+        let addresses: Vec<IpAddr> = network.ip_networks().iter().map(|n| n.addr).collect();
+        if filter.matches_interface(name, is_loopback(name, &addresses)) {
+            selected.push(interface.clone());
+        }
+        every.push(interface);
+    }
+    selected.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let all = NetworkInfo::from_interfaces(every);
+    NetworkInfo {
+        download_speed: all.download_speed,
+        upload_speed: all.upload_speed,
+        total_received: all.total_received,
+        total_transmitted: all.total_transmitted,
+        ..NetworkInfo::from_interfaces(selected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(include: &[&str], exclude: &[&str]) -> InterfaceFilter {
+        let owned = |list: &[&str]| list.iter().map(|p| p.to_string()).collect();
+        InterfaceFilter::new(owned(include), owned(exclude))
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("eth*", "eth0"));
+        assert!(glob_match("eth*", "eth"));
+        assert!(glob_match("*0", "wlan0"));
+        assert!(glob_match("veth*a*", "veth1b2a3"));
+        assert!(glob_match("tun?", "tun0"));
+        assert!(!glob_match("tun?", "tun10"));
+        assert!(!glob_match("eth*", "veth0"));
+        assert!(!glob_match("lo", "lo0"));
+    }
 
-    let mut total_received = 0u64;
-    let mut total_transmitted = 0u64;
+    #[test]
+    fn test_interface_filter() {
+        // Loopback is left out by default
+        let default = InterfaceFilter::default();
+        assert!(default.matches("eth0") && !default.matches("lo") && !default.matches("lo0"));
 
-    for (_name, network) in networks.iter() {
-        total_received += network.total_received();
-        total_transmitted += network.total_transmitted();
+        let uplink = filter(&["eth*", "wl*"], &[]);
+        assert!(uplink.matches("eth0") && uplink.matches("wlp2s0"));
+        assert!(!uplink.matches("docker0"));
+
+        // Exclusions win, and loopback stays out without being listed
+        let physical = filter(&[], &["docker*", "veth*"]);
+        assert!(physical.matches("eth0") && physical.matches("tun0"));
+        assert!(!physical.matches("docker0") && !physical.matches("lo"));
+        assert!(!filter(&["e*"], &["eth1"]).matches("eth1"));
+
+        // Only naming loopback brings it back
+        assert!(!filter(&["*"], &[]).matches("lo"));
+        assert!(filter(&["lo"], &[]).matches("lo"));
+        assert!(!filter(&["lo"], &["l*"]).matches("lo"));
+
+        // Loopback under another name, as on Windows
+        let windows = "Loopback Pseudo-Interface 1";
+        assert!(!default.matches_interface(windows, true));
+        assert!(filter(&[windows], &[]).matches_interface(windows, true));
     }
 
-    // Speed will be calculated by SystemMonitor using deltas
-    NetworkInfo::new(0.0, 0.0, total_received, total_transmitted)
+    #[test]
+    fn test_is_loopback() {
+        let addresses = |list: &[&str]| -> Vec<IpAddr> {
+            list.iter().map(|addr| addr.parse().unwrap()).collect()
+        };
+        assert!(is_loopback("lo", &[]));
+        assert!(is_loopback(
+            "Loopback Pseudo-Interface 1",
+            &addresses(&["127.0.0.1", "::1"])
+        ));
+        assert!(!is_loopback("eth0", &addresses(&["192.168.1.2", "::1"])));
+        // No address known, not loopback
+        assert!(!is_loopback("Ethernet", &[]));
+    }
+
+    #[test]
+    fn test_totals_add_up_interfaces() {
+        let interface = |name: &str, speed: f64, total: u64| InterfaceInfo {
+            name: name.to_string(),
+            download_speed: speed,
+            upload_speed: speed / 2.0,
+            total_received: total,
+            total_transmitted: total / 2,
+        };
+        let info = NetworkInfo::from_interfaces(vec![
+            interface("eth0", 1000.0, 4000),
+            interface("wlan0", 24.0, 96),
+        ]);
+        assert_eq!(info.download_speed, 1024.0);
+        assert_eq!(info.upload_speed, 512.0);
+        assert_eq!(info.total_received, 4096);
+        assert_eq!(info.total_transmitted, 2048);
+        assert_eq!(info.selected_download_speed, 1024.0);
+        assert_eq!(info.selected_received, 4096);
+        assert_eq!(info.interfaces.len(), 2);
+    }
 }
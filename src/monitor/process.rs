@@ -1,72 +1,76 @@
 // Process monitoring module
 // Provides information about running processes
 
-use sysinfo::{Pid, System};
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, System, ThreadKind, Users};
 
 /// Individual process information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessInfo {
+    /// Process name
     pub name: String,
+    /// Process ID
     pub pid: u32,
+    /// Parent process ID (None for root processes or if unknown)
+    pub parent_pid: Option<u32>,
+    /// Name of the user owning the process (None if unknown)
+    pub user: Option<String>,
+    /// CPU usage in percent (can exceed 100 on multi-core systems)
     pub cpu_usage: f32,
+    /// Resident memory in MB
     pub memory_mb: f64,
 }
 
 impl ProcessInfo {
     /// Create process info from sysinfo Process
-    pub fn new(pid: Pid, process: &sysinfo::Process) -> Self {
+    ///
+    /// # Arguments
+    /// * `pid` - Process ID
+    /// * `process` - sysinfo process entry
+    /// * `users` - User list used to resolve the owner name
+    pub fn new(pid: Pid, process: &sysinfo::Process, users: &Users) -> Self {
         const MB: f64 = 1_048_576.0; // 1024^2
 
         ProcessInfo {
             name: process.name().to_string_lossy().to_string(),
             pid: pid.as_u32(),
+            parent_pid: process.parent().map(|parent| parent.as_u32()),
+            user: process
+                .user_id()
+                .and_then(|uid| users.get_user_by_id(uid))
+                .map(|user| user.name().to_string()),
             cpu_usage: process.cpu_usage(),
             memory_mb: process.memory() as f64 / MB,
         }
     }
 }
 
-/// Get top N processes sorted by CPU usage
-pub fn get_top_processes_by_cpu(sys: &System, n: usize) -> Vec<ProcessInfo> {
-    let mut processes: Vec<ProcessInfo> = sys
-        .processes()
+/// Get every running process (unsorted)
+///
+/// Userland threads, which sysinfo lists next to processes on Linux, are
+/// skipped so every program appears once.
+pub fn get_all_processes(sys: &System, users: &Users) -> Vec<ProcessInfo> {
+    sys.processes()
         .iter()
-        .map(|(pid, process)| ProcessInfo::new(*pid, process))
-        .collect();
+        .filter(|(_, process)| process.thread_kind() != Some(ThreadKind::Userland))
+        .map(|(pid, process)| ProcessInfo::new(*pid, process, users))
+        .collect()
+}
 
+/// Get top N processes sorted by CPU usage
+pub fn get_top_processes_by_cpu(mut processes: Vec<ProcessInfo>, n: usize) -> Vec<ProcessInfo> {
     // Sort by CPU usage (descending)
-    processes.sort_by(|a, b| b.cpu_usage.partial_cmp(&a.cpu_usage).unwrap());
+    processes.sort_by(|a, b| b.cpu_usage.total_cmp(&a.cpu_usage));
 
     // Take top N
     processes.into_iter().take(n).collect()
 }
 
 /// Get top N processes sorted by memory usage
-pub fn get_top_processes_by_memory(sys: &System, n: usize) -> Vec<ProcessInfo> {
-    let mut processes: Vec<ProcessInfo> = sys
-        .processes()
-        .iter()
-        .map(|(pid, process)| ProcessInfo::new(*pid, process))
-        .collect();
-
+pub fn get_top_processes_by_memory(mut processes: Vec<ProcessInfo>, n: usize) -> Vec<ProcessInfo> {
     // Sort by memory usage (descending)
-    processes.sort_by(|a, b| b.memory_mb.partial_cmp(&a.memory_mb).unwrap());
+    processes.sort_by(|a, b| b.memory_mb.total_cmp(&a.memory_mb));
 
     // Take top N
     processes.into_iter().take(n).collect()
 }
-
-/// Get top 3 busiest CPU cores
-pub fn get_top_cores(cpus: &[sysinfo::Cpu], n: usize) -> Vec<(usize, f32)> {
-    let mut cores: Vec<(usize, f32)> = cpus
-        .iter()
-        .enumerate()
-        .map(|(index, cpu)| (index, cpu.cpu_usage()))
-        .collect();
-
-    // Sort by usage (descending)
-    cores.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-
-    // Take top N
-    cores.into_iter().take(n).collect()
-}
@@ -0,0 +1,163 @@
+// Refresh module
+// Which subsystems `SystemMonitor::refresh` updates, how often, and how long
+// each collector took
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// A group of metrics that is collected together
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Subsystem {
+    /// Global and per-core CPU usage
+    Cpu,
+    /// RAM usage
+    Memory,
+    /// Disk usage per mount point
+    Disks,
+    /// Network traffic and speeds
+    Network,
+    /// Every process, with its owner (by far the most expensive)
+    Processes,
+}
+
+impl Subsystem {
+    /// Every subsystem in collection order
+    pub const ALL: [Subsystem; 5] = [
+        Subsystem::Cpu,
+        Subsystem::Memory,
+        Subsystem::Disks,
+        Subsystem::Network,
+        Subsystem::Processes,
+    ];
+
+    /// Position in [`Subsystem::ALL`]
+    pub(crate) fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Whether the values are rates computed between two samples
+    pub(crate) fn is_rate(self) -> bool {
+        matches!(
+            self,
+            Subsystem::Cpu | Subsystem::Network | Subsystem::Processes
+        )
+    }
+}
+
+impl fmt::Display for Subsystem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Subsystem::Cpu => "cpu",
+            Subsystem::Memory => "memory",
+            Subsystem::Disks => "disks",
+            Subsystem::Network => "network",
+            Subsystem::Processes => "processes",
+        })
+    }
+}
+
+impl FromStr for Subsystem {
+    type Err = String;
+
+    /// Parse "cpu", "memory", "disks", "network" or "processes"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase();
+        Subsystem::ALL
+            .into_iter()
+            .find(|subsystem| subsystem.to_string() == name)
+            .ok_or_else(|| {
+                format!(
+                    "unknown subsystem '{}' (cpu, memory, disks, network or processes)",
+                    s.trim()
+                )
+            })
+    }
+}
+
+/// Which subsystems are refreshed, and at most how often
+///
+/// The default refreshes every subsystem on every call to
+/// [`SystemMonitor::refresh`](super::system::SystemMonitor::refresh).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefreshPlan {
+    /// Minimum time between two refreshes, None if never refreshed
+    intervals: [Option<Duration>; 5],
+}
+
+impl RefreshPlan {
+    /// Refresh every subsystem on every call
+    pub fn all() -> Self {
+        Self::only(Subsystem::ALL)
+    }
+
+    /// Refresh only these subsystems, on every call
+    pub fn only(subsystems: impl IntoIterator<Item = Subsystem>) -> Self {
+        let mut intervals = [None; 5];
+        for subsystem in subsystems {
+            intervals[subsystem.index()] = Some(Duration::ZERO);
+        }
+        RefreshPlan { intervals }
+    }
+
+    /// Refresh a subsystem at most once per `interval`
+    ///
+    /// Has no effect on subsystems that are not refreshed at all.
+    pub fn with_interval(mut self, subsystem: Subsystem, interval: Duration) -> Self {
+        if let Some(current) = &mut self.intervals[subsystem.index()] {
+            *current = interval;
+        }
+        self
+    }
+
+    /// Minimum time between two refreshes (None if never refreshed)
+    pub fn interval(&self, subsystem: Subsystem) -> Option<Duration> {
+        self.intervals[subsystem.index()]
+    }
+
+    /// Whether the subsystem is refreshed at all
+    pub fn is_enabled(&self, subsystem: Subsystem) -> bool {
+        self.interval(subsystem).is_some()
+    }
+}
+
+impl Default for RefreshPlan {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+/// How long one collector took during the last refresh
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollectorTiming {
+    /// The refreshed subsystem
+    pub subsystem: Subsystem,
+    /// Time spent refreshing it
+    pub duration: Duration,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refresh_plan() {
+        let plan = RefreshPlan::only([Subsystem::Cpu, Subsystem::Processes])
+            .with_interval(Subsystem::Processes, Duration::from_secs(5))
+            .with_interval(Subsystem::Disks, Duration::from_secs(60));
+
+        assert_eq!(plan.interval(Subsystem::Cpu), Some(Duration::ZERO));
+        assert_eq!(
+            plan.interval(Subsystem::Processes),
+            Some(Duration::from_secs(5))
+        );
+        // Intervals don't switch subsystems on
+        assert!(!plan.is_enabled(Subsystem::Disks));
+        assert!(RefreshPlan::default().is_enabled(Subsystem::Disks));
+
+        assert_eq!("Processes".parse(), Ok(Subsystem::Processes));
+        assert!("load".parse::<Subsystem>().is_err());
+    }
+}
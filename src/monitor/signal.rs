@@ -0,0 +1,247 @@
+// Signal module
+// Sends signals (TERM, KILL, STOP, CONT, ...) to processes
+
+use super::process::ProcessInfo;
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+/// Signal numbers of the current platform
+#[cfg(unix)]
+mod numbers {
+    pub const HUP: i32 = libc::SIGHUP;
+    pub const INT: i32 = libc::SIGINT;
+    pub const QUIT: i32 = libc::SIGQUIT;
+    pub const KILL: i32 = libc::SIGKILL;
+    pub const USR1: i32 = libc::SIGUSR1;
+    pub const USR2: i32 = libc::SIGUSR2;
+    pub const TERM: i32 = libc::SIGTERM;
+    pub const CONT: i32 = libc::SIGCONT;
+    pub const STOP: i32 = libc::SIGSTOP;
+    pub const TSTP: i32 = libc::SIGTSTP;
+}
+
+/// Linux numbering, only used for parsing where signals can't be sent
+#[cfg(not(unix))]
+mod numbers {
+    pub const HUP: i32 = 1;
+    pub const INT: i32 = 2;
+    pub const QUIT: i32 = 3;
+    pub const KILL: i32 = 9;
+    pub const USR1: i32 = 10;
+    pub const USR2: i32 = 12;
+    pub const TERM: i32 = 15;
+    pub const CONT: i32 = 18;
+    pub const STOP: i32 = 19;
+    pub const TSTP: i32 = 20;
+}
+
+/// Signals that can be given by name
+const NAMED: [(&str, i32); 10] = [
+    ("HUP", numbers::HUP),
+    ("INT", numbers::INT),
+    ("QUIT", numbers::QUIT),
+    ("KILL", numbers::KILL),
+    ("USR1", numbers::USR1),
+    ("USR2", numbers::USR2),
+    ("TERM", numbers::TERM),
+    ("CONT", numbers::CONT),
+    ("STOP", numbers::STOP),
+    ("TSTP", numbers::TSTP),
+];
+
+/// Highest signal number accepted (covers Linux real-time signals)
+const MAX_SIGNAL: i32 = 64;
+
+/// A signal that can be sent to a process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signal(i32);
+
+impl Signal {
+    /// Ask the process to terminate
+    pub const TERM: Signal = Signal(numbers::TERM);
+    /// Terminate the process immediately (cannot be caught)
+    pub const KILL: Signal = Signal(numbers::KILL);
+    /// Pause the process (cannot be caught)
+    pub const STOP: Signal = Signal(numbers::STOP);
+    /// Resume a stopped process
+    pub const CONT: Signal = Signal(numbers::CONT);
+
+    /// Signal number on this platform
+    pub fn number(self) -> i32 {
+        self.0
+    }
+}
+
+impl fmt::Display for Signal {
+    /// Shows "SIGTERM" for named signals and "signal 40" otherwise
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match NAMED.iter().find(|(_, number)| *number == self.0) {
+            Some((name, _)) => write!(f, "SIG{}", name),
+            None => write!(f, "signal {}", self.0),
+        }
+    }
+}
+
+impl FromStr for Signal {
+    type Err = String;
+
+    /// Parse "TERM", "sigterm", "SIGTERM" or a number like "9"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+
+        if let Ok(number) = trimmed.parse::<i32>() {
+            return if (1..=MAX_SIGNAL).contains(&number) {
+                Ok(Signal(number))
+            } else {
+                Err(format!(
+                    "signal number must be between 1 and {}",
+                    MAX_SIGNAL
+                ))
+            };
+        }
+
+        let upper = trimmed.to_ascii_uppercase();
+        let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+        NAMED
+            .iter()
+            .find(|(known, _)| *known == name)
+            .map(|(_, number)| Signal(*number))
+            .ok_or_else(|| format!("unknown signal '{}'", trimmed))
+    }
+}
+
+/// Send a signal to a process
+///
+/// Errors come straight from the OS, so a process owned by another user
+/// reports "Operation not permitted". Signalling sysmon itself is refused.
+///
+/// # Arguments
+/// * `pid` - Target process ID
+/// * `signal` - Signal to send
+pub fn send_signal(pid: u32, signal: Signal) -> io::Result<()> {
+    if pid == std::process::id() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "refusing to signal sysmon itself",
+        ));
+    }
+
+    platform_send(pid, signal)
+}
+
+#[cfg(unix)]
+fn platform_send(pid: u32, signal: Signal) -> io::Result<()> {
+    // PIDs above i32::MAX would turn into process group targets
+    let pid = libc::pid_t::try_from(pid)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid process ID"))?;
+    if pid <= 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "invalid process ID",
+        ));
+    }
+
+    // SAFETY: kill() has no memory safety requirements
+    let result = unsafe { libc::kill(pid, signal.number()) };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(unix))]
+fn platform_send(_pid: u32, _signal: Signal) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "sending signals is only supported on Unix",
+    ))
+}
+
+/// Processes whose name contains `pattern`, sysmon itself and PID 1 excluded
+///
+/// An empty pattern matches nothing rather than every process.
+///
+/// # Arguments
+/// * `processes` - Candidate processes
+/// * `pattern` - Case-sensitive substring of the process name
+pub fn matching_processes(processes: Vec<ProcessInfo>, pattern: &str) -> Vec<ProcessInfo> {
+    if pattern.trim().is_empty() {
+        return Vec::new();
+    }
+
+    let own_pid = std::process::id();
+    processes
+        .into_iter()
+        .filter(|process| {
+            process.pid != own_pid && process.pid != 1 && process.name.contains(pattern)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::snapshot::sample_process;
+
+    fn process(pid: u32, name: &str) -> ProcessInfo {
+        sample_process(pid, name, 0.0, 0.0)
+    }
+
+    #[test]
+    fn test_parse_signal() {
+        assert_eq!("TERM".parse::<Signal>(), Ok(Signal::TERM));
+        assert_eq!("sigkill".parse::<Signal>(), Ok(Signal::KILL));
+        assert_eq!(" SIGSTOP ".parse::<Signal>(), Ok(Signal::STOP));
+        assert_eq!("40".parse::<Signal>().unwrap().number(), 40);
+        assert!("0".parse::<Signal>().is_err());
+        assert!("BOGUS".parse::<Signal>().is_err());
+    }
+
+    #[test]
+    fn test_display_signal() {
+        assert_eq!(Signal::TERM.to_string(), "SIGTERM");
+        assert_eq!(Signal::CONT.to_string(), "SIGCONT");
+        assert_eq!("40".parse::<Signal>().unwrap().to_string(), "signal 40");
+    }
+
+    #[test]
+    fn test_matching_excludes_self() {
+        let own = std::process::id();
+        let processes = vec![
+            process(own, "sysmon"),
+            process(own + 1, "sysmon-helper"),
+            process(own + 2, "bash"),
+        ];
+
+        let matched = matching_processes(processes.clone(), "sysmon");
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].name, "sysmon-helper");
+
+        // Neither init nor an empty pattern matches
+        let mut with_init = processes;
+        with_init.push(process(1, "systemd"));
+        assert!(matching_processes(with_init.clone(), "systemd").is_empty());
+        assert!(matching_processes(with_init, " ").is_empty());
+    }
+
+    #[test]
+    fn test_refuses_to_signal_self() {
+        let err = send_signal(std::process::id(), Signal::TERM).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_send_signal_to_child() {
+        use std::os::unix::process::ExitStatusExt;
+        use std::process::Command;
+
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        send_signal(child.id(), Signal::TERM).unwrap();
+
+        let status = child.wait().unwrap();
+        assert_eq!(status.signal(), Some(Signal::TERM.number()));
+    }
+}
@@ -0,0 +1,184 @@
+// Snapshot module
+// Captures one complete, serializable view of the system at a point in time
+
+use super::cpu::CpuInfo;
+use super::disk::DiskInfo;
+use super::memory::MemoryInfo;
+use super::network::NetworkInfo;
+use super::process::ProcessInfo;
+use super::system::SystemMonitor;
+use super::tree::{self, ProcessNode};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Version of the snapshot schema
+///
+/// Bump this whenever a field is renamed, removed or changes meaning.
+/// Adding new fields is backwards compatible and does not require a bump.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Complete system snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Schema version, see [`SCHEMA_VERSION`]
+    pub schema_version: u32,
+    /// Collection time in milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    /// System uptime in seconds
+    pub uptime_secs: u64,
+    /// CPU usage
+    pub cpu: CpuInfo,
+    /// Memory usage
+    pub memory: MemoryInfo,
+    /// Mounted disks (drives under 1 GB are skipped)
+    pub disks: Vec<DiskInfo>,
+    /// Network traffic of the selected interfaces, in total and per interface
+    pub network: NetworkInfo,
+    /// Busiest processes by CPU usage
+    pub top_processes_cpu: Vec<ProcessInfo>,
+    /// Busiest processes by memory usage
+    pub top_processes_memory: Vec<ProcessInfo>,
+    /// Every process as a parent/child tree (only collected on request)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub process_tree: Vec<ProcessNode>,
+}
+
+/// Number of processes kept in the top lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TopCounts {
+    /// Busiest processes by CPU usage
+    pub cpu: usize,
+    /// Busiest processes by memory usage
+    pub memory: usize,
+}
+
+impl TopCounts {
+    /// Default counts: 10/5 for the detailed view, 5/3 otherwise
+    pub fn for_view(detailed: bool) -> Self {
+        if detailed {
+            TopCounts { cpu: 10, memory: 5 }
+        } else {
+            TopCounts { cpu: 5, memory: 3 }
+        }
+    }
+}
+
+impl Snapshot {
+    /// Collect a snapshot from an already refreshed monitor
+    ///
+    /// # Arguments
+    /// * `monitor` - System monitor instance
+    /// * `detailed` - If true, keeps 10/5 top processes; if false, 5/3
+    pub fn collect(monitor: &mut SystemMonitor, detailed: bool) -> Self {
+        Self::collect_top(monitor, TopCounts::for_view(detailed))
+    }
+
+    /// Collect a snapshot keeping a custom number of top processes
+    ///
+    /// # Arguments
+    /// * `monitor` - System monitor instance
+    /// * `top` - Length of the top process lists
+    pub fn collect_top(monitor: &mut SystemMonitor, top: TopCounts) -> Self {
+        Snapshot {
+            schema_version: SCHEMA_VERSION,
+            timestamp_ms: now_millis(),
+            uptime_secs: monitor.uptime(),
+            cpu: monitor.cpu_info(),
+            memory: monitor.memory_info(),
+            disks: monitor.disks_info(),
+            network: monitor.network_info(),
+            top_processes_cpu: monitor.top_processes_by_cpu(top.cpu),
+            top_processes_memory: monitor.top_processes_by_memory(top.memory),
+            process_tree: Vec::new(),
+        }
+    }
+
+    /// Add the full process tree to the snapshot
+    pub fn with_process_tree(mut self, monitor: &SystemMonitor) -> Self {
+        self.process_tree = tree::build_tree(&monitor.processes());
+        self
+    }
+}
+
+/// Current wall-clock time in milliseconds since the Unix epoch
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Hand-built snapshot shared by the output tests
+#[cfg(test)]
+pub(crate) fn sample_snapshot() -> Snapshot {
+    use super::cpu::CoreInfo;
+    use super::network::InterfaceInfo;
+
+    Snapshot {
+        schema_version: SCHEMA_VERSION,
+        timestamp_ms: 1_700_000_000_000,
+        uptime_secs: 3600,
+        cpu: CpuInfo {
+            global_usage: 25.0,
+            cores: vec![
+                CoreInfo {
+                    index: 0,
+                    usage: 20.0,
+                },
+                CoreInfo {
+                    index: 1,
+                    usage: 30.0,
+                },
+            ],
+        },
+        memory: MemoryInfo {
+            total_gb: 16.0,
+            used_gb: 8.0,
+            percentage: 50.0,
+        },
+        disks: vec![DiskInfo {
+            name: "sda1".to_string(),
+            mount_point: "/".to_string(),
+            total_gb: 100.0,
+            available_gb: 40.0,
+            used_gb: 60.0,
+            percentage: 60.0,
+        }],
+        network: NetworkInfo::from_interfaces(vec![InterfaceInfo {
+            name: "eth0".to_string(),
+            download_speed: 1024.0,
+            upload_speed: 512.0,
+            total_received: 10_000,
+            total_transmitted: 5_000,
+        }]),
+        top_processes_cpu: vec![ProcessInfo {
+            parent_pid: Some(1),
+            user: Some("dev".to_string()),
+            ..sample_process(42, "cargo", 12.5, 256.0)
+        }],
+        top_processes_memory: vec![],
+        process_tree: vec![],
+    }
+}
+
+/// The sample snapshot taken at another time with another global CPU usage
+#[cfg(test)]
+pub(crate) fn sample_snapshot_at(secs: u64, cpu: f32) -> Snapshot {
+    let mut snapshot = sample_snapshot();
+    snapshot.timestamp_ms = secs * 1000;
+    snapshot.cpu.global_usage = cpu;
+    snapshot
+}
+
+/// Hand-built process without parent and user, shared by the process tests
+#[cfg(test)]
+pub(crate) fn sample_process(pid: u32, name: &str, cpu_usage: f32, memory_mb: f64) -> ProcessInfo {
+    ProcessInfo {
+        name: name.to_string(),
+        pid,
+        parent_pid: None,
+        user: None,
+        cpu_usage,
+        memory_mb,
+    }
+}
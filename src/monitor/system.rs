@@ -2,31 +2,173 @@
 // Main facade for accessing all system information
 
 use super::cpu::CpuInfo;
+use super::detail::ProcessDetail;
+use super::device::{self, DeviceEvent, DeviceKind, DEVICE_SCAN_INTERVAL};
 use super::disk::DiskInfo;
+use super::filter::Filter;
 use super::memory::MemoryInfo;
-use super::network::NetworkInfo;
+use super::network::{InterfaceFilter, NetworkInfo, MINIMUM_NETWORK_UPDATE_INTERVAL};
 use super::process::ProcessInfo;
+use super::refresh::{CollectorTiming, RefreshPlan, Subsystem};
 use std::time::Instant;
-use sysinfo::System;
+use sysinfo::{
+    Disks, MemoryRefreshKind, Networks, Pid, ProcessRefreshKind, ProcessesToUpdate, System, Users,
+    MINIMUM_CPU_UPDATE_INTERVAL,
+};
 
 /// Main system monitor facade
 pub struct SystemMonitor {
     sys: System,
-    last_network_check: Option<(Instant, u64, u64)>, // (time, rx, tx)
+    users: Users,
+    disks: Disks,
+    networks: Networks,
+    process_filter: Option<Filter>,
+    interface_filter: InterfaceFilter,
+    last_network_check: Option<Instant>,
+    network_window_secs: f64,
+    plan: RefreshPlan,
+    refreshed_at: [Option<Instant>; 5],
+    sampled_at: [Option<Instant>; 5],
+    timings: Vec<CollectorTiming>,
+    disks_scanned_at: Option<Instant>,
+    networks_scanned_at: Option<Instant>,
+    device_events: Vec<DeviceEvent>,
+    disk_usage: Vec<DiskInfo>,
+    traffic: NetworkInfo,
 }
 
 impl SystemMonitor {
-    /// Create a new system monitor
+    /// Create a new system monitor that refreshes everything
     pub fn new() -> Self {
-        SystemMonitor {
-            sys: System::new_all(),
+        Self::with_plan(RefreshPlan::all())
+    }
+
+    /// Create a system monitor that only refreshes some subsystems
+    ///
+    /// The enabled subsystems are collected once right away. For memory and
+    /// disks that counts as a first [`refresh`](Self::refresh), so they
+    /// aren't collected again until their interval has passed. CPU usage,
+    /// process CPU usage and network speeds only get a baseline, see
+    /// [`refresh`](Self::refresh).
+    pub fn with_plan(plan: RefreshPlan) -> Self {
+        let mut monitor = SystemMonitor {
+            sys: System::new(),
+            users: Users::new(),
+            disks: Disks::new(),
+            networks: Networks::new(),
+            process_filter: None,
+            interface_filter: InterfaceFilter::default(),
             last_network_check: None,
-        }
+            network_window_secs: 0.0,
+            plan,
+            refreshed_at: [None; 5],
+            sampled_at: [None; 5],
+            timings: Vec::new(),
+            disks_scanned_at: None,
+            networks_scanned_at: None,
+            device_events: Vec::new(),
+            disk_usage: Vec::new(),
+            traffic: NetworkInfo::new(0.0, 0.0, 0, 0),
+        };
+        monitor.refresh();
+        monitor
+    }
+
+    /// Change which subsystems are refreshed and how often
+    pub fn set_refresh_plan(&mut self, plan: RefreshPlan) {
+        self.plan = plan;
     }
 
-    /// Refresh all system information
+    /// Refresh the subsystems of the plan whose interval has passed
+    ///
+    /// Subsystems that are not refreshed keep their last values. The time
+    /// spent on each one is available from [`refresh_timings`](Self::refresh_timings).
+    ///
+    /// CPU usage, process CPU usage and network speeds are rates between two
+    /// samples. A sample taken less than `MINIMUM_CPU_UPDATE_INTERVAL` after
+    /// the previous one gives no meaningful rate, so it doesn't count as a
+    /// refresh and the next call samples again whatever the interval.
+    ///
+    /// Disks and network interfaces are refreshed in place; at most every
+    /// [`DEVICE_SCAN_INTERVAL`] they are also listed again, and the ones that
+    /// were attached or removed are reported by [`device_events`](Self::device_events).
     pub fn refresh(&mut self) {
-        self.sys.refresh_all();
+        self.timings.clear();
+        self.device_events.clear();
+        for subsystem in Subsystem::ALL {
+            let Some(interval) = self.plan.interval(subsystem) else {
+                continue;
+            };
+            let start = Instant::now();
+            let due = self.refreshed_at[subsystem.index()]
+                .is_none_or(|at| start.duration_since(at) >= interval);
+            if !due {
+                continue;
+            }
+
+            self.refresh_subsystem(subsystem);
+            let previous = self.sampled_at[subsystem.index()].replace(start);
+            let meaningful = !subsystem.is_rate()
+                || previous
+                    .is_some_and(|at| start.duration_since(at) >= MINIMUM_CPU_UPDATE_INTERVAL);
+            if meaningful {
+                self.refreshed_at[subsystem.index()] = Some(start);
+            }
+            self.timings.push(CollectorTiming {
+                subsystem,
+                duration: start.elapsed(),
+            });
+        }
+    }
+
+    /// Collectors that ran during the last [`refresh`](Self::refresh), in order
+    pub fn refresh_timings(&self) -> &[CollectorTiming] {
+        &self.timings
+    }
+
+    /// Disks and network interfaces attached or removed during the last
+    /// [`refresh`](Self::refresh)
+    pub fn device_events(&self) -> &[DeviceEvent] {
+        &self.device_events
+    }
+
+    fn refresh_subsystem(&mut self, subsystem: Subsystem) {
+        match subsystem {
+            Subsystem::Cpu => self.sys.refresh_cpu_usage(),
+            Subsystem::Memory => self
+                .sys
+                .refresh_memory_specifics(MemoryRefreshKind::new().with_ram()),
+            Subsystem::Disks => self.refresh_disks(),
+            Subsystem::Network => self.refresh_networks(),
+            Subsystem::Processes => {
+                self.sys.refresh_processes_specifics(
+                    ProcessesToUpdate::All,
+                    true,
+                    ProcessRefreshKind::everything(),
+                );
+                self.users.refresh_list();
+            }
+        }
+    }
+
+    /// Only report processes matching `filter` (None reports every process)
+    ///
+    /// Applies to the process list, the top-N lists and the process tree.
+    pub fn set_process_filter(&mut self, filter: Option<Filter>) {
+        self.process_filter = filter;
+    }
+
+    /// Only report and add up the network interfaces selected by `filter`
+    ///
+    /// Also applies to the values of the last network refresh. By default
+    /// every interface but loopback is selected.
+    pub fn set_interface_filter(&mut self, filter: InterfaceFilter) {
+        self.interface_filter = filter;
+        self.traffic = super::network::get_network_info(
+            &self.networks,
+            self.network_window_secs,
+            &self.interface_filter,
+        );
     }
 
     /// Get CPU information
@@ -41,59 +183,105 @@ impl SystemMonitor {
 
     /// Get disk information
     pub fn disks_info(&self) -> Vec<DiskInfo> {
-        super::disk::get_disks_info()
+        self.disk_usage.clone()
+    }
+
+    /// Get every running process that passes the filter (unsorted)
+    pub fn processes(&self) -> Vec<ProcessInfo> {
+        let processes = super::process::get_all_processes(&self.sys, &self.users);
+        match &self.process_filter {
+            Some(filter) => filter.apply(processes),
+            None => processes,
+        }
+    }
+
+    /// Get everything known about one process (None if it does not exist)
+    pub fn process_detail(&self, pid: u32) -> Option<ProcessDetail> {
+        let pid = Pid::from_u32(pid);
+        self.sys
+            .process(pid)
+            .map(|process| ProcessDetail::new(pid, process, &self.users))
     }
 
     /// Get top N processes by CPU usage
     pub fn top_processes_by_cpu(&self, n: usize) -> Vec<ProcessInfo> {
-        super::process::get_top_processes_by_cpu(&self.sys, n)
+        super::process::get_top_processes_by_cpu(self.processes(), n)
     }
 
     /// Get top N processes by memory usage
     pub fn top_processes_by_memory(&self, n: usize) -> Vec<ProcessInfo> {
-        super::process::get_top_processes_by_memory(&self.sys, n)
-    }
-
-    /// Get top N busiest CPU cores
-    pub fn top_cpu_cores(&self, n: usize) -> Vec<(usize, f32)> {
-        super::process::get_top_cores(self.sys.cpus(), n)
+        super::process::get_top_processes_by_memory(self.processes(), n)
     }
 
-    /// Get total number of CPU cores
-    pub fn cpu_count(&self) -> usize {
-        self.sys.cpus().len()
+    /// Get network information with the speed since the previous refresh
+    pub fn network_info(&self) -> NetworkInfo {
+        self.traffic.clone()
     }
 
-    /// Get network information with speed calculation
-    pub fn network_info(&mut self) -> NetworkInfo {
-        let current = super::network::get_network_info();
+    /// Update the known disks, listing them again when a scan is due
+    fn refresh_disks(&mut self) {
         let now = Instant::now();
+        if is_scan_due(self.disks_scanned_at, now) {
+            let before: Vec<String> = self
+                .disk_usage
+                .iter()
+                .map(|d| d.mount_point.clone())
+                .collect();
+            self.disks.refresh_list();
+            self.disk_usage = super::disk::get_disks_info(&self.disks);
 
-        if let Some((last_time, last_rx, last_tx)) = self.last_network_check {
-            let elapsed = now.duration_since(last_time).as_secs_f64();
-
-            if elapsed > 0.0 {
-                let rx_diff = current.total_received.saturating_sub(last_rx) as f64;
-                let tx_diff = current.total_transmitted.saturating_sub(last_tx) as f64;
+            // The first scan finds every disk, which is no news
+            if self.disks_scanned_at.is_some() {
+                let after: Vec<String> = self
+                    .disk_usage
+                    .iter()
+                    .map(|d| d.mount_point.clone())
+                    .collect();
+                self.device_events
+                    .extend(device::compare(DeviceKind::Disk, &before, &after));
+            }
+            self.disks_scanned_at = Some(now);
+        } else {
+            self.disks.refresh();
+            self.disk_usage = super::disk::get_disks_info(&self.disks);
+        }
+    }
 
-                let download_speed = rx_diff / elapsed;
-                let upload_speed = tx_diff / elapsed;
+    /// Update the interface counters and the speed since the last
+    /// measurement, listing the interfaces again when a scan is due
+    ///
+    /// Within [`MINIMUM_NETWORK_UPDATE_INTERVAL`] of the last measurement
+    /// nothing is updated, which would give a meaningless rate.
+    fn refresh_networks(&mut self) {
+        let now = Instant::now();
+        let elapsed = self.last_network_check.map(|last| now.duration_since(last));
+        if elapsed.is_some_and(|elapsed| elapsed < MINIMUM_NETWORK_UPDATE_INTERVAL) {
+            return;
+        }
 
-                self.last_network_check =
-                    Some((now, current.total_received, current.total_transmitted));
+        if is_scan_due(self.networks_scanned_at, now) {
+            let before: Vec<String> = self.networks.keys().cloned().collect();
+            // Also updates the counters of the interfaces that are still there
+            self.networks.refresh_list();
 
-                return NetworkInfo::new(
-                    download_speed,
-                    upload_speed,
-                    current.total_received,
-                    current.total_transmitted,
-                );
+            if self.networks_scanned_at.is_some() {
+                let after: Vec<String> = self.networks.keys().cloned().collect();
+                self.device_events
+                    .extend(device::compare(DeviceKind::Network, &before, &after));
             }
+            self.networks_scanned_at = Some(now);
+        } else {
+            self.networks.refresh();
         }
 
-        // First call or error - store current values
-        self.last_network_check = Some((now, current.total_received, current.total_transmitted));
-        current
+        // The first measurement has no previous one to compute speeds from
+        self.network_window_secs = elapsed.map_or(0.0, |elapsed| elapsed.as_secs_f64());
+        self.traffic = super::network::get_network_info(
+            &self.networks,
+            self.network_window_secs,
+            &self.interface_filter,
+        );
+        self.last_network_check = Some(now);
     }
 
     /// Get system uptime in seconds
@@ -102,6 +290,11 @@ impl SystemMonitor {
     }
 }
 
+/// Whether the device list was never scanned or the last scan is old enough
+fn is_scan_due(scanned_at: Option<Instant>, now: Instant) -> bool {
+    scanned_at.is_none_or(|at| now.duration_since(at) >= DEVICE_SCAN_INTERVAL)
+}
+
 impl Default for SystemMonitor {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,233 @@
+// Process tree module
+// Builds the parent/child hierarchy of processes with per-subtree totals
+
+use super::process::ProcessInfo;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+/// A process with its children and the totals of its whole subtree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessNode {
+    /// The process itself
+    pub process: ProcessInfo,
+    /// CPU usage of the process and all its descendants
+    pub total_cpu_usage: f32,
+    /// Memory of the process and all its descendants in MB
+    pub total_memory_mb: f64,
+    /// Child processes, ordered by PID
+    pub children: Vec<ProcessNode>,
+}
+
+impl ProcessNode {
+    fn new(process: ProcessInfo, children: Vec<ProcessNode>) -> Self {
+        let total_cpu_usage =
+            process.cpu_usage + children.iter().map(|c| c.total_cpu_usage).sum::<f32>();
+        let total_memory_mb =
+            process.memory_mb + children.iter().map(|c| c.total_memory_mb).sum::<f64>();
+
+        ProcessNode {
+            process,
+            total_cpu_usage,
+            total_memory_mb,
+            children,
+        }
+    }
+}
+
+/// Build the process forest from a flat process list
+///
+/// Processes whose parent is missing from the list (or unknown) become
+/// roots. Roots and siblings are ordered by PID.
+pub fn build_tree(processes: &[ProcessInfo]) -> Vec<ProcessNode> {
+    let pids: HashSet<u32> = processes.iter().map(|p| p.pid).collect();
+
+    let mut children: HashMap<u32, Vec<&ProcessInfo>> = HashMap::new();
+    let mut roots = Vec::new();
+    for process in processes {
+        match process.parent_pid {
+            Some(parent) if parent != process.pid && pids.contains(&parent) => {
+                children.entry(parent).or_default().push(process)
+            }
+            _ => roots.push(process),
+        }
+    }
+
+    let mut visited = HashSet::new();
+    let mut forest = build_level(roots, &children, &mut visited);
+
+    // Parent links that form a loop (PID reuse between refreshes) are never
+    // reached from a root; show those processes at the top level
+    let orphans: Vec<&ProcessInfo> = processes
+        .iter()
+        .filter(|p| !visited.contains(&p.pid))
+        .collect();
+    if !orphans.is_empty() {
+        forest.extend(build_level(orphans, &children, &mut visited));
+        forest.sort_by_key(|node| node.process.pid);
+    }
+
+    forest
+}
+
+fn build_level(
+    mut level: Vec<&ProcessInfo>,
+    children: &HashMap<u32, Vec<&ProcessInfo>>,
+    visited: &mut HashSet<u32>,
+) -> Vec<ProcessNode> {
+    level.sort_by_key(|p| p.pid);
+
+    let mut nodes = Vec::with_capacity(level.len());
+    for process in level {
+        if !visited.insert(process.pid) {
+            continue;
+        }
+        let kids = children.get(&process.pid).cloned().unwrap_or_default();
+        let kids = build_level(kids, children, visited);
+        nodes.push(ProcessNode::new(process.clone(), kids));
+    }
+    nodes
+}
+
+/// Reorder siblings at every level of the tree
+pub fn sort_tree<F>(nodes: &mut [ProcessNode], compare: &F)
+where
+    F: Fn(&ProcessNode, &ProcessNode) -> Ordering,
+{
+    nodes.sort_by(compare);
+    for node in nodes {
+        sort_tree(&mut node.children, compare);
+    }
+}
+
+/// One line of a rendered tree
+#[derive(Debug)]
+pub struct TreeRow<'a> {
+    /// The node shown on this line
+    pub node: &'a ProcessNode,
+    /// Branch drawing to put before the name (e.g. "│  └─ ")
+    pub prefix: String,
+    /// Whether the children of this node are hidden
+    pub collapsed: bool,
+}
+
+/// Flatten the tree into display order (depth first)
+///
+/// # Arguments
+/// * `nodes` - Root nodes
+/// * `collapsed` - PIDs whose children are hidden
+pub fn flatten<'a>(nodes: &'a [ProcessNode], collapsed: &HashSet<u32>) -> Vec<TreeRow<'a>> {
+    let mut rows = Vec::new();
+    for node in nodes {
+        flatten_node(node, collapsed, "", None, &mut rows);
+    }
+    rows
+}
+
+fn flatten_node<'a>(
+    node: &'a ProcessNode,
+    collapsed: &HashSet<u32>,
+    indent: &str,
+    is_last: Option<bool>, // None for roots
+    rows: &mut Vec<TreeRow<'a>>,
+) {
+    let branch = match is_last {
+        None => "",
+        Some(true) => "└─ ",
+        Some(false) => "├─ ",
+    };
+    let is_collapsed = !node.children.is_empty() && collapsed.contains(&node.process.pid);
+
+    rows.push(TreeRow {
+        node,
+        prefix: format!("{}{}", indent, branch),
+        collapsed: is_collapsed,
+    });
+
+    if is_collapsed {
+        return;
+    }
+
+    let child_indent = match is_last {
+        None => String::new(),
+        Some(true) => format!("{}   ", indent),
+        Some(false) => format!("{}│  ", indent),
+    };
+    let count = node.children.len();
+    for (i, child) in node.children.iter().enumerate() {
+        flatten_node(child, collapsed, &child_indent, Some(i + 1 == count), rows);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::snapshot::sample_process;
+
+    fn process(pid: u32, parent_pid: Option<u32>, cpu: f32) -> ProcessInfo {
+        ProcessInfo {
+            parent_pid,
+            ..sample_process(pid, &format!("p{}", pid), cpu, 10.0)
+        }
+    }
+
+    fn sample() -> Vec<ProcessInfo> {
+        vec![
+            process(30, Some(10), 5.0),
+            process(1, None, 1.0),
+            process(10, Some(1), 2.0),
+            process(20, Some(1), 3.0),
+            process(40, Some(999), 4.0), // parent not in the list
+        ]
+    }
+
+    #[test]
+    fn test_build_tree_with_totals() {
+        let forest = build_tree(&sample());
+        let roots: Vec<u32> = forest.iter().map(|n| n.process.pid).collect();
+        assert_eq!(roots, vec![1, 40]);
+
+        let init = &forest[0];
+        assert_eq!(init.children.len(), 2);
+        assert_eq!(init.children[0].children[0].process.pid, 30);
+        assert_eq!(init.total_cpu_usage, 11.0);
+        assert_eq!(init.total_memory_mb, 40.0);
+    }
+
+    #[test]
+    fn test_parent_loop_is_kept() {
+        let processes = vec![process(5, Some(6), 0.0), process(6, Some(5), 0.0)];
+        let forest = build_tree(&processes);
+        let count: usize = flatten(&forest, &HashSet::new()).len();
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_flatten_prefixes_and_collapse() {
+        let forest = build_tree(&sample());
+        let rows = flatten(&forest, &HashSet::new());
+        let prefixes: Vec<(u32, &str)> = rows
+            .iter()
+            .map(|r| (r.node.process.pid, r.prefix.as_str()))
+            .collect();
+        assert_eq!(
+            prefixes,
+            vec![(1, ""), (10, "├─ "), (30, "│  └─ "), (20, "└─ "), (40, "")]
+        );
+
+        let collapsed = HashSet::from([10]);
+        let rows = flatten(&forest, &collapsed);
+        assert_eq!(rows.len(), 4);
+        assert!(rows[1].collapsed);
+    }
+
+    #[test]
+    fn test_sort_tree_siblings() {
+        let mut forest = build_tree(&sample());
+        sort_tree(&mut forest, &|a: &ProcessNode, b: &ProcessNode| {
+            b.process.cpu_usage.total_cmp(&a.process.cpu_usage)
+        });
+        assert_eq!(forest[0].process.pid, 40);
+        assert_eq!(forest[1].children[0].process.pid, 20);
+    }
+}
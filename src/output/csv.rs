@@ -0,0 +1,356 @@
+// CSV output module
+// Flattens system snapshots into rows with a stable column layout
+
+use super::Sink;
+use crate::monitor::snapshot::Snapshot;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+/// Column layout of a CSV export
+///
+/// The layout is fixed by the first snapshot written (number of cores and
+/// mounted disks) so every row of a file has the same columns. Disks that
+/// disappear later leave their cells empty; new disks are not added.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvLayout {
+    /// Number of per-core CPU columns
+    pub core_count: usize,
+    /// Mount points with a disk usage column, in column order
+    pub mount_points: Vec<String>,
+}
+
+impl CsvLayout {
+    /// Derive the layout from a snapshot
+    pub fn from_snapshot(snapshot: &Snapshot) -> Self {
+        CsvLayout {
+            core_count: snapshot.cpu.cores.len(),
+            mount_points: snapshot
+                .disks
+                .iter()
+                .map(|disk| disk.mount_point.clone())
+                .collect(),
+        }
+    }
+
+    /// Build the header row (without trailing newline)
+    pub fn header(&self) -> String {
+        let mut columns = vec!["timestamp_ms".to_string(), "cpu_global".to_string()];
+
+        for index in 0..self.core_count {
+            columns.push(format!("cpu_core_{}", index));
+        }
+
+        columns.push("mem_used_gb".to_string());
+        columns.push("mem_total_gb".to_string());
+        columns.push("mem_percent".to_string());
+
+        for mount in &self.mount_points {
+            columns.push(format!("disk[{}]_percent", mount));
+        }
+
+        columns.push("net_download_bps".to_string());
+        columns.push("net_upload_bps".to_string());
+        columns.push("net_total_rx_bytes".to_string());
+        columns.push("net_total_tx_bytes".to_string());
+
+        join_fields(&columns)
+    }
+
+    /// Build one data row (without trailing newline)
+    pub fn row(&self, snapshot: &Snapshot) -> String {
+        let mut fields = vec![
+            snapshot.timestamp_ms.to_string(),
+            format!("{:.2}", snapshot.cpu.global_usage),
+        ];
+
+        for index in 0..self.core_count {
+            let usage = snapshot
+                .cpu
+                .cores
+                .iter()
+                .find(|core| core.index == index)
+                .map(|core| format!("{:.2}", core.usage))
+                .unwrap_or_default();
+            fields.push(usage);
+        }
+
+        fields.push(format!("{:.3}", snapshot.memory.used_gb));
+        fields.push(format!("{:.3}", snapshot.memory.total_gb));
+        fields.push(format!("{:.2}", snapshot.memory.percentage));
+
+        for mount in &self.mount_points {
+            let percentage = snapshot
+                .disks
+                .iter()
+                .find(|disk| &disk.mount_point == mount)
+                .map(|disk| format!("{:.2}", disk.percentage))
+                .unwrap_or_default();
+            fields.push(percentage);
+        }
+
+        fields.push(format!("{:.0}", snapshot.network.selected_download_speed));
+        fields.push(format!("{:.0}", snapshot.network.selected_upload_speed));
+        fields.push(snapshot.network.selected_received.to_string());
+        fields.push(snapshot.network.selected_transmitted.to_string());
+
+        join_fields(&fields)
+    }
+}
+
+/// CSV writer that emits the header once and then one row per snapshot
+pub struct CsvWriter<W: Write> {
+    out: W,
+    layout: Option<CsvLayout>,
+    header_pending: bool,
+}
+
+impl<W: Write> CsvWriter<W> {
+    /// Create a writer that starts with a header row
+    pub fn new(out: W) -> Self {
+        CsvWriter {
+            out,
+            layout: None,
+            header_pending: true,
+        }
+    }
+
+    /// Create a writer that continues a file whose header is already present
+    pub fn continuing(out: W, layout: CsvLayout) -> Self {
+        CsvWriter {
+            out,
+            layout: Some(layout),
+            header_pending: false,
+        }
+    }
+
+    /// Write one snapshot as a row (preceded by the header on first use)
+    pub fn write_snapshot(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        let layout = self
+            .layout
+            .get_or_insert_with(|| CsvLayout::from_snapshot(snapshot));
+
+        if self.header_pending {
+            writeln!(self.out, "{}", layout.header())?;
+            self.header_pending = false;
+        }
+
+        writeln!(self.out, "{}", layout.row(snapshot))?;
+        self.out.flush()
+    }
+}
+
+impl<W: Write> Sink for CsvWriter<W> {
+    fn emit(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        self.write_snapshot(snapshot)
+    }
+}
+
+/// CSV sink writing to stdout or appending to a file
+///
+/// The destination is opened on the first snapshot, once the column layout
+/// is known, so an existing file's header can be checked before appending.
+pub struct CsvSink {
+    path: Option<PathBuf>,
+    writer: Option<CsvWriter<Box<dyn Write>>>,
+}
+
+impl CsvSink {
+    /// Create a CSV sink
+    ///
+    /// # Arguments
+    /// * `path` - File to append to, or `None` for stdout
+    pub fn new(path: Option<PathBuf>) -> Self {
+        CsvSink { path, writer: None }
+    }
+
+    /// Open the destination, continuing an existing file if its header matches
+    fn open(&self, first: &Snapshot) -> io::Result<CsvWriter<Box<dyn Write>>> {
+        let layout = CsvLayout::from_snapshot(first);
+        let has_header = match &self.path {
+            Some(path) => check_existing_header(path, &layout)?,
+            None => false,
+        };
+
+        let out = super::open_output(self.path.as_deref())?;
+        if has_header {
+            Ok(CsvWriter::continuing(out, layout))
+        } else {
+            Ok(CsvWriter::new(out))
+        }
+    }
+}
+
+impl Sink for CsvSink {
+    fn emit(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        if self.writer.is_none() {
+            self.writer = Some(self.open(snapshot)?);
+        }
+        self.writer
+            .as_mut()
+            .expect("writer was just opened")
+            .write_snapshot(snapshot)
+    }
+}
+
+/// Check whether an existing CSV file can be appended to with a layout
+///
+/// # Returns
+/// * `Ok(true)` - The file starts with exactly this layout's header
+/// * `Ok(false)` - The file does not exist or is empty (header must be written)
+/// * `Err(_)` - The file has a different header, appending would mix layouts
+pub fn check_existing_header(path: &Path, layout: &CsvLayout) -> io::Result<bool> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e),
+    };
+
+    let mut first_line = String::new();
+    BufReader::new(file).read_line(&mut first_line)?;
+    let existing = first_line.trim_end_matches(['\r', '\n']);
+
+    if existing.is_empty() {
+        return Ok(false);
+    }
+
+    let expected = layout.header();
+    if existing == expected {
+        Ok(true)
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "CSV header mismatch in {}\n  existing: {}\n  expected: {}",
+                path.display(),
+                existing,
+                expected
+            ),
+        ))
+    }
+}
+
+/// Join fields into a CSV line, quoting them when needed
+fn join_fields(fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|field| escape_field(field))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Quote a field if it contains a separator, quote or newline (RFC 4180)
+fn escape_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::disk::DiskInfo;
+    use crate::monitor::snapshot::sample_snapshot as base_snapshot;
+
+    fn disk(mount_point: &str, percentage: f64) -> DiskInfo {
+        DiskInfo {
+            name: "disk".to_string(),
+            mount_point: mount_point.to_string(),
+            total_gb: 100.0,
+            available_gb: 100.0 - percentage,
+            used_gb: percentage,
+            percentage,
+        }
+    }
+
+    fn sample_snapshot(disks: Vec<DiskInfo>) -> Snapshot {
+        Snapshot {
+            disks,
+            ..base_snapshot()
+        }
+    }
+
+    #[test]
+    fn test_header_layout() {
+        let snapshot = sample_snapshot(vec![disk("/", 50.0), disk("/home", 20.0)]);
+        let layout = CsvLayout::from_snapshot(&snapshot);
+
+        assert_eq!(
+            layout.header(),
+            "timestamp_ms,cpu_global,cpu_core_0,cpu_core_1,mem_used_gb,mem_total_gb,\
+             mem_percent,disk[/]_percent,disk[/home]_percent,net_download_bps,\
+             net_upload_bps,net_total_rx_bytes,net_total_tx_bytes"
+        );
+    }
+
+    #[test]
+    fn test_row_matches_header_width() {
+        let snapshot = sample_snapshot(vec![disk("/", 50.0)]);
+        let layout = CsvLayout::from_snapshot(&snapshot);
+
+        let row = layout.row(&snapshot);
+        assert_eq!(
+            row,
+            "1700000000000,25.00,20.00,30.00,8.000,16.000,50.00,50.00,1024,512,10000,5000"
+        );
+        assert_eq!(row.split(',').count(), layout.header().split(',').count());
+    }
+
+    #[test]
+    fn test_missing_disk_leaves_empty_cell() {
+        let first = sample_snapshot(vec![disk("/", 50.0), disk("/mnt/usb", 10.0)]);
+        let layout = CsvLayout::from_snapshot(&first);
+
+        let later = sample_snapshot(vec![disk("/", 55.0)]);
+        let row = layout.row(&later);
+        assert!(row.contains(",55.00,,1024,"));
+    }
+
+    #[test]
+    fn test_writer_emits_header_once() {
+        let snapshot = sample_snapshot(vec![]);
+        let mut buf = Vec::new();
+        {
+            let mut writer = CsvWriter::new(&mut buf);
+            writer.write_snapshot(&snapshot).unwrap();
+            writer.write_snapshot(&snapshot).unwrap();
+        }
+
+        let text = String::from_utf8(buf).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("timestamp_ms,"));
+        assert!(lines[1].starts_with("1700000000000,"));
+    }
+
+    #[test]
+    fn test_escape_field_with_comma() {
+        assert_eq!(escape_field("disk[C:,x]"), "\"disk[C:,x]\"");
+        assert_eq!(escape_field("a\"b"), "\"a\"\"b\"");
+        assert_eq!(escape_field("plain"), "plain");
+    }
+
+    #[test]
+    fn test_check_existing_header() {
+        let snapshot = sample_snapshot(vec![disk("/", 50.0)]);
+        let layout = CsvLayout::from_snapshot(&snapshot);
+        let path = std::env::temp_dir().join(format!("sysmon_csv_{}.csv", std::process::id()));
+
+        // Missing file: header must be written
+        let _ = std::fs::remove_file(&path);
+        assert!(!check_existing_header(&path, &layout).unwrap());
+
+        // Matching header: append is allowed
+        std::fs::write(&path, format!("{}\n", layout.header())).unwrap();
+        assert!(check_existing_header(&path, &layout).unwrap());
+
+        // Different layout: refuse to append
+        std::fs::write(&path, "timestamp_ms,cpu_global\n").unwrap();
+        let err = check_existing_header(&path, &layout).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
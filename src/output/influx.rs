@@ -0,0 +1,307 @@
+// InfluxDB output module
+// Renders system snapshots as InfluxDB line protocol and ships them over UDP/TCP
+
+use super::{connect_udp, resolve, split_datagrams, Sink, MAX_DATAGRAM};
+use crate::monitor::snapshot::Snapshot;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::net::{TcpStream, UdpSocket};
+use std::str::FromStr;
+
+const BYTES_PER_GB: f64 = 1_073_741_824.0; // 1024^3
+const BYTES_PER_MB: f64 = 1_048_576.0; // 1024^2
+
+/// Render a snapshot as line protocol (one line per point, `\n` terminated)
+///
+/// Measurements: `cpu`, `mem`, `disk`, `net` (tagged `interface=all` for the
+/// total and with each interface name) and `proc` (tagged with the name and
+/// the rank in the top lists).
+/// Timestamps are in nanoseconds, the line protocol default precision.
+pub fn render(snapshot: &Snapshot) -> String {
+    let ts = snapshot.timestamp_ms as u128 * 1_000_000;
+    let mut lines = String::new();
+
+    // CPU: one point for the global usage and one per core
+    let _ = writeln!(
+        lines,
+        "cpu,core=total usage={} {}",
+        snapshot.cpu.global_usage, ts
+    );
+    for core in &snapshot.cpu.cores {
+        let _ = writeln!(lines, "cpu,core={} usage={} {}", core.index, core.usage, ts);
+    }
+
+    // Memory
+    let _ = writeln!(
+        lines,
+        "mem total_bytes={}i,used_bytes={}i,used_percent={} {}",
+        (snapshot.memory.total_gb * BYTES_PER_GB).round() as u64,
+        (snapshot.memory.used_gb * BYTES_PER_GB).round() as u64,
+        snapshot.memory.percentage,
+        ts
+    );
+
+    // Disks
+    for disk in &snapshot.disks {
+        let _ = writeln!(
+            lines,
+            "disk,mount_point={},device={} total_bytes={}i,used_bytes={}i,available_bytes={}i,used_percent={} {}",
+            escape_tag(&disk.mount_point),
+            escape_tag(&disk.name),
+            (disk.total_gb * BYTES_PER_GB).round() as u64,
+            (disk.used_gb * BYTES_PER_GB).round() as u64,
+            (disk.available_gb * BYTES_PER_GB).round() as u64,
+            disk.percentage,
+            ts
+        );
+    }
+
+    // Network: one point for the selected interfaces and one per interface,
+    // tagged like the CPU so that summing over interfaces doesn't count twice
+    let _ = writeln!(
+        lines,
+        "net,interface=all download_bps={},upload_bps={},bytes_recv={}i,bytes_sent={}i {}",
+        snapshot.network.selected_download_speed,
+        snapshot.network.selected_upload_speed,
+        snapshot.network.selected_received,
+        snapshot.network.selected_transmitted,
+        ts
+    );
+    for interface in &snapshot.network.interfaces {
+        let _ = writeln!(
+            lines,
+            "net,interface={} download_bps={},upload_bps={},bytes_recv={}i,bytes_sent={}i {}",
+            escape_tag(&interface.name),
+            interface.download_speed,
+            interface.upload_speed,
+            interface.total_received,
+            interface.total_transmitted,
+            ts
+        );
+    }
+
+    // Processes: top lists merged, each process reported once. The PID is a
+    // field, a tag would create a series for every short-lived process. The
+    // rank (CPU list first, then the memory-only entries) keeps processes
+    // of the same name apart, they would be a single point otherwise
+    let mut seen = HashSet::new();
+    let processes = snapshot
+        .top_processes_cpu
+        .iter()
+        .chain(&snapshot.top_processes_memory)
+        .filter(|proc| seen.insert(proc.pid));
+    for (rank, proc) in processes.enumerate() {
+        let _ = writeln!(
+            lines,
+            "proc,name={},rank={} pid={}i,cpu_usage={},memory_bytes={}i {}",
+            escape_tag(&proc.name),
+            rank + 1,
+            proc.pid,
+            proc.cpu_usage,
+            (proc.memory_mb * BYTES_PER_MB).round() as u64,
+            ts
+        );
+    }
+
+    lines
+}
+
+/// Escape a tag value (commas, equals signs and spaces)
+fn escape_tag(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, ',' | '=' | ' ' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    if escaped.is_empty() {
+        // Empty tag values are not allowed by the line protocol
+        escaped.push_str("unknown");
+    }
+    escaped
+}
+
+/// Network destination for line protocol
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InfluxTarget {
+    /// UDP listener at `host:port`
+    Udp(String),
+    /// TCP listener at `host:port`
+    Tcp(String),
+}
+
+impl FromStr for InfluxTarget {
+    type Err = String;
+
+    /// Parse `udp://host:port` or `tcp://host:port`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(addr) = s.strip_prefix("udp://") {
+            Ok(InfluxTarget::Udp(addr.to_string()))
+        } else if let Some(addr) = s.strip_prefix("tcp://") {
+            Ok(InfluxTarget::Tcp(addr.to_string()))
+        } else {
+            Err(format!(
+                "invalid target '{}', expected udp://host:port or tcp://host:port",
+                s
+            ))
+        }
+    }
+}
+
+/// Sends line protocol batches to a UDP or TCP listener
+pub struct InfluxSender {
+    target: InfluxTarget,
+    udp: Option<UdpSocket>,
+    tcp: Option<TcpStream>,
+}
+
+impl InfluxSender {
+    /// Create a sender (connections are opened lazily)
+    pub fn new(target: InfluxTarget) -> Self {
+        InfluxSender {
+            target,
+            udp: None,
+            tcp: None,
+        }
+    }
+
+    /// Send a batch of lines
+    ///
+    /// UDP batches are split on line boundaries into datagrams of at most
+    /// `MAX_DATAGRAM` bytes. A broken TCP connection is dropped and reopened on the
+    /// next call.
+    pub fn send(&mut self, lines: &str) -> io::Result<()> {
+        match &self.target {
+            InfluxTarget::Udp(addr) => {
+                if self.udp.is_none() {
+                    self.udp = Some(connect_udp(addr)?);
+                }
+                let socket = self.udp.as_ref().expect("socket was just created");
+                for datagram in split_datagrams(lines, MAX_DATAGRAM) {
+                    socket.send(datagram.as_bytes())?;
+                }
+                Ok(())
+            }
+            InfluxTarget::Tcp(addr) => {
+                if self.tcp.is_none() {
+                    self.tcp = Some(TcpStream::connect(resolve(addr)?)?);
+                }
+                let stream = self.tcp.as_mut().expect("stream was just created");
+                let result = stream
+                    .write_all(lines.as_bytes())
+                    .and_then(|_| stream.flush());
+                if result.is_err() {
+                    self.tcp = None;
+                }
+                result
+            }
+        }
+    }
+}
+
+/// Line protocol sink: written to a stream or sent to a collector
+pub enum InfluxSink {
+    /// Write lines to a stream (stdout or file)
+    Writer(Box<dyn Write>),
+    /// Send lines to a collector
+    Network(InfluxSender),
+}
+
+impl Sink for InfluxSink {
+    fn emit(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        let lines = render(snapshot);
+
+        match self {
+            InfluxSink::Writer(out) => {
+                out.write_all(lines.as_bytes())?;
+                out.flush()
+            }
+            InfluxSink::Network(sender) => {
+                // An unreachable collector must not stop the watch loop
+                if let Err(e) = sender.send(&lines) {
+                    eprintln!("Error sending line protocol: {}", e);
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::snapshot::{sample_process, sample_snapshot};
+    use std::time::Duration;
+
+    #[test]
+    fn test_render_measurements() {
+        let lines = render(&sample_snapshot());
+        let ts = "1700000000000000000";
+
+        assert!(lines.contains(&format!("cpu,core=total usage=25 {}\n", ts)));
+        assert!(lines.contains(&format!("cpu,core=1 usage=30 {}\n", ts)));
+        assert!(lines.contains("mem total_bytes=17179869184i,"));
+        assert!(lines.contains("disk,mount_point=/,device=sda1 "));
+        assert!(lines.contains(
+            "net,interface=all download_bps=1024,upload_bps=512,bytes_recv=10000i,bytes_sent=5000i"
+        ));
+        assert!(lines.contains("net,interface=eth0 download_bps=1024,"));
+        assert!(
+            lines.contains("proc,name=cargo,rank=1 pid=42i,cpu_usage=12.5,memory_bytes=268435456i")
+        );
+    }
+
+    #[test]
+    fn test_same_name_processes() {
+        let mut snapshot = sample_snapshot();
+        let worker = |pid| sample_process(pid, "php-fpm", 1.0, 30.0);
+        snapshot.top_processes_cpu = vec![worker(100), worker(101)];
+        // Also in the memory list, reported once
+        snapshot.top_processes_memory = vec![worker(101), worker(102)];
+
+        let lines = render(&snapshot);
+        let procs: Vec<_> = lines.lines().filter(|l| l.starts_with("proc,")).collect();
+        assert_eq!(procs.len(), 3);
+        assert!(procs[0].starts_with("proc,name=php-fpm,rank=1 pid=100i,"));
+        assert!(procs[1].starts_with("proc,name=php-fpm,rank=2 pid=101i,"));
+        assert!(procs[2].starts_with("proc,name=php-fpm,rank=3 pid=102i,"));
+    }
+
+    #[test]
+    fn test_escape_tag() {
+        assert_eq!(escape_tag("My Disk"), "My\\ Disk");
+        assert_eq!(escape_tag("a,b=c"), "a\\,b\\=c");
+        assert_eq!(escape_tag(""), "unknown");
+    }
+
+    #[test]
+    fn test_parse_target() {
+        assert_eq!(
+            "udp://127.0.0.1:8089".parse::<InfluxTarget>(),
+            Ok(InfluxTarget::Udp("127.0.0.1:8089".to_string()))
+        );
+        assert_eq!(
+            "tcp://localhost:8094".parse::<InfluxTarget>(),
+            Ok(InfluxTarget::Tcp("localhost:8094".to_string()))
+        );
+        assert!("http://localhost".parse::<InfluxTarget>().is_err());
+    }
+
+    #[test]
+    fn test_send_udp() {
+        let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+        listener
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let mut sender = InfluxSender::new(InfluxTarget::Udp(addr.to_string()));
+        sender.send("cpu,core=total usage=1 1\n").unwrap();
+
+        let mut buf = [0u8; 2048];
+        let len = listener.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"cpu,core=total usage=1 1\n");
+    }
+}
@@ -0,0 +1,138 @@
+// JSON output module
+// Serializes system snapshots as JSON documents or NDJSON streams
+
+use super::Sink;
+use crate::monitor::snapshot::Snapshot;
+use serde::Serialize;
+use std::io::{self, Write};
+
+/// One line of an NDJSON stream: the snapshot plus its sequence number
+#[derive(Serialize)]
+struct StreamRecord<'a> {
+    seq: u64,
+    #[serde(flatten)]
+    snapshot: &'a Snapshot,
+}
+
+/// Write a snapshot as a pretty-printed JSON document
+///
+/// # Arguments
+/// * `out` - Destination writer (usually stdout)
+/// * `snapshot` - Snapshot to serialize
+pub fn write_snapshot<W: Write>(out: &mut W, snapshot: &Snapshot) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *out, snapshot)?;
+    writeln!(out)?;
+    out.flush()
+}
+
+/// Write a snapshot as a single compact JSON line (NDJSON)
+///
+/// # Arguments
+/// * `out` - Destination writer (usually stdout)
+/// * `seq` - Monotonic sequence number of this record, starting at 0
+/// * `snapshot` - Snapshot to serialize
+pub fn write_stream_record<W: Write>(out: &mut W, seq: u64, snapshot: &Snapshot) -> io::Result<()> {
+    serde_json::to_writer(&mut *out, &StreamRecord { seq, snapshot })?;
+    writeln!(out)?;
+    out.flush()
+}
+
+/// JSON sink: a pretty document per snapshot, or an NDJSON stream
+pub struct JsonSink<W: Write> {
+    out: W,
+    stream: bool,
+    seq: u64,
+}
+
+impl<W: Write> JsonSink<W> {
+    /// Create a JSON sink
+    ///
+    /// # Arguments
+    /// * `out` - Destination writer
+    /// * `stream` - If true, writes NDJSON records with sequence numbers
+    pub fn new(out: W, stream: bool) -> Self {
+        JsonSink {
+            out,
+            stream,
+            seq: 0,
+        }
+    }
+}
+
+impl<W: Write> Sink for JsonSink<W> {
+    fn emit(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        if self.stream {
+            write_stream_record(&mut self.out, self.seq, snapshot)?;
+            self.seq += 1;
+            Ok(())
+        } else {
+            write_snapshot(&mut self.out, snapshot)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::snapshot::{sample_snapshot, SCHEMA_VERSION};
+
+    #[test]
+    fn test_json_contains_schema_version() {
+        let mut buf = Vec::new();
+        write_snapshot(&mut buf, &sample_snapshot()).unwrap();
+
+        let value: serde_json::Value = serde_json::from_slice(&buf).unwrap();
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        assert_eq!(value["timestamp_ms"], 1_700_000_000_000u64);
+    }
+
+    #[test]
+    fn test_json_includes_all_sections() {
+        let mut buf = Vec::new();
+        write_snapshot(&mut buf, &sample_snapshot()).unwrap();
+
+        let value: serde_json::Value = serde_json::from_slice(&buf).unwrap();
+        assert_eq!(value["cpu"]["cores"].as_array().unwrap().len(), 2);
+        assert_eq!(value["memory"]["percentage"], 50.0);
+        assert_eq!(value["disks"][0]["mount_point"], "/");
+        assert_eq!(value["network"]["total_received"], 10_000);
+        assert_eq!(value["top_processes_cpu"][0]["name"], "cargo");
+        assert_eq!(value["uptime_secs"], 3600);
+    }
+
+    #[test]
+    fn test_stream_record_is_single_line() {
+        let mut buf = Vec::new();
+        write_stream_record(&mut buf, 7, &sample_snapshot()).unwrap();
+
+        let text = String::from_utf8(buf).unwrap();
+        assert_eq!(text.lines().count(), 1);
+        assert!(text.ends_with('\n'));
+
+        let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(value["seq"], 7);
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        assert_eq!(value["cpu"]["global_usage"], 25.0);
+    }
+
+    #[test]
+    fn test_sink_numbers_stream_records() {
+        let mut buf = Vec::new();
+        {
+            let mut sink = JsonSink::new(&mut buf, true);
+            sink.emit(&sample_snapshot()).unwrap();
+            sink.emit(&sample_snapshot()).unwrap();
+        }
+
+        let text = String::from_utf8(buf).unwrap();
+        let seqs: Vec<u64> = text
+            .lines()
+            .map(|line| {
+                serde_json::from_str::<serde_json::Value>(line).unwrap()["seq"]
+                    .as_u64()
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(seqs, vec![0, 1]);
+    }
+}
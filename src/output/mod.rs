@@ -0,0 +1,113 @@
+// Output module - Snapshot sinks
+// This module contains the `Sink` trait and its implementations: the colored
+// terminal view, serializers for parseable output and network senders
+
+/// CSV rows with a stable column layout
+pub mod csv;
+/// InfluxDB line protocol
+pub mod influx;
+/// JSON documents and NDJSON streams
+pub mod json;
+/// Prometheus text exposition format
+pub mod prometheus;
+/// Session recordings for `sysmon replay`
+pub mod recording;
+/// StatsD/DogStatsD datagrams
+pub mod statsd;
+/// Colored terminal view
+pub mod terminal;
+
+use crate::monitor::snapshot::Snapshot;
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::path::Path;
+
+/// Largest UDP datagram we send (stays below a typical Ethernet MTU)
+pub(crate) const MAX_DATAGRAM: usize = 1400;
+
+/// Destination for complete system snapshots
+///
+/// Every output (terminal view, JSON, CSV, ...) implements this trait so the
+/// monitoring loops only collect snapshots and hand them over.
+pub trait Sink {
+    /// Emit one snapshot
+    fn emit(&mut self, snapshot: &Snapshot) -> io::Result<()>;
+
+    /// Called once after the last snapshot (e.g. when Ctrl+C stops watch mode)
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Open the destination for machine-readable output
+///
+/// # Arguments
+/// * `path` - File to append to, or `None` for stdout
+pub fn open_output(path: Option<&Path>) -> io::Result<Box<dyn Write>> {
+    match path {
+        Some(path) => {
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            Ok(Box::new(io::BufWriter::new(file)))
+        }
+        None => Ok(Box::new(io::stdout())),
+    }
+}
+
+/// Resolve `host:port` to the first matching socket address
+pub(crate) fn resolve(addr: &str) -> io::Result<SocketAddr> {
+    addr.to_socket_addrs()?.next().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("address '{}' did not resolve", addr),
+        )
+    })
+}
+
+/// Open a UDP socket connected to `host:port`
+pub(crate) fn connect_udp(addr: &str) -> io::Result<UdpSocket> {
+    let resolved = resolve(addr)?;
+    let local = if resolved.is_ipv6() {
+        "[::]:0"
+    } else {
+        "0.0.0.0:0"
+    };
+
+    let socket = UdpSocket::bind(local)?;
+    socket.connect(resolved)?;
+    Ok(socket)
+}
+
+/// Group lines into chunks no larger than `max_len` bytes
+///
+/// A single line longer than `max_len` is sent on its own.
+pub(crate) fn split_datagrams(lines: &str, max_len: usize) -> Vec<String> {
+    let mut datagrams = Vec::new();
+    let mut current = String::new();
+
+    for line in lines.lines() {
+        if !current.is_empty() && current.len() + line.len() + 1 > max_len {
+            datagrams.push(std::mem::take(&mut current));
+        }
+        current.push_str(line);
+        current.push('\n');
+    }
+    if !current.is_empty() {
+        datagrams.push(current);
+    }
+
+    datagrams
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_datagrams() {
+        let lines = "aaaa\nbbbb\ncccc\n";
+        assert_eq!(split_datagrams(lines, 10), vec!["aaaa\nbbbb\n", "cccc\n"]);
+        assert_eq!(split_datagrams(lines, 100), vec![lines]);
+    }
+}
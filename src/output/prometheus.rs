@@ -0,0 +1,317 @@
+// Prometheus output module
+// Renders system snapshots in the Prometheus text exposition format (v0.0.4)
+
+use super::Sink;
+use crate::monitor::snapshot::Snapshot;
+use std::fmt::Write;
+use std::io;
+
+/// Content type expected by Prometheus scrapers
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+const BYTES_PER_GB: f64 = 1_073_741_824.0; // 1024^3
+
+/// Render a snapshot as a Prometheus metrics page
+pub fn render(snapshot: &Snapshot) -> String {
+    let mut page = MetricsPage::default();
+
+    // CPU
+    page.family(
+        "sysmon_cpu_global_usage_percent",
+        "gauge",
+        "Global CPU usage in percent",
+    );
+    page.sample(
+        "sysmon_cpu_global_usage_percent",
+        &[],
+        snapshot.cpu.global_usage as f64,
+    );
+
+    page.family(
+        "sysmon_cpu_usage_percent",
+        "gauge",
+        "CPU usage per core in percent",
+    );
+    for core in &snapshot.cpu.cores {
+        page.sample(
+            "sysmon_cpu_usage_percent",
+            &[("core", &core.index.to_string())],
+            core.usage as f64,
+        );
+    }
+
+    // Memory
+    page.family(
+        "sysmon_memory_total_bytes",
+        "gauge",
+        "Total physical memory in bytes",
+    );
+    page.sample(
+        "sysmon_memory_total_bytes",
+        &[],
+        (snapshot.memory.total_gb * BYTES_PER_GB).round(),
+    );
+
+    page.family(
+        "sysmon_memory_used_bytes",
+        "gauge",
+        "Used physical memory in bytes",
+    );
+    page.sample(
+        "sysmon_memory_used_bytes",
+        &[],
+        (snapshot.memory.used_gb * BYTES_PER_GB).round(),
+    );
+
+    page.family(
+        "sysmon_memory_usage_percent",
+        "gauge",
+        "Used physical memory in percent",
+    );
+    page.sample(
+        "sysmon_memory_usage_percent",
+        &[],
+        snapshot.memory.percentage,
+    );
+
+    // Disks
+    page.family(
+        "sysmon_disk_total_bytes",
+        "gauge",
+        "Total disk space in bytes",
+    );
+    for disk in &snapshot.disks {
+        page.sample(
+            "sysmon_disk_total_bytes",
+            &[("mount_point", &disk.mount_point), ("device", &disk.name)],
+            (disk.total_gb * BYTES_PER_GB).round(),
+        );
+    }
+
+    page.family(
+        "sysmon_disk_available_bytes",
+        "gauge",
+        "Available disk space in bytes",
+    );
+    for disk in &snapshot.disks {
+        page.sample(
+            "sysmon_disk_available_bytes",
+            &[("mount_point", &disk.mount_point), ("device", &disk.name)],
+            (disk.available_gb * BYTES_PER_GB).round(),
+        );
+    }
+
+    page.family(
+        "sysmon_disk_usage_percent",
+        "gauge",
+        "Used disk space in percent",
+    );
+    for disk in &snapshot.disks {
+        page.sample(
+            "sysmon_disk_usage_percent",
+            &[("mount_point", &disk.mount_point), ("device", &disk.name)],
+            disk.percentage,
+        );
+    }
+
+    // Network
+    page.family(
+        "sysmon_network_received_bytes_total",
+        "counter",
+        "Total bytes received on the selected interfaces",
+    );
+    page.sample(
+        "sysmon_network_received_bytes_total",
+        &[],
+        snapshot.network.selected_received as f64,
+    );
+
+    page.family(
+        "sysmon_network_transmitted_bytes_total",
+        "counter",
+        "Total bytes transmitted on the selected interfaces",
+    );
+    page.sample(
+        "sysmon_network_transmitted_bytes_total",
+        &[],
+        snapshot.network.selected_transmitted as f64,
+    );
+
+    page.family(
+        "sysmon_network_download_bytes_per_second",
+        "gauge",
+        "Download speed since the previous refresh",
+    );
+    page.sample(
+        "sysmon_network_download_bytes_per_second",
+        &[],
+        snapshot.network.selected_download_speed,
+    );
+
+    page.family(
+        "sysmon_network_upload_bytes_per_second",
+        "gauge",
+        "Upload speed since the previous refresh",
+    );
+    page.sample(
+        "sysmon_network_upload_bytes_per_second",
+        &[],
+        snapshot.network.selected_upload_speed,
+    );
+
+    // Network, per interface
+    page.family(
+        "sysmon_network_interface_received_bytes_total",
+        "counter",
+        "Total bytes received per interface",
+    );
+    for interface in &snapshot.network.interfaces {
+        page.sample(
+            "sysmon_network_interface_received_bytes_total",
+            &[("interface", &interface.name)],
+            interface.total_received as f64,
+        );
+    }
+
+    page.family(
+        "sysmon_network_interface_transmitted_bytes_total",
+        "counter",
+        "Total bytes transmitted per interface",
+    );
+    for interface in &snapshot.network.interfaces {
+        page.sample(
+            "sysmon_network_interface_transmitted_bytes_total",
+            &[("interface", &interface.name)],
+            interface.total_transmitted as f64,
+        );
+    }
+
+    page.family(
+        "sysmon_network_interface_download_bytes_per_second",
+        "gauge",
+        "Download speed per interface since the previous refresh",
+    );
+    for interface in &snapshot.network.interfaces {
+        page.sample(
+            "sysmon_network_interface_download_bytes_per_second",
+            &[("interface", &interface.name)],
+            interface.download_speed,
+        );
+    }
+
+    page.family(
+        "sysmon_network_interface_upload_bytes_per_second",
+        "gauge",
+        "Upload speed per interface since the previous refresh",
+    );
+    for interface in &snapshot.network.interfaces {
+        page.sample(
+            "sysmon_network_interface_upload_bytes_per_second",
+            &[("interface", &interface.name)],
+            interface.upload_speed,
+        );
+    }
+
+    // Uptime
+    page.family("sysmon_uptime_seconds", "gauge", "System uptime in seconds");
+    page.sample("sysmon_uptime_seconds", &[], snapshot.uptime_secs as f64);
+
+    page.body
+}
+
+/// Prometheus sink: keeps the latest rendered metrics page in memory
+///
+/// Prometheus pulls metrics, so this sink does not write anywhere; the
+/// exporter serves `page()` to scrapers.
+#[derive(Default)]
+pub struct PrometheusSink {
+    page: String,
+}
+
+impl PrometheusSink {
+    /// Latest rendered page (empty before the first snapshot)
+    pub fn page(&self) -> &str {
+        &self.page
+    }
+}
+
+impl Sink for PrometheusSink {
+    fn emit(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        self.page = render(snapshot);
+        Ok(())
+    }
+}
+
+/// Text buffer for a metrics page
+#[derive(Default)]
+struct MetricsPage {
+    body: String,
+}
+
+impl MetricsPage {
+    /// Write the HELP and TYPE lines of a metric family
+    fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.body, "# HELP {} {}", name, help);
+        let _ = writeln!(self.body, "# TYPE {} {}", name, kind);
+    }
+
+    /// Write one sample line
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        self.body.push_str(name);
+
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(key, value)| format!("{}=\"{}\"", key, escape_label_value(value)))
+                .collect();
+            let _ = write!(self.body, "{{{}}}", labels.join(","));
+        }
+
+        let _ = writeln!(self.body, " {}", value);
+    }
+}
+
+/// Escape a label value (backslash, double quote and newline)
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::snapshot::sample_snapshot;
+
+    #[test]
+    fn test_render_types() {
+        let page = render(&sample_snapshot());
+
+        assert!(page.contains("# TYPE sysmon_cpu_usage_percent gauge\n"));
+        assert!(page.contains("# TYPE sysmon_network_received_bytes_total counter\n"));
+        assert!(page.contains("# TYPE sysmon_network_transmitted_bytes_total counter\n"));
+        assert!(page.contains("# TYPE sysmon_disk_usage_percent gauge\n"));
+    }
+
+    #[test]
+    fn test_render_samples() {
+        let page = render(&sample_snapshot());
+
+        assert!(page.contains("sysmon_cpu_usage_percent{core=\"0\"} 20\n"));
+        assert!(page.contains("sysmon_cpu_usage_percent{core=\"1\"} 30\n"));
+        assert!(page.contains("sysmon_disk_usage_percent{mount_point=\"/\",device=\"sda1\"} 60\n"));
+        assert!(page.contains("sysmon_network_received_bytes_total 10000\n"));
+        assert!(page.contains(
+            "sysmon_network_interface_download_bytes_per_second{interface=\"eth0\"} 1024\n"
+        ));
+        assert!(page.contains("sysmon_memory_total_bytes 17179869184\n"));
+    }
+
+    #[test]
+    fn test_escape_label_value() {
+        assert_eq!(escape_label_value(r"C:\"), r"C:\\");
+        assert_eq!(escape_label_value("a\"b"), "a\\\"b");
+        assert_eq!(escape_label_value("a\nb"), "a\\nb");
+    }
+}
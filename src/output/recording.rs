@@ -0,0 +1,178 @@
+// Recording output module
+// Session files: a short header, then one length-prefixed compact JSON frame
+// per snapshot (`sysmon record` writes them, `sysmon replay` reads them)
+
+use super::Sink;
+use crate::monitor::snapshot::Snapshot;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// First bytes of every session file
+pub const MAGIC: &[u8; 8] = b"SYSMONRC";
+
+/// Version of the frame layout, stored after the magic bytes
+pub const FORMAT_VERSION: u16 = 1;
+
+/// Largest frame accepted when reading (guards against corrupt lengths)
+const MAX_FRAME_LEN: usize = 64 * 1024 * 1024;
+
+/// Sink writing every snapshot as one frame of a session file
+///
+/// Each frame is flushed right away, so a recording stopped abruptly stays
+/// readable up to its last complete frame.
+pub struct RecordingSink<W: Write> {
+    out: W,
+    frames: u64,
+}
+
+impl RecordingSink<BufWriter<File>> {
+    /// Create (or truncate) a session file and write its header
+    pub fn create(path: &Path) -> io::Result<Self> {
+        RecordingSink::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> RecordingSink<W> {
+    /// Write the header and start recording
+    ///
+    /// # Arguments
+    /// * `out` - Destination writer (usually a new file)
+    pub fn new(mut out: W) -> io::Result<Self> {
+        out.write_all(MAGIC)?;
+        out.write_all(&FORMAT_VERSION.to_le_bytes())?;
+        out.flush()?;
+        Ok(RecordingSink { out, frames: 0 })
+    }
+
+    /// Number of snapshots written so far
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+}
+
+impl<W: Write> Sink for RecordingSink<W> {
+    fn emit(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        let frame = serde_json::to_vec(snapshot)?;
+        let len = u32::try_from(frame.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "snapshot too large"))?;
+        self.out.write_all(&len.to_le_bytes())?;
+        self.out.write_all(&frame)?;
+        self.out.flush()?;
+        self.frames += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// Read every snapshot of a session file, in recording order
+pub fn load_recording(path: &Path) -> io::Result<Vec<Snapshot>> {
+    read_recording(BufReader::new(File::open(path)?))
+}
+
+/// Read every snapshot of a session from a reader
+///
+/// A frame cut short at the end (e.g. the recorder was killed while writing)
+/// is ignored; any other damage is an `InvalidData` error.
+pub fn read_recording<R: Read>(mut input: R) -> io::Result<Vec<Snapshot>> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+    let mut header = [0u8; 10];
+    input
+        .read_exact(&mut header)
+        .map_err(|_| invalid("not a sysmon recording (file too short)".to_string()))?;
+    if &header[..8] != MAGIC {
+        return Err(invalid("not a sysmon recording".to_string()));
+    }
+    let version = u16::from_le_bytes([header[8], header[9]]);
+    if version != FORMAT_VERSION {
+        return Err(invalid(format!(
+            "unsupported recording version {} (expected {})",
+            version, FORMAT_VERSION
+        )));
+    }
+
+    let mut snapshots = Vec::new();
+    let mut len = [0u8; 4];
+    loop {
+        if read_full(&mut input, &mut len)? < len.len() {
+            break;
+        }
+        let len = u32::from_le_bytes(len) as usize;
+        if len > MAX_FRAME_LEN {
+            return Err(invalid(format!(
+                "frame {} is too large ({} bytes)",
+                snapshots.len() + 1,
+                len
+            )));
+        }
+
+        let mut frame = vec![0u8; len];
+        if read_full(&mut input, &mut frame)? < len {
+            break;
+        }
+        let snapshot = serde_json::from_slice(&frame)
+            .map_err(|e| invalid(format!("frame {}: {}", snapshots.len() + 1, e)))?;
+        snapshots.push(snapshot);
+    }
+
+    Ok(snapshots)
+}
+
+/// Fill `buf` as far as possible, returning the number of bytes read
+fn read_full<R: Read>(input: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match input.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::snapshot::sample_snapshot;
+
+    #[test]
+    fn test_round_trip() {
+        let mut sink = RecordingSink::new(Vec::new()).unwrap();
+        let first = sample_snapshot();
+        let mut second = sample_snapshot();
+        second.timestamp_ms += 1000;
+        second.cpu.global_usage = 75.0;
+        sink.emit(&first).unwrap();
+        sink.emit(&second).unwrap();
+        assert_eq!(sink.frames(), 2);
+
+        let bytes = sink.out;
+        assert!(bytes.starts_with(MAGIC));
+        let snapshots = read_recording(&bytes[..]).unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[1].timestamp_ms, first.timestamp_ms + 1000);
+        assert_eq!(snapshots[1].cpu.global_usage, 75.0);
+        assert_eq!(snapshots[0].disks[0].mount_point, "/");
+
+        // A frame cut short at the end is dropped
+        let snapshots = read_recording(&bytes[..bytes.len() - 5]).unwrap();
+        assert_eq!(snapshots.len(), 1);
+    }
+
+    #[test]
+    fn test_rejects_other_files() {
+        let err = read_recording(&b"{\"schema_version\":1}"[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&9u16.to_le_bytes());
+        let err = read_recording(&bytes[..]).unwrap_err();
+        assert!(err.to_string().contains("version 9"));
+    }
+}
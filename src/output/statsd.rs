@@ -0,0 +1,293 @@
+// StatsD output module
+// Encodes system snapshots as StatsD datagrams and pushes them over UDP
+
+use super::{connect_udp, split_datagrams, Sink, MAX_DATAGRAM};
+use crate::monitor::snapshot::Snapshot;
+use std::io;
+use std::net::UdpSocket;
+
+const BYTES_PER_GB: f64 = 1_073_741_824.0; // 1024^3
+
+/// StatsD naming options
+#[derive(Debug, Clone)]
+pub struct StatsdConfig {
+    /// Prefix prepended to every metric name (e.g. "sysmon")
+    pub prefix: String,
+    /// Use DogStatsD tags for cores and mount points instead of name segments
+    pub dogstatsd: bool,
+    /// Extra `key:value` tags added to every metric (DogStatsD only)
+    pub tags: Vec<String>,
+}
+
+/// Turns snapshots into StatsD metric lines
+///
+/// Gauges carry the current values. The network byte totals are sent as
+/// counters, i.e. as the increase since the previous snapshot, so nothing
+/// is sent for them on the first tick.
+pub struct StatsdEncoder {
+    config: StatsdConfig,
+    last_totals: Option<(u64, u64)>, // (rx, tx)
+}
+
+impl StatsdEncoder {
+    /// Create an encoder
+    pub fn new(config: StatsdConfig) -> Self {
+        StatsdEncoder {
+            config,
+            last_totals: None,
+        }
+    }
+
+    /// Encode one snapshot as StatsD lines
+    pub fn encode(&mut self, snapshot: &Snapshot) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        // CPU
+        lines.push(self.metric("cpu.global", &[], snapshot.cpu.global_usage as f64, "g"));
+        for core in &snapshot.cpu.cores {
+            let index = core.index.to_string();
+            lines.push(self.metric("cpu.core", &[("core", &index)], core.usage as f64, "g"));
+        }
+
+        // Memory
+        lines.push(self.metric(
+            "mem.used_bytes",
+            &[],
+            (snapshot.memory.used_gb * BYTES_PER_GB).round(),
+            "g",
+        ));
+        lines.push(self.metric(
+            "mem.total_bytes",
+            &[],
+            (snapshot.memory.total_gb * BYTES_PER_GB).round(),
+            "g",
+        ));
+        lines.push(self.metric("mem.percent", &[], snapshot.memory.percentage, "g"));
+
+        // Disks
+        for disk in &snapshot.disks {
+            let labels = [("mount_point", disk.mount_point.as_str())];
+            lines.push(self.metric("disk.percent", &labels, disk.percentage, "g"));
+            lines.push(self.metric(
+                "disk.used_bytes",
+                &labels,
+                (disk.used_gb * BYTES_PER_GB).round(),
+                "g",
+            ));
+        }
+
+        // Network speeds (gauges) and traffic (counters)
+        lines.push(self.metric(
+            "net.download_bps",
+            &[],
+            snapshot.network.selected_download_speed.round(),
+            "g",
+        ));
+        lines.push(self.metric(
+            "net.upload_bps",
+            &[],
+            snapshot.network.selected_upload_speed.round(),
+            "g",
+        ));
+
+        let rx = snapshot.network.selected_received;
+        let tx = snapshot.network.selected_transmitted;
+        if let Some((last_rx, last_tx)) = self.last_totals {
+            lines.push(self.metric("net.rx_bytes", &[], rx.saturating_sub(last_rx) as f64, "c"));
+            lines.push(self.metric("net.tx_bytes", &[], tx.saturating_sub(last_tx) as f64, "c"));
+        }
+        self.last_totals = Some((rx, tx));
+
+        lines
+    }
+
+    /// Format one metric line
+    ///
+    /// Labels become DogStatsD tags, or extra name segments in plain StatsD.
+    fn metric(&self, name: &str, labels: &[(&str, &str)], value: f64, kind: &str) -> String {
+        let mut full_name = String::new();
+        if !self.config.prefix.is_empty() {
+            full_name.push_str(&self.config.prefix);
+            full_name.push('.');
+        }
+        full_name.push_str(name);
+
+        if !self.config.dogstatsd {
+            for (_, value) in labels {
+                full_name.push('.');
+                full_name.push_str(&sanitize_segment(value));
+            }
+            return format!("{}:{}|{}", full_name, value, kind);
+        }
+
+        let tags: Vec<String> = self
+            .config
+            .tags
+            .iter()
+            .cloned()
+            .chain(
+                labels
+                    .iter()
+                    .map(|(key, value)| format!("{}:{}", key, sanitize_tag(value))),
+            )
+            .collect();
+
+        if tags.is_empty() {
+            format!("{}:{}|{}", full_name, value, kind)
+        } else {
+            format!("{}:{}|{}|#{}", full_name, value, kind, tags.join(","))
+        }
+    }
+}
+
+/// Pushes snapshots to a StatsD server over UDP
+pub struct StatsdSender {
+    socket: UdpSocket,
+    encoder: StatsdEncoder,
+}
+
+impl StatsdSender {
+    /// Connect to a StatsD server at `host:port`
+    pub fn connect(addr: &str, config: StatsdConfig) -> io::Result<Self> {
+        Ok(StatsdSender {
+            socket: connect_udp(addr)?,
+            encoder: StatsdEncoder::new(config),
+        })
+    }
+
+    /// Send one snapshot (metrics are batched into as few datagrams as possible)
+    pub fn send(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        let lines = self.encoder.encode(snapshot).join("\n");
+        for datagram in split_datagrams(&lines, MAX_DATAGRAM) {
+            // StatsD expects no trailing newline on the last metric
+            self.socket.send(datagram.trim_end().as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+impl Sink for StatsdSender {
+    fn emit(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        // A missing StatsD server must not stop the watch loop
+        if let Err(e) = self.send(snapshot) {
+            eprintln!("Error sending StatsD metrics: {}", e);
+        }
+        Ok(())
+    }
+}
+
+/// Make a value safe to use as a metric name segment
+///
+/// "/" becomes "root" and "/var/log" becomes "var_log".
+fn sanitize_segment(value: &str) -> String {
+    let trimmed = value.trim_matches(|c| c == '/' || c == '\\');
+    if trimmed.is_empty() {
+        return "root".to_string();
+    }
+
+    trimmed
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Make a value safe to use as a DogStatsD tag value
+fn sanitize_tag(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| {
+            if matches!(c, ',' | '|' | '#' | ' ') {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::snapshot::sample_snapshot;
+    use std::time::Duration;
+
+    fn plain_config() -> StatsdConfig {
+        StatsdConfig {
+            prefix: "sysmon".to_string(),
+            dogstatsd: false,
+            tags: vec![],
+        }
+    }
+
+    #[test]
+    fn test_encode_plain_names() {
+        let mut encoder = StatsdEncoder::new(plain_config());
+        let lines = encoder.encode(&sample_snapshot());
+
+        assert!(lines.contains(&"sysmon.cpu.global:25|g".to_string()));
+        assert!(lines.contains(&"sysmon.cpu.core.1:30|g".to_string()));
+        assert!(lines.contains(&"sysmon.disk.percent.root:60|g".to_string()));
+        assert!(lines.contains(&"sysmon.mem.percent:50|g".to_string()));
+    }
+
+    #[test]
+    fn test_encode_dogstatsd_tags() {
+        let mut encoder = StatsdEncoder::new(StatsdConfig {
+            prefix: "sysmon".to_string(),
+            dogstatsd: true,
+            tags: vec!["env:prod".to_string()],
+        });
+        let lines = encoder.encode(&sample_snapshot());
+
+        assert!(lines.contains(&"sysmon.cpu.global:25|g|#env:prod".to_string()));
+        assert!(lines.contains(&"sysmon.cpu.core:20|g|#env:prod,core:0".to_string()));
+        assert!(lines.contains(&"sysmon.disk.percent:60|g|#env:prod,mount_point:/".to_string()));
+    }
+
+    #[test]
+    fn test_counters_are_deltas() {
+        let mut encoder = StatsdEncoder::new(plain_config());
+        let mut snapshot = sample_snapshot();
+
+        // First tick has no baseline
+        let first = encoder.encode(&snapshot);
+        assert!(!first.iter().any(|line| line.ends_with("|c")));
+
+        snapshot.network.selected_received += 300;
+        snapshot.network.selected_transmitted += 100;
+        let second = encoder.encode(&snapshot);
+        assert!(second.contains(&"sysmon.net.rx_bytes:300|c".to_string()));
+        assert!(second.contains(&"sysmon.net.tx_bytes:100|c".to_string()));
+    }
+
+    #[test]
+    fn test_sanitize_segment() {
+        assert_eq!(sanitize_segment("/"), "root");
+        assert_eq!(sanitize_segment("/var/log"), "var_log");
+        assert_eq!(sanitize_segment("C:\\"), "C_");
+    }
+
+    #[test]
+    fn test_send_to_local_listener() {
+        let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+        listener
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        let mut sender = StatsdSender::connect(&addr, plain_config()).unwrap();
+        sender.send(&sample_snapshot()).unwrap();
+
+        let mut buf = [0u8; 2048];
+        let len = listener.recv(&mut buf).unwrap();
+        let datagram = String::from_utf8_lossy(&buf[..len]);
+        assert!(datagram.starts_with("sysmon.cpu.global:25|g\n"));
+        assert!(!datagram.ends_with('\n'));
+    }
+}
@@ -0,0 +1,293 @@
+// Terminal output module
+// Colored, human-readable view of system snapshots
+
+use super::Sink;
+use crate::display::formatter;
+use crate::display::sparkline::{SparklineStyle, Trends, DEFAULT_SPARKLINE_LENGTH};
+use crate::display::style::Style;
+use crate::monitor::snapshot::Snapshot;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+
+/// A part of the colored overview
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Section {
+    /// Global and per-core CPU usage
+    Cpu,
+    /// RAM usage
+    Memory,
+    /// Disk usage per mount point
+    Disk,
+    /// Network speed and totals
+    Network,
+    /// Top processes by CPU and memory, and the process tree
+    Processes,
+    /// System uptime
+    Uptime,
+}
+
+impl Section {
+    /// Every section in the default order
+    pub const ALL: [Section; 6] = [
+        Section::Cpu,
+        Section::Memory,
+        Section::Disk,
+        Section::Network,
+        Section::Processes,
+        Section::Uptime,
+    ];
+}
+
+/// What the colored overview shows and how
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewOptions {
+    /// If true, shows all cores; if false, shows top 3
+    pub detailed: bool,
+    /// Sections to render, in this order
+    pub sections: Vec<Section>,
+    /// Colors and thresholds
+    pub style: Style,
+    /// Samples drawn as sparklines in watch mode (0 turns them off)
+    pub sparkline_length: usize,
+    /// Characters used to draw the sparklines
+    pub sparkline_style: SparklineStyle,
+}
+
+impl ViewOptions {
+    /// Every section with the default style
+    ///
+    /// # Arguments
+    /// * `detailed` - If true, shows all cores; if false, shows top 3
+    pub fn new(detailed: bool) -> Self {
+        ViewOptions {
+            detailed,
+            sections: Section::ALL.to_vec(),
+            style: Style::default(),
+            sparkline_length: DEFAULT_SPARKLINE_LENGTH,
+            sparkline_style: SparklineStyle::default(),
+        }
+    }
+
+    /// Empty sparkline buffers, or None if sparklines are off
+    pub fn trends(&self) -> Option<Trends> {
+        (self.sparkline_length > 0)
+            .then(|| Trends::new(self.sparkline_length, self.sparkline_style))
+    }
+}
+
+/// Terminal sink rendering the colored system overview
+pub struct TerminalSink<W: Write> {
+    out: W,
+    options: ViewOptions,
+    watch_interval: Option<u64>,
+    trends: Option<Trends>,
+}
+
+impl<W: Write> TerminalSink<W> {
+    /// Create a terminal sink for a single snapshot
+    ///
+    /// # Arguments
+    /// * `out` - Destination writer (usually stdout)
+    /// * `options` - Sections and style of the overview
+    pub fn new(out: W, options: ViewOptions) -> Self {
+        TerminalSink {
+            out,
+            options,
+            watch_interval: None,
+            trends: None,
+        }
+    }
+
+    /// Switch to watch mode: clear the screen before every frame and draw
+    /// sparklines of the recent values
+    ///
+    /// # Arguments
+    /// * `interval` - Update interval in seconds (shown in the header)
+    pub fn watching(mut self, interval: u64) -> Self {
+        self.watch_interval = Some(interval);
+        self.trends = self.options.trends();
+        self
+    }
+
+    /// Render one complete frame into a writer
+    pub fn render<O: Write>(&self, out: &mut O, snapshot: &Snapshot) -> io::Result<()> {
+        let watch_mode = self.watch_interval.is_some();
+
+        formatter::print_header(out, watch_mode, self.watch_interval.unwrap_or(0))?;
+        render_sections(out, snapshot, &self.options, self.trends.as_ref())?;
+        formatter::print_footer(out, watch_mode, &self.options.style)
+    }
+}
+
+/// Render the selected sections of the overview (no header or footer)
+///
+/// # Arguments
+/// * `out` - Destination writer
+/// * `snapshot` - Snapshot to display
+/// * `options` - Sections and style of the overview
+/// * `trends` - Recent values drawn as sparklines (None for a single snapshot)
+pub fn render_sections<W: Write>(
+    out: &mut W,
+    snapshot: &Snapshot,
+    options: &ViewOptions,
+    trends: Option<&Trends>,
+) -> io::Result<()> {
+    let style = &options.style;
+
+    for (i, section) in options.sections.iter().enumerate() {
+        match section {
+            Section::Cpu => {
+                formatter::print_cpu_info(out, &snapshot.cpu, options.detailed, style, trends)?
+            }
+            Section::Memory => formatter::print_memory_info(out, &snapshot.memory, style, trends)?,
+            Section::Disk => formatter::print_disk_info(out, &snapshot.disks, style)?,
+            Section::Network => formatter::print_network_info(
+                out,
+                &snapshot.network,
+                options.detailed,
+                style,
+                trends,
+            )?,
+            Section::Processes => {
+                formatter::print_top_processes_cpu(out, &snapshot.top_processes_cpu, style)?;
+                formatter::print_top_processes_memory(out, &snapshot.top_processes_memory, style)?;
+                formatter::print_process_tree(out, &snapshot.process_tree, style)?;
+            }
+            Section::Uptime => {
+                formatter::print_uptime(out, snapshot.uptime_secs)?;
+                // Uptime is usually last and has no blank line of its own
+                if i + 1 < options.sections.len() {
+                    writeln!(out)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+impl<W: Write> Sink for TerminalSink<W> {
+    fn emit(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        if let Some(trends) = &mut self.trends {
+            trends.record(snapshot);
+        }
+
+        // Render the whole frame first so it is written in one go
+        let mut frame = Vec::new();
+        self.render(&mut frame, snapshot)?;
+
+        if self.watch_interval.is_some() {
+            formatter::clear_screen();
+        }
+
+        self.out.write_all(&frame)?;
+        self.out.flush()
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if self.watch_interval.is_some() {
+            writeln!(self.out, "\nExiting System Monitor...")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::snapshot::sample_snapshot;
+
+    fn render_to_string(sink: TerminalSink<Vec<u8>>) -> String {
+        let mut sink = sink;
+        sink.emit(&sample_snapshot()).unwrap();
+        String::from_utf8(sink.out).unwrap()
+    }
+
+    #[test]
+    fn test_renders_all_sections() {
+        let text = render_to_string(TerminalSink::new(Vec::new(), ViewOptions::new(false)));
+
+        assert!(text.contains("(2 cores)"));
+        assert!(text.contains("Top 3: Core 1"));
+        assert!(text.contains("Memory:"));
+        assert!(text.contains("Disk Usage:"));
+        assert!(text.contains("Network:"));
+        assert!(text.contains("Top 1 Processes (by CPU):"));
+        assert!(text.contains("cargo"));
+        assert!(text.contains("Uptime: 0 days, 1 hours, 0 minutes"));
+        assert!(text.contains("Run with --watch"));
+    }
+
+    #[test]
+    fn test_detailed_shows_every_core() {
+        let text = render_to_string(TerminalSink::new(Vec::new(), ViewOptions::new(true)));
+
+        assert!(text.contains("Core  0:"));
+        assert!(text.contains("Core  1:"));
+        assert!(!text.contains("Top 3:"));
+        // One row per network interface
+        assert!(text.contains("  eth0 "));
+        assert!(
+            !render_to_string(TerminalSink::new(Vec::new(), ViewOptions::new(false)))
+                .contains("eth0")
+        );
+    }
+
+    #[test]
+    fn test_renders_process_tree() {
+        let mut snapshot = sample_snapshot();
+        let mut child = snapshot.top_processes_cpu[0].clone();
+        child.pid = 43;
+        child.parent_pid = Some(42);
+        snapshot.process_tree =
+            crate::monitor::tree::build_tree(&[snapshot.top_processes_cpu[0].clone(), child]);
+
+        let mut out = Vec::new();
+        render_sections(&mut out, &snapshot, &ViewOptions::new(false), None).unwrap();
+        let text = String::from_utf8(out).unwrap();
+
+        assert!(text.contains("Process Tree:"));
+        assert!(text.contains("└─ cargo"));
+        // Parent row shows the subtree totals
+        assert!(text.contains("25.0%"));
+    }
+
+    #[test]
+    fn test_renders_selected_sections_in_order() {
+        let options = ViewOptions {
+            sections: vec![Section::Uptime, Section::Memory],
+            ..ViewOptions::new(false)
+        };
+        let mut out = Vec::new();
+        render_sections(&mut out, &sample_snapshot(), &options, None).unwrap();
+        let text = String::from_utf8(out).unwrap();
+
+        assert!(!text.contains("CPU:"));
+        assert!(!text.contains("Network:"));
+        assert!(text.find("Uptime:").unwrap() < text.find("Memory:").unwrap());
+    }
+
+    #[test]
+    fn test_watch_mode_draws_sparklines() {
+        let options = ViewOptions {
+            sparkline_length: 3,
+            ..ViewOptions::new(false)
+        };
+        let mut sink = TerminalSink::new(Vec::new(), options).watching(1);
+        for cpu in [0.0, 50.0, 100.0, 100.0] {
+            let mut snapshot = sample_snapshot();
+            snapshot.cpu.global_usage = cpu;
+            sink.out.clear();
+            sink.emit(&snapshot).unwrap();
+        }
+        let text = String::from_utf8(sink.out).unwrap();
+
+        // Only the last three samples are kept
+        assert!(text.contains("▅██"), "{}", text);
+        assert!(text.contains("Upload:"));
+
+        // Single snapshots have no history to draw
+        let text = render_to_string(TerminalSink::new(Vec::new(), ViewOptions::new(false)));
+        assert!(!text.contains('▁'));
+    }
+}
@@ -0,0 +1,51 @@
+// Process detail command
+// Shows everything known about one process (`sysmon proc <pid>`)
+
+use crate::cli::OutputFormat;
+use std::io::{self, Write};
+use std::thread;
+use system_monitor::display::formatter;
+use system_monitor::display::style::Style;
+use system_monitor::SystemMonitor;
+
+/// Print the details of one process
+///
+/// Returns the process exit code: 0 on success, 1 if the process does not
+/// exist, 2 for an unsupported format.
+///
+/// # Arguments
+/// * `monitor` - System monitor instance
+/// * `pid` - Process ID
+/// * `format` - Text or JSON
+/// * `style` - Colors and thresholds of the text view
+pub fn run(monitor: &mut SystemMonitor, pid: u32, format: OutputFormat, style: &Style) -> i32 {
+    if !matches!(format, OutputFormat::Text | OutputFormat::Json) {
+        eprintln!("Error: sysmon proc supports --format text or json");
+        return 2;
+    }
+
+    // CPU usage is measured between two refreshes
+    thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+    monitor.refresh();
+
+    let Some(detail) = monitor.process_detail(pid) else {
+        eprintln!("No process with PID {}", pid);
+        return 1;
+    };
+
+    let mut out = io::stdout().lock();
+    let result = match format {
+        OutputFormat::Json => serde_json::to_writer_pretty(&mut out, &detail)
+            .map_err(io::Error::from)
+            .and_then(|()| writeln!(out)),
+        _ => formatter::print_process_detail(&mut out, &detail, style),
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Error writing output: {}", e);
+            1
+        }
+    }
+}
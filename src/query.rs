@@ -0,0 +1,228 @@
+// Query module - Metric history
+// Prints the recorded samples of a time range (`sysmon history`)
+
+use crate::cli::OutputFormat;
+use crate::config::Settings;
+use std::io::{self, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use system_monitor::alert::Metric;
+use system_monitor::display::formatter::format_utc;
+use system_monitor::display::style::Style;
+use system_monitor::history::{HistoryStore, Sample};
+use system_monitor::NetworkInfo;
+
+/// Metrics shown when none are given
+fn default_metrics() -> Vec<Metric> {
+    vec![
+        Metric::CpuGlobal,
+        Metric::Memory,
+        Metric::Disk(None),
+        Metric::NetRx,
+        Metric::NetTx,
+    ]
+}
+
+/// Print the recorded samples between `since` and `until` ago
+///
+/// Returns the process exit code: 0 on success, 1 if the history can't be
+/// read, 2 for invalid arguments.
+///
+/// # Arguments
+/// * `settings` - History directory, output format and style
+/// * `since` - Start of the range, as a duration ago
+/// * `until` - End of the range, as a duration ago (None for now)
+/// * `metrics` - Metrics to show (empty for the defaults)
+pub fn run(
+    settings: &Settings,
+    since: Duration,
+    until: Option<Duration>,
+    metrics: &[Metric],
+) -> i32 {
+    if !matches!(settings.format, OutputFormat::Text | OutputFormat::Json) {
+        eprintln!("Error: sysmon history supports --format text or json");
+        return 2;
+    }
+    let Some(dir) = &settings.history.dir else {
+        eprintln!("Error: no history directory, use --history-dir or [history] dir");
+        return 2;
+    };
+    if let Some(metric) = metrics.iter().find(|m| matches!(m, Metric::CpuCore(_))) {
+        eprintln!(
+            "Error: {} is not recorded, use cpu for the global usage",
+            metric
+        );
+        return 2;
+    }
+    let until = until.unwrap_or(Duration::ZERO);
+    if until > since {
+        eprintln!("Error: --until must be more recent than --since");
+        return 2;
+    }
+
+    let metrics = if metrics.is_empty() {
+        default_metrics()
+    } else {
+        metrics.to_vec()
+    };
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    let from_ms = now_ms.saturating_sub(since.as_millis() as u64);
+    let to_ms = now_ms.saturating_sub(until.as_millis() as u64);
+
+    let samples = match HistoryStore::new(dir).query(from_ms, to_ms) {
+        Ok(samples) => samples,
+        Err(e) => {
+            eprintln!("Error reading history in {}: {}", dir.display(), e);
+            return 1;
+        }
+    };
+
+    let mut out = io::stdout().lock();
+    let result = match settings.format {
+        OutputFormat::Json => write_json(&mut out, &samples, &metrics),
+        _ if samples.is_empty() => writeln!(
+            out,
+            "No samples recorded between {} and {} (in {})",
+            format_utc(from_ms / 1000),
+            format_utc(to_ms / 1000),
+            dir.display()
+        ),
+        _ => write_table(&mut out, &samples, &metrics, &settings.style),
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Error writing output: {}", e);
+            1
+        }
+    }
+}
+
+/// Format a value as a percentage or a rate
+fn format_value(metric: &Metric, value: Option<f64>) -> String {
+    match value {
+        None => "-".to_string(),
+        Some(value) if metric.is_rate() => {
+            let (speed, unit) = NetworkInfo::format_speed(value);
+            format!("{:.1} {}", speed, unit)
+        }
+        Some(value) => format!("{:.1}%", value),
+    }
+}
+
+/// One row per sample, then the minimum, average and maximum of each metric
+fn write_table<W: Write>(
+    out: &mut W,
+    samples: &[Sample],
+    metrics: &[Metric],
+    style: &Style,
+) -> io::Result<()> {
+    let names: Vec<String> = metrics.iter().map(Metric::to_string).collect();
+    let widths: Vec<usize> = names.iter().map(|name| name.len().max(11)).collect();
+
+    let mut header = format!("{:<19}", "Time (UTC)");
+    for (name, width) in names.iter().zip(&widths) {
+        header.push_str(&format!("  {:>width$}", name, width = width));
+    }
+    writeln!(out, "{}", style.heading(&header))?;
+
+    for sample in samples {
+        let time = format_utc(sample.timestamp_ms / 1000);
+        write!(out, "{:<19}", time.trim_end_matches(" UTC"))?;
+        for (metric, width) in metrics.iter().zip(&widths) {
+            let value = format_value(metric, sample.value(metric));
+            write!(out, "  {:>width$}", value, width = width)?;
+        }
+        writeln!(out)?;
+    }
+
+    // Minimum, average and maximum of every metric
+    let summaries: Vec<[Option<f64>; 3]> = metrics
+        .iter()
+        .map(|metric| {
+            let values: Vec<f64> = samples.iter().filter_map(|s| s.value(metric)).collect();
+            [
+                values.iter().copied().min_by(f64::total_cmp),
+                (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64),
+                values.iter().copied().max_by(f64::total_cmp),
+            ]
+        })
+        .collect();
+    for (row, label) in ["min", "avg", "max"].iter().enumerate() {
+        write!(out, "{}", style.muted(&format!("{:<19}", label)))?;
+        for ((metric, width), summary) in metrics.iter().zip(&widths).zip(&summaries) {
+            let value = format_value(metric, summary[row]);
+            write!(out, "  {:>width$}", value, width = width)?;
+        }
+        writeln!(out)?;
+    }
+    writeln!(
+        out,
+        "{}",
+        style.muted(&format!("{} samples", samples.len()))
+    )
+}
+
+/// A JSON array with one object per sample
+fn write_json<W: Write>(out: &mut W, samples: &[Sample], metrics: &[Metric]) -> io::Result<()> {
+    let rows: Vec<serde_json::Value> = samples
+        .iter()
+        .map(|sample| {
+            let mut row = serde_json::Map::new();
+            row.insert("timestamp_ms".to_string(), sample.timestamp_ms.into());
+            for metric in metrics {
+                row.insert(metric.to_string(), sample.value(metric).into());
+            }
+            row.into()
+        })
+        .collect();
+    serde_json::to_writer_pretty(&mut *out, &rows)?;
+    writeln!(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn sample(secs: u64, cpu: f32) -> Sample {
+        Sample {
+            timestamp_ms: secs * 1000,
+            cpu,
+            mem: 50.0,
+            disk: BTreeMap::from([("/".to_string(), 70.0)]),
+            rx: 2048.0,
+            tx: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_table_with_summary() {
+        let samples = [sample(1_700_000_000, 10.0), sample(1_700_000_060, 30.0)];
+        let metrics = [
+            Metric::CpuGlobal,
+            Metric::Disk(Some("/var".to_string())),
+            Metric::NetRx,
+        ];
+
+        let mut out = Vec::new();
+        write_table(&mut out, &samples, &metrics, &Style::default()).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert!(lines[0].contains("Time (UTC)"));
+        assert!(lines[0].contains("cpu.global"));
+        assert!(lines[1].starts_with("2023-11-14 22:13:20"));
+        assert!(lines[1].contains("10.0%"));
+        assert!(lines[1].contains("2.0 KB/s"));
+        // Unknown mount points have no values
+        assert!(lines[1].contains("          -"));
+        assert!(lines[3].contains("min") && lines[3].contains("10.0%"));
+        assert!(lines[4].contains("avg") && lines[4].contains("20.0%"));
+        assert!(lines[5].contains("max") && lines[5].contains("30.0%"));
+        assert!(lines[6].contains("2 samples"));
+    }
+}
@@ -0,0 +1,214 @@
+// Exporter module
+// Serves Prometheus metrics over plain HTTP (`sysmon serve`)
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use system_monitor::display::formatter::format_utc;
+use system_monitor::output::prometheus::{self, PrometheusSink};
+use system_monitor::output::Sink;
+use system_monitor::{Snapshot, SystemMonitor};
+
+/// Metrics page cache that limits how often the system is refreshed
+///
+/// Scrapes arriving faster than `min_interval` are answered from the last
+/// rendered page instead of refreshing the system again.
+pub struct MetricsCache {
+    min_interval: Duration,
+    sink: PrometheusSink,
+    refreshed_at: Option<Instant>,
+}
+
+impl MetricsCache {
+    /// Create an empty cache
+    pub fn new(min_interval: Duration) -> Self {
+        MetricsCache {
+            min_interval,
+            sink: PrometheusSink::default(),
+            refreshed_at: None,
+        }
+    }
+
+    /// Whether the cached page is too old to be served
+    pub fn is_stale(&self, now: Instant) -> bool {
+        match self.refreshed_at {
+            Some(at) => now.duration_since(at) >= self.min_interval,
+            None => true,
+        }
+    }
+
+    /// Get the metrics page, refreshing the monitor only if the cache is stale
+    ///
+    /// Attached and removed devices are logged to stderr.
+    pub fn page(&mut self, monitor: &mut SystemMonitor) -> io::Result<&str> {
+        let now = Instant::now();
+
+        if self.is_stale(now) {
+            monitor.refresh();
+            let snapshot = Snapshot::collect(monitor, false);
+            for event in monitor.device_events() {
+                eprintln!("{} {}", format_utc(snapshot.timestamp_ms / 1000), event);
+            }
+            self.sink.emit(&snapshot)?;
+            self.refreshed_at = Some(now);
+        }
+
+        Ok(self.sink.page())
+    }
+}
+
+/// Serve metrics until Ctrl+C
+///
+/// # Arguments
+/// * `listen` - Address to bind (e.g. 127.0.0.1:9101)
+/// * `min_refresh` - Minimum time between two system refreshes
+pub fn run(
+    monitor: &mut SystemMonitor,
+    listen: SocketAddr,
+    min_refresh: Duration,
+    running: &AtomicBool,
+) -> io::Result<()> {
+    let listener = TcpListener::bind(listen)?;
+    // Non-blocking accept so the running flag is checked regularly
+    listener.set_nonblocking(true)?;
+
+    eprintln!("Serving Prometheus metrics on http://{}/metrics", listen);
+
+    let mut cache = MetricsCache::new(min_refresh);
+
+    while running.load(Ordering::SeqCst) {
+        match listener.accept() {
+            Ok((stream, _peer)) => {
+                if let Err(e) = handle_connection(stream, monitor, &mut cache) {
+                    eprintln!("Error handling request: {}", e);
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(50));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Answer a single HTTP request
+fn handle_connection(
+    stream: TcpStream,
+    monitor: &mut SystemMonitor,
+    cache: &mut MetricsCache,
+) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // Drain the request headers
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 && line != "\r\n" && line != "\n" {
+        line.clear();
+    }
+
+    let mut writer = &stream;
+    match parse_request_line(&request_line) {
+        Some(("GET", "/metrics")) => {
+            let page = cache.page(monitor)?;
+            write_response(&mut writer, "200 OK", prometheus::CONTENT_TYPE, page)
+        }
+        Some(("GET", "/")) => write_response(
+            &mut writer,
+            "200 OK",
+            "text/plain; charset=utf-8",
+            "sysmon exporter - metrics at /metrics\n",
+        ),
+        Some((_, _)) => write_response(
+            &mut writer,
+            "404 Not Found",
+            "text/plain; charset=utf-8",
+            "Not Found\n",
+        ),
+        None => write_response(
+            &mut writer,
+            "400 Bad Request",
+            "text/plain; charset=utf-8",
+            "Bad Request\n",
+        ),
+    }
+}
+
+/// Split an HTTP request line into method and path (query string dropped)
+fn parse_request_line(line: &str) -> Option<(&str, &str)> {
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?;
+    let target = parts.next()?;
+    parts.next()?; // HTTP version
+
+    let path = target.split('?').next().unwrap_or(target);
+    Some((method, path))
+}
+
+/// Write a complete HTTP/1.1 response and close the connection
+fn write_response<W: Write>(
+    out: &mut W,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> io::Result<()> {
+    write!(
+        out,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request_line() {
+        assert_eq!(
+            parse_request_line("GET /metrics HTTP/1.1\r\n"),
+            Some(("GET", "/metrics"))
+        );
+        assert_eq!(
+            parse_request_line("GET /metrics?x=1 HTTP/1.0\r\n"),
+            Some(("GET", "/metrics"))
+        );
+        assert_eq!(parse_request_line("garbage\r\n"), None);
+    }
+
+    #[test]
+    fn test_cache_staleness() {
+        let cache = MetricsCache::new(Duration::from_secs(5));
+        let now = Instant::now();
+        assert!(cache.is_stale(now));
+
+        let cache = MetricsCache {
+            refreshed_at: Some(now),
+            ..cache
+        };
+        assert!(!cache.is_stale(now + Duration::from_secs(1)));
+        assert!(cache.is_stale(now + Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_write_response() {
+        let mut buf = Vec::new();
+        write_response(&mut buf, "200 OK", "text/plain", "hello").unwrap();
+
+        let text = String::from_utf8(buf).unwrap();
+        assert!(text.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(text.contains("Content-Length: 5\r\n"));
+        assert!(text.ends_with("\r\n\r\nhello"));
+    }
+}
@@ -0,0 +1,438 @@
+// Interactive mode state
+// Holds what the user toggled with the keyboard and maps keys to actions
+
+use super::table::{ProcessTable, SortColumn};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use system_monitor::display::sparkline::Trends;
+use system_monitor::monitor::signal::Signal;
+
+/// Refresh intervals (seconds) cycled through with + and -
+const INTERVAL_STEPS: [u64; 8] = [1, 2, 3, 5, 10, 15, 30, 60];
+
+/// What the event loop should do after a key press
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Nothing changed
+    None,
+    /// State changed, redraw with the current data
+    Redraw,
+    /// Another view or detail level, filled from the data of the last refresh
+    ViewChanged,
+    /// Send a confirmed signal to a process
+    Signal(u32, Signal),
+    /// Leave interactive mode
+    Quit,
+}
+
+/// Question shown in the status bar, which takes all key presses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Prompt {
+    /// Typing the name or number of a signal for a process
+    SignalInput {
+        pid: u32,
+        name: String,
+        input: String,
+    },
+    /// Typing a filter expression for the process table
+    Search { input: String },
+    /// Waiting for y/n before sending a signal
+    Confirm {
+        pid: u32,
+        name: String,
+        signal: Signal,
+    },
+}
+
+/// Screen shown in interactive mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    /// The regular system overview
+    Overview,
+    /// Scrollable table of every process
+    Processes,
+    /// Everything known about one process
+    Detail(u32),
+}
+
+/// Interactive mode state
+#[derive(Debug)]
+pub struct App {
+    pub interval: u64,
+    pub detailed: bool,
+    pub paused: bool,
+    pub view: View,
+    pub table: ProcessTable,
+    pub prompt: Option<Prompt>,
+    /// Result of the last command, shown until the next key press
+    pub message: Option<String>,
+    /// Recent values drawn as sparklines (kept when the interval changes)
+    pub trends: Option<Trends>,
+}
+
+impl App {
+    /// Create the state from the command-line settings
+    pub fn new(interval: u64, detailed: bool) -> Self {
+        App {
+            interval: interval.max(1),
+            detailed,
+            paused: false,
+            view: View::Overview,
+            table: ProcessTable::new(),
+            prompt: None,
+            message: None,
+            trends: None,
+        }
+    }
+
+    /// Apply a key press
+    pub fn handle_key(&mut self, key: KeyEvent) -> Action {
+        // Raw mode swallows SIGINT, so Ctrl+C arrives as a key
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Action::Quit;
+        }
+
+        let had_message = self.message.take().is_some();
+
+        if let Some(prompt) = self.prompt.take() {
+            return self.handle_prompt_key(prompt, key);
+        }
+
+        match self.view {
+            View::Processes => {
+                if let Some(action) = self.handle_table_key(key) {
+                    return action;
+                }
+            }
+            View::Detail(_) => {
+                if matches!(
+                    key.code,
+                    KeyCode::Esc | KeyCode::Backspace | KeyCode::Left | KeyCode::Char('i')
+                ) {
+                    self.view = View::Processes;
+                    return Action::ViewChanged;
+                }
+            }
+            View::Overview => {}
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
+            KeyCode::Tab => {
+                self.view = match self.view {
+                    View::Overview => View::Processes,
+                    _ => View::Overview,
+                };
+                Action::ViewChanged
+            }
+            KeyCode::Char('p') | KeyCode::Char(' ') => {
+                self.paused = !self.paused;
+                Action::Redraw
+            }
+            KeyCode::Char('+') | KeyCode::Char('=') => {
+                self.interval = next_interval(self.interval);
+                Action::Redraw
+            }
+            KeyCode::Char('-') => {
+                self.interval = previous_interval(self.interval);
+                Action::Redraw
+            }
+            KeyCode::Char('d') => {
+                self.detailed = !self.detailed;
+                Action::ViewChanged
+            }
+            KeyCode::Char('/') => {
+                self.prompt = Some(Prompt::Search {
+                    input: self.table.filter_text().to_string(),
+                });
+                if self.view == View::Processes {
+                    Action::Redraw
+                } else {
+                    self.view = View::Processes;
+                    Action::ViewChanged
+                }
+            }
+            _ if had_message => Action::Redraw,
+            _ => Action::None,
+        }
+    }
+
+    /// Keys that only apply to the process table
+    fn handle_table_key(&mut self, key: KeyEvent) -> Option<Action> {
+        let table = &mut self.table;
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => table.move_by(-1),
+            KeyCode::Down | KeyCode::Char('j') => table.move_by(1),
+            KeyCode::PageUp => table.page_up(),
+            KeyCode::PageDown => table.page_down(),
+            KeyCode::Home | KeyCode::Char('g') => table.first(),
+            KeyCode::End | KeyCode::Char('G') => table.last(),
+            KeyCode::Char('1') => table.sort_by(SortColumn::Pid),
+            KeyCode::Char('2') => table.sort_by(SortColumn::Name),
+            KeyCode::Char('3') => table.sort_by(SortColumn::Cpu),
+            KeyCode::Char('4') => table.sort_by(SortColumn::Memory),
+            KeyCode::Char('v') => table.toggle_tree(),
+            KeyCode::Left | KeyCode::Char('h') => table.set_collapsed(true),
+            KeyCode::Right | KeyCode::Char('l') => table.set_collapsed(false),
+            KeyCode::Enter => table.toggle_collapsed(),
+            KeyCode::Char('i') => {
+                if let Some(process) = self.table.selected() {
+                    self.view = View::Detail(process.pid);
+                    return Some(Action::ViewChanged);
+                }
+            }
+            KeyCode::Char('t') => self.ask_confirm(Signal::TERM),
+            KeyCode::Char('K') => self.ask_confirm(Signal::KILL),
+            KeyCode::Char('s') => self.ask_confirm(Signal::STOP),
+            KeyCode::Char('c') => self.ask_confirm(Signal::CONT),
+            KeyCode::Char('x') => {
+                if let Some(process) = self.table.selected() {
+                    self.prompt = Some(Prompt::SignalInput {
+                        pid: process.pid,
+                        name: process.name.clone(),
+                        input: String::new(),
+                    });
+                }
+            }
+            _ => return None,
+        }
+        Some(Action::Redraw)
+    }
+
+    /// Ask before sending `signal` to the selected process
+    fn ask_confirm(&mut self, signal: Signal) {
+        if let Some(process) = self.table.selected() {
+            self.prompt = Some(Prompt::Confirm {
+                pid: process.pid,
+                name: process.name.clone(),
+                signal,
+            });
+        }
+    }
+
+    /// Keys while a prompt is open
+    fn handle_prompt_key(&mut self, prompt: Prompt, key: KeyEvent) -> Action {
+        match prompt {
+            Prompt::Confirm { pid, name, signal } => match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => Action::Signal(pid, signal),
+                _ => {
+                    self.message = Some(format!("Not sending {} to {}", signal, name));
+                    Action::Redraw
+                }
+            },
+            Prompt::Search { mut input } => {
+                match key.code {
+                    KeyCode::Esc => return Action::Redraw,
+                    KeyCode::Enter => {
+                        if let Err(e) = self.table.set_filter(&input) {
+                            self.message = Some(format!("Invalid filter: {}", e));
+                        }
+                        return Action::Redraw;
+                    }
+                    KeyCode::Backspace => {
+                        input.pop();
+                    }
+                    KeyCode::Char(c) => input.push(c),
+                    _ => {}
+                }
+                self.prompt = Some(Prompt::Search { input });
+                Action::Redraw
+            }
+            Prompt::SignalInput {
+                pid,
+                name,
+                mut input,
+            } => {
+                match key.code {
+                    KeyCode::Esc => return Action::Redraw,
+                    KeyCode::Enter => {
+                        match input.parse::<Signal>() {
+                            Ok(signal) => self.prompt = Some(Prompt::Confirm { pid, name, signal }),
+                            Err(e) => self.message = Some(format!("Error: {}", e)),
+                        }
+                        return Action::Redraw;
+                    }
+                    KeyCode::Backspace => {
+                        input.pop();
+                    }
+                    KeyCode::Char(c) if c.is_ascii_alphanumeric() => input.push(c),
+                    _ => {}
+                }
+                self.prompt = Some(Prompt::SignalInput { pid, name, input });
+                Action::Redraw
+            }
+        }
+    }
+}
+
+/// Next longer refresh interval
+fn next_interval(current: u64) -> u64 {
+    INTERVAL_STEPS
+        .iter()
+        .copied()
+        .find(|&step| step > current)
+        .unwrap_or(current)
+}
+
+/// Next shorter refresh interval
+fn previous_interval(current: u64) -> u64 {
+    INTERVAL_STEPS
+        .iter()
+        .rev()
+        .copied()
+        .find(|&step| step < current)
+        .unwrap_or(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_pause_toggles() {
+        let mut app = App::new(1, false);
+        assert_eq!(app.handle_key(key(KeyCode::Char('p'))), Action::Redraw);
+        assert!(app.paused);
+        app.handle_key(key(KeyCode::Char(' ')));
+        assert!(!app.paused);
+    }
+
+    #[test]
+    fn test_interval_steps() {
+        let mut app = App::new(1, false);
+        app.handle_key(key(KeyCode::Char('+')));
+        app.handle_key(key(KeyCode::Char('+')));
+        assert_eq!(app.interval, 3);
+        app.handle_key(key(KeyCode::Char('-')));
+        assert_eq!(app.interval, 2);
+    }
+
+    #[test]
+    fn test_interval_limits() {
+        assert_eq!(previous_interval(1), 1);
+        assert_eq!(next_interval(60), 60);
+        // Values outside the steps snap to the nearest step
+        assert_eq!(next_interval(4), 5);
+        assert_eq!(previous_interval(4), 3);
+    }
+
+    #[test]
+    fn test_detailed_changes_view() {
+        let mut app = App::new(1, false);
+        assert_eq!(app.handle_key(key(KeyCode::Char('d'))), Action::ViewChanged);
+        assert!(app.detailed);
+    }
+
+    #[test]
+    fn test_quit_keys() {
+        let mut app = App::new(1, false);
+        assert_eq!(app.handle_key(key(KeyCode::Char('q'))), Action::Quit);
+        assert_eq!(app.handle_key(key(KeyCode::Esc)), Action::Quit);
+        assert_eq!(
+            app.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            Action::Quit
+        );
+    }
+
+    #[test]
+    fn test_tab_switches_view() {
+        let mut app = App::new(1, false);
+        assert_eq!(app.handle_key(key(KeyCode::Tab)), Action::ViewChanged);
+        assert_eq!(app.view, View::Processes);
+        // Navigation keys are ignored outside the table view
+        app.handle_key(key(KeyCode::Tab));
+        assert_eq!(app.handle_key(key(KeyCode::Down)), Action::None);
+    }
+
+    fn app_with_process() -> App {
+        let mut app = App::new(1, false);
+        app.handle_key(key(KeyCode::Tab));
+        app.table.set_rows(vec![system_monitor::ProcessInfo {
+            name: "cargo".to_string(),
+            pid: 42,
+            parent_pid: None,
+            user: None,
+            cpu_usage: 0.0,
+            memory_mb: 0.0,
+        }]);
+        app
+    }
+
+    #[test]
+    fn test_signal_needs_confirmation() {
+        let mut app = app_with_process();
+        assert_eq!(app.handle_key(key(KeyCode::Char('t'))), Action::Redraw);
+        assert!(matches!(app.prompt, Some(Prompt::Confirm { pid: 42, .. })));
+        assert_eq!(
+            app.handle_key(key(KeyCode::Char('y'))),
+            Action::Signal(42, Signal::TERM)
+        );
+
+        // Anything but y cancels
+        app.handle_key(key(KeyCode::Char('K')));
+        assert_eq!(app.handle_key(key(KeyCode::Char('n'))), Action::Redraw);
+        assert!(app.prompt.is_none());
+        assert!(app.message.is_some());
+    }
+
+    #[test]
+    fn test_arbitrary_signal_input() {
+        let mut app = app_with_process();
+        app.handle_key(key(KeyCode::Char('x')));
+        for c in "usr1".chars() {
+            app.handle_key(key(KeyCode::Char(c)));
+        }
+        app.handle_key(key(KeyCode::Enter));
+        assert!(matches!(app.prompt, Some(Prompt::Confirm { .. })));
+        let expected: Signal = "USR1".parse().unwrap();
+        assert_eq!(
+            app.handle_key(key(KeyCode::Char('y'))),
+            Action::Signal(42, expected)
+        );
+
+        // Unknown names are reported instead of sent
+        app.handle_key(key(KeyCode::Char('x')));
+        app.handle_key(key(KeyCode::Char('z')));
+        app.handle_key(key(KeyCode::Enter));
+        assert!(app.prompt.is_none());
+        assert!(app.message.as_deref().unwrap().contains("unknown signal"));
+    }
+
+    #[test]
+    fn test_search_sets_table_filter() {
+        let mut app = App::new(1, false);
+        assert_eq!(app.handle_key(key(KeyCode::Char('/'))), Action::ViewChanged);
+        assert_eq!(app.view, View::Processes);
+        for c in "cpu>5".chars() {
+            app.handle_key(key(KeyCode::Char(c)));
+        }
+        app.handle_key(key(KeyCode::Enter));
+        assert!(app.prompt.is_none());
+        assert_eq!(app.table.filter_text(), "cpu>5");
+
+        // Invalid expressions keep the old filter
+        app.handle_key(key(KeyCode::Char('/')));
+        app.handle_key(key(KeyCode::Char('(')));
+        app.handle_key(key(KeyCode::Enter));
+        assert_eq!(app.table.filter_text(), "cpu>5");
+        assert!(app
+            .message
+            .as_deref()
+            .unwrap()
+            .starts_with("Invalid filter"));
+    }
+
+    #[test]
+    fn test_detail_view() {
+        let mut app = app_with_process();
+        assert_eq!(app.handle_key(key(KeyCode::Char('i'))), Action::ViewChanged);
+        assert_eq!(app.view, View::Detail(42));
+
+        // Esc goes back to the table instead of quitting
+        assert_eq!(app.handle_key(key(KeyCode::Esc)), Action::ViewChanged);
+        assert_eq!(app.view, View::Processes);
+    }
+}
@@ -0,0 +1,323 @@
+// Interactive mode - Full-screen view with keyboard controls
+// Uses the alternate screen and raw mode; redraws in place without clearing
+
+mod app;
+pub mod replay;
+mod table;
+
+use crate::config::Settings;
+use crate::watchdog::Watchdog;
+use app::{Action, App, Prompt, View};
+use colored::*;
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyEventKind};
+use crossterm::terminal::{
+    self, disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen,
+    LeaveAlternateScreen,
+};
+use crossterm::{execute, queue};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use system_monitor::display::formatter;
+use system_monitor::display::style::{paint, Style};
+use system_monitor::history::Recorder;
+use system_monitor::monitor::signal;
+use system_monitor::output::terminal::{render_sections, ViewOptions};
+use system_monitor::output::Sink;
+use system_monitor::{ProcessDetail, Snapshot, SystemMonitor};
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Longest time to wait for a key before checking timers again
+const POLL_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Restores the terminal when dropped (also on early return or panic)
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> io::Result<Self> {
+        enable_raw_mode()?;
+        let guard = TerminalGuard;
+        execute!(io::stdout(), EnterAlternateScreen, Hide)?;
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+        let _ = disable_raw_mode();
+    }
+}
+
+/// Run the interactive mode until the user quits
+///
+/// # Arguments
+/// * `monitor` - System monitor instance
+/// * `settings` - Initial interval and detailed setting, sections and style
+/// * `tree` - Start in the process table, showing the process tree
+/// * `running` - Cleared by the Ctrl+C handler
+/// * `watchdog` - Alert rules checked on every refresh
+/// * `recorder` - Metric history every refresh is recorded to
+pub fn run(
+    monitor: &mut SystemMonitor,
+    settings: &Settings,
+    tree: bool,
+    running: &AtomicBool,
+    mut watchdog: Option<&mut Watchdog>,
+    mut recorder: Option<&mut Recorder>,
+) -> io::Result<()> {
+    let _guard = TerminalGuard::enter()?;
+    let mut stdout = io::stdout();
+
+    let options = settings.view_options();
+    let mut app = App::new(settings.interval, settings.detailed);
+    app.trends = options.trends();
+    if tree {
+        app.view = View::Processes;
+        app.table.toggle_tree();
+    }
+    let mut snapshot: Option<Snapshot> = None;
+    let mut detail: Option<ProcessDetail> = None;
+    let mut last_refresh: Option<Instant> = None;
+    let mut dirty = true;
+
+    while running.load(Ordering::SeqCst) {
+        let now = Instant::now();
+        let due = match last_refresh {
+            Some(at) => now.duration_since(at) >= Duration::from_secs(app.interval),
+            None => true,
+        };
+
+        if due && !app.paused {
+            monitor.refresh();
+            let current = Snapshot::collect_top(monitor, settings.top_counts(app.detailed));
+            if let Some(event) = monitor.device_events().last() {
+                app.message = Some(event.to_string());
+            }
+            if let Some(event) = watchdog
+                .as_deref_mut()
+                .and_then(|watchdog| watchdog.check(&current).pop())
+            {
+                app.message = Some(format!("Alert {}: {}", event.state, event.rule));
+            }
+            if let Some(recorder) = recorder.as_deref_mut() {
+                recorder.emit(&current)?;
+            }
+            if let Some(trends) = &mut app.trends {
+                trends.record(&current);
+            }
+            snapshot = Some(current);
+            fill_view(&mut app, monitor, &mut detail);
+            last_refresh = Some(now);
+            dirty = true;
+        }
+
+        if dirty {
+            if let Some(snapshot) = &snapshot {
+                draw(&mut stdout, &mut app, &options, snapshot, detail.as_ref())?;
+            }
+            dirty = false;
+        }
+
+        if !event::poll(POLL_TIMEOUT)? {
+            continue;
+        }
+
+        match event::read()? {
+            // Ignore key releases (reported on Windows)
+            Event::Key(key) if key.kind == KeyEventKind::Press => match app.handle_key(key) {
+                Action::Quit => break,
+                Action::ViewChanged => {
+                    // Sampling again right away would measure rates over a
+                    // few milliseconds, so the last refresh is shown instead
+                    if snapshot.is_some() {
+                        snapshot = Some(Snapshot::collect_top(
+                            monitor,
+                            settings.top_counts(app.detailed),
+                        ));
+                    }
+                    fill_view(&mut app, monitor, &mut detail);
+                    dirty = true;
+                }
+                Action::Signal(pid, sig) => {
+                    app.message = Some(match signal::send_signal(pid, sig) {
+                        Ok(()) => format!("Sent {} to PID {}", sig, pid),
+                        Err(e) => format!("Failed to send {} to PID {}: {}", sig, pid, e),
+                    });
+                    // Show the effect (e.g. a killed process disappearing) right away
+                    last_refresh = None;
+                    dirty = true;
+                }
+                Action::Redraw => dirty = true,
+                Action::None => {}
+            },
+            Event::Resize(_, _) => dirty = true,
+            _ => {}
+        }
+    }
+
+    match recorder {
+        Some(recorder) => recorder.finish(),
+        None => Ok(()),
+    }
+}
+
+/// Fill the process table or detail view from the monitor's current data
+fn fill_view(app: &mut App, monitor: &SystemMonitor, detail: &mut Option<ProcessDetail>) {
+    match app.view {
+        View::Processes => app.table.set_rows(monitor.processes()),
+        View::Detail(pid) => *detail = monitor.process_detail(pid),
+        View::Overview => {}
+    }
+}
+
+/// Draw one frame in place
+fn draw<W: Write>(
+    out: &mut W,
+    app: &mut App,
+    options: &ViewOptions,
+    snapshot: &Snapshot,
+    detail: Option<&ProcessDetail>,
+) -> io::Result<()> {
+    let (columns, rows) = terminal::size()?;
+    let rows = rows as usize;
+    // Keep the last row for the status bar
+    let visible = rows.saturating_sub(1);
+
+    let mut lines = vec![title_line(app, &options.style), String::new()];
+    match app.view {
+        View::Overview => {
+            let mut body = Vec::new();
+            let options = ViewOptions {
+                detailed: app.detailed,
+                ..options.clone()
+            };
+            render_sections(&mut body, snapshot, &options, app.trends.as_ref())?;
+            let body = String::from_utf8_lossy(&body);
+            lines.extend(body.lines().map(str::to_string));
+        }
+        View::Processes => {
+            // Title, blank line and the table header take three rows
+            app.table.set_page_size(visible.saturating_sub(3));
+            lines.extend(app.table.lines(columns as usize, &options.style));
+        }
+        View::Detail(pid) => match detail.filter(|detail| detail.pid == pid) {
+            Some(detail) => {
+                let mut body = Vec::new();
+                formatter::print_process_detail(&mut body, detail, &options.style)?;
+                let body = String::from_utf8_lossy(&body);
+                // Long command lines would wrap and push the status bar away
+                lines.extend(
+                    body.lines()
+                        .map(|line| line.chars().take(columns as usize).collect()),
+                );
+            }
+            None => lines.push(format!("Process {} has exited", pid)),
+        },
+    }
+
+    write_screen(out, &lines, &status_line(app, &options.style))
+}
+
+/// Overwrite the screen with lines, keeping the last row for the status bar
+///
+/// Every line is overwritten and cleared to its end instead of clearing the
+/// whole screen first, which avoids flicker.
+fn write_screen<W: Write>(out: &mut W, lines: &[String], status: &str) -> io::Result<()> {
+    let rows = terminal::size()?.1 as usize;
+    let visible = rows.saturating_sub(1);
+
+    for (row, line) in lines.iter().take(visible).enumerate() {
+        queue!(out, MoveTo(0, row as u16))?;
+        write!(out, "{}", line)?;
+        queue!(out, Clear(ClearType::UntilNewLine))?;
+    }
+
+    let used = lines.len().min(visible);
+    if used < visible {
+        queue!(
+            out,
+            MoveTo(0, used as u16),
+            Clear(ClearType::FromCursorDown)
+        )?;
+    }
+
+    queue!(out, MoveTo(0, rows.saturating_sub(1) as u16))?;
+    write!(out, "{}", status)?;
+    queue!(out, Clear(ClearType::UntilNewLine))?;
+
+    out.flush()
+}
+
+/// Title shown on the first row
+fn title_line(app: &App, style: &Style) -> String {
+    let state = if app.paused {
+        paint("PAUSED", style.theme.warning).bold().to_string()
+    } else {
+        format!("every {}s", app.interval)
+    };
+
+    format!(
+        "{}  {}",
+        style.heading(&format!("System Monitor v{} (Interactive)", VERSION)),
+        state
+    )
+}
+
+/// Prompt, last message or key help shown on the last row
+fn status_line(app: &App, style: &Style) -> String {
+    if let Some(prompt) = &app.prompt {
+        let question = match prompt {
+            Prompt::Confirm { pid, name, signal } => {
+                format!("Send {} to {} (PID {})? [y/N]", signal, name, pid)
+            }
+            Prompt::Search { input } => format!(
+                "Filter (e.g. name~nginx && cpu>20, empty clears): {}_  [enter] ok  [esc] cancel",
+                input
+            ),
+            Prompt::SignalInput { pid, name, input } => format!(
+                "Signal for {} (PID {}), name or number: {}_  [enter] ok  [esc] cancel",
+                name, pid, input
+            ),
+        };
+        return paint(&question, style.theme.warning).bold().to_string();
+    }
+
+    if let Some(message) = &app.message {
+        return paint(message, style.theme.warning).to_string();
+    }
+
+    let pause = if app.paused { "resume" } else { "pause" };
+
+    let help = match app.view {
+        View::Overview => {
+            let view = if app.detailed { "compact" } else { "detailed" };
+            format!(
+                "[p] {}  [+/-] interval ({}s)  [d] {}  [tab] processes  [/] search  [q] quit",
+                pause, app.interval, view
+            )
+        }
+        View::Detail(_) => format!(
+            "[esc/i] back to processes  [p] {}  [tab] overview  [q] quit",
+            pause
+        ),
+        View::Processes => format!(
+            "{}{}  [up/down/pgup/pgdn] move  [/] filter  [i] details  [1-4] sort  [v] {}  [t]erm [K]ill [s]top [c]ont  [x] signal  [p] {}  [tab] overview  [q] quit",
+            app.table.position(),
+            match app.table.filter_text() {
+                "" => String::new(),
+                text => format!(" (filter: {})", text),
+            },
+            if app.table.is_tree() {
+                "list  [left/right] fold"
+            } else {
+                "tree"
+            },
+            pause
+        ),
+    };
+
+    style.muted(&help).to_string()
+}
@@ -0,0 +1,326 @@
+// Replay mode - Recorded sessions in the terminal view
+// Plays the snapshots of a session file back at any speed, with seeking and
+// pausing; never touches the live system
+
+use super::app::Action;
+use super::{write_screen, TerminalGuard, POLL_TIMEOUT, VERSION};
+use crate::config::Settings;
+use colored::*;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use system_monitor::display::formatter::{format_duration, format_utc};
+use system_monitor::display::sparkline::Trends;
+use system_monitor::display::style::{paint, Style};
+use system_monitor::output::terminal::{render_sections, ViewOptions};
+use system_monitor::Snapshot;
+
+/// Recording time skipped by the left and right arrows
+const SEEK_STEP_MS: u64 = 10_000;
+
+/// Slowest and fastest playback speed reachable with + and -
+pub const MIN_SPEED: f64 = 1.0 / 16.0;
+pub const MAX_SPEED: f64 = 256.0;
+
+/// Playback position and controls of a recorded session
+#[derive(Debug)]
+struct Player {
+    frames: Vec<Snapshot>,
+    index: usize,
+    speed: f64,
+    paused: bool,
+}
+
+impl Player {
+    /// Start playing from the first snapshot (`frames` must not be empty)
+    fn new(frames: Vec<Snapshot>, speed: f64) -> Self {
+        Player {
+            frames,
+            index: 0,
+            speed,
+            paused: false,
+        }
+    }
+
+    fn current(&self) -> &Snapshot {
+        &self.frames[self.index]
+    }
+
+    fn at_end(&self) -> bool {
+        self.index + 1 >= self.frames.len()
+    }
+
+    /// Real time until the next snapshot at the current speed
+    fn delay(&self) -> Option<Duration> {
+        let next = self.frames.get(self.index + 1)?;
+        let gap_ms = next
+            .timestamp_ms
+            .saturating_sub(self.current().timestamp_ms);
+        Some(Duration::from_secs_f64(gap_ms as f64 / 1000.0 / self.speed))
+    }
+
+    /// Move to the next snapshot; pauses at the end
+    fn advance(&mut self) {
+        if self.at_end() {
+            self.paused = true;
+        } else {
+            self.index += 1;
+        }
+    }
+
+    /// Jump to the last snapshot taken at or before a time
+    fn seek_to(&mut self, timestamp_ms: u64) {
+        self.index = self
+            .frames
+            .partition_point(|frame| frame.timestamp_ms <= timestamp_ms)
+            .saturating_sub(1);
+    }
+
+    /// Jump forward or back in recording time (at least one snapshot)
+    fn seek(&mut self, forward: bool) {
+        let now = self.current().timestamp_ms;
+        let previous = self.index;
+        if forward {
+            self.seek_to(now.saturating_add(SEEK_STEP_MS));
+            self.index = self.index.max(previous + 1).min(self.frames.len() - 1);
+        } else {
+            self.seek_to(now.saturating_sub(SEEK_STEP_MS));
+            self.index = self.index.min(previous.saturating_sub(1));
+        }
+    }
+
+    /// Sparklines of the snapshots leading up to the current one
+    fn trends(&self, options: &ViewOptions) -> Option<Trends> {
+        let mut trends = options.trends()?;
+        let start = (self.index + 1).saturating_sub(options.sparkline_length);
+        for frame in &self.frames[start..=self.index] {
+            trends.record(frame);
+        }
+        Some(trends)
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Action {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Action::Quit;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Action::Quit,
+            KeyCode::Char(' ') | KeyCode::Char('p') => {
+                // Resuming at the end starts over
+                if self.paused && self.at_end() {
+                    self.index = 0;
+                }
+                self.paused = !self.paused;
+            }
+            KeyCode::Right => self.seek(true),
+            KeyCode::Left => self.seek(false),
+            KeyCode::Char('.') => self.index = (self.index + 1).min(self.frames.len() - 1),
+            KeyCode::Char(',') => self.index = self.index.saturating_sub(1),
+            KeyCode::Home => self.index = 0,
+            KeyCode::End => self.index = self.frames.len() - 1,
+            KeyCode::Char('+') | KeyCode::Char('=') => {
+                self.speed = (self.speed * 2.0).min(MAX_SPEED)
+            }
+            KeyCode::Char('-') => self.speed = (self.speed / 2.0).max(MIN_SPEED),
+            _ => return Action::None,
+        }
+        Action::Redraw
+    }
+}
+
+/// Play a recorded session until the user quits
+///
+/// # Arguments
+/// * `frames` - Recorded snapshots in order (at least one)
+/// * `speed` - Initial playback speed (2.0 plays twice as fast)
+/// * `settings` - Sections, style and sparklines of the overview
+/// * `running` - Cleared by the Ctrl+C handler
+pub fn run(
+    frames: Vec<Snapshot>,
+    speed: f64,
+    settings: &Settings,
+    running: &AtomicBool,
+) -> io::Result<()> {
+    let _guard = TerminalGuard::enter()?;
+    let mut stdout = io::stdout();
+
+    let options = settings.view_options();
+    let mut player = Player::new(frames, speed);
+    let mut next_at = Instant::now();
+    let mut dirty = true;
+
+    while running.load(Ordering::SeqCst) {
+        let now = Instant::now();
+        if dirty {
+            draw(&mut stdout, &player, &options)?;
+            next_at = now + player.delay().unwrap_or_default();
+            dirty = false;
+        }
+
+        let timeout = if player.paused {
+            POLL_TIMEOUT
+        } else if now >= next_at {
+            player.advance();
+            dirty = true;
+            continue;
+        } else {
+            (next_at - now).min(POLL_TIMEOUT)
+        };
+
+        if !event::poll(timeout)? {
+            continue;
+        }
+
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => match player.handle_key(key) {
+                Action::Quit => break,
+                Action::None => {}
+                _ => dirty = true,
+            },
+            Event::Resize(_, _) => dirty = true,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Draw the current snapshot in place
+fn draw<W: Write>(out: &mut W, player: &Player, options: &ViewOptions) -> io::Result<()> {
+    let snapshot = player.current();
+    let trends = player.trends(options);
+
+    let mut lines = vec![title_line(player, &options.style), String::new()];
+    let mut body = Vec::new();
+    render_sections(&mut body, snapshot, options, trends.as_ref())?;
+    lines.extend(String::from_utf8_lossy(&body).lines().map(str::to_string));
+
+    write_screen(out, &lines, &status_line(player, &options.style))
+}
+
+/// Title with the position in the recording, its time and the speed
+fn title_line(player: &Player, style: &Style) -> String {
+    let start_ms = player.frames[0].timestamp_ms;
+    let elapsed = player.current().timestamp_ms.saturating_sub(start_ms) / 1000;
+    let total = player.frames[player.frames.len() - 1]
+        .timestamp_ms
+        .saturating_sub(start_ms)
+        / 1000;
+
+    let state = if player.paused {
+        paint("PAUSED", style.theme.warning).bold().to_string()
+    } else {
+        format!("{}x", player.speed)
+    };
+
+    format!(
+        "{}  {}/{}  {} / {}  {}  {}",
+        style.heading(&format!("System Monitor v{} (Replay)", VERSION)),
+        player.index + 1,
+        player.frames.len(),
+        format_duration(elapsed),
+        format_duration(total),
+        format_utc(player.current().timestamp_ms / 1000),
+        state
+    )
+}
+
+/// Key help shown on the last row
+fn status_line(player: &Player, style: &Style) -> String {
+    let pause = if !player.paused {
+        "pause"
+    } else if player.at_end() {
+        "restart"
+    } else {
+        "resume"
+    };
+
+    style
+        .muted(&format!(
+            "[space] {}  [left/right] seek 10s  [,/.] step  [home/end] start/end  [+/-] speed ({}x)  [q] quit",
+            pause, player.speed
+        ))
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use system_monitor::output::terminal::ViewOptions;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    /// Snapshots taken every `step_secs`, with the index as CPU usage
+    fn player(count: usize, step_secs: u64) -> Player {
+        let mut monitor = Default::default();
+        let base = Snapshot::collect(&mut monitor, false);
+        let frames = (0..count)
+            .map(|i| {
+                let mut frame = base.clone();
+                frame.timestamp_ms = 1_700_000_000_000 + i as u64 * step_secs * 1000;
+                frame.cpu.global_usage = i as f32;
+                frame
+            })
+            .collect();
+        Player::new(frames, 4.0)
+    }
+
+    #[test]
+    fn test_playback_timing() {
+        let mut player = player(3, 2);
+        // Two seconds of recording at 4x
+        assert_eq!(player.delay(), Some(Duration::from_millis(500)));
+
+        player.advance();
+        player.advance();
+        assert!(player.at_end() && !player.paused);
+        assert_eq!(player.delay(), None);
+        player.advance();
+        assert!(player.paused);
+
+        // Resuming at the end starts over
+        assert_eq!(player.handle_key(key(KeyCode::Char(' '))), Action::Redraw);
+        assert_eq!(player.index, 0);
+        assert!(!player.paused);
+
+        player.handle_key(key(KeyCode::Char('+')));
+        assert_eq!(player.delay(), Some(Duration::from_millis(250)));
+    }
+
+    #[test]
+    fn test_seeking() {
+        let mut player = player(30, 1);
+        player.handle_key(key(KeyCode::Right));
+        assert_eq!(player.index, 10);
+        player.handle_key(key(KeyCode::Char(',')));
+        assert_eq!(player.index, 9);
+        player.handle_key(key(KeyCode::Left));
+        assert_eq!(player.index, 0);
+        player.handle_key(key(KeyCode::End));
+        player.handle_key(key(KeyCode::Right));
+        assert_eq!(player.index, 29);
+
+        // Gaps longer than the seek step still move one snapshot
+        let mut sparse = self::player(3, 60);
+        sparse.handle_key(key(KeyCode::Right));
+        assert_eq!(sparse.index, 1);
+        sparse.handle_key(key(KeyCode::Left));
+        assert_eq!(sparse.index, 0);
+
+        // Sparklines show the snapshots up to the current one
+        let options = ViewOptions {
+            sparkline_length: 5,
+            ..ViewOptions::new(false)
+        };
+        player.index = 12;
+        let trends = player.trends(&options).unwrap();
+        assert_eq!(
+            trends.cpu.values().collect::<Vec<_>>(),
+            [8.0, 9.0, 10.0, 11.0, 12.0]
+        );
+    }
+}
@@ -0,0 +1,556 @@
+// Process table state
+// Sorting, scrolling and a selection that follows its PID across refreshes
+
+use colored::*;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use system_monitor::display::formatter::{format_memory_mb, truncate_string};
+use system_monitor::display::style::Style;
+use system_monitor::monitor::filter::Filter;
+use system_monitor::monitor::tree::{self, ProcessNode};
+use system_monitor::ProcessInfo;
+
+/// Width of the name column
+const NAME_WIDTH: usize = 28;
+
+/// Column the table is sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortColumn {
+    Pid,
+    Name,
+    Cpu,
+    Memory,
+}
+
+impl SortColumn {
+    /// Direction used when the column is first selected
+    ///
+    /// Usage columns start with the heaviest processes, the others ascending.
+    fn default_descending(self) -> bool {
+        matches!(self, SortColumn::Cpu | SortColumn::Memory)
+    }
+
+    fn compare(self, a: &ProcessInfo, b: &ProcessInfo) -> Ordering {
+        match self {
+            SortColumn::Pid => a.pid.cmp(&b.pid),
+            SortColumn::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            SortColumn::Cpu => a.cpu_usage.total_cmp(&b.cpu_usage),
+            SortColumn::Memory => a.memory_mb.total_cmp(&b.memory_mb),
+        }
+    }
+}
+
+/// One displayed line of the table
+#[derive(Debug)]
+struct Row {
+    process: ProcessInfo,
+    /// Tree branch drawing put before the name (empty in list mode)
+    prefix: String,
+    /// CPU and memory totals of the subtree, for tree nodes with children
+    totals: Option<(f32, f64)>,
+    /// Whether the children of this row are hidden
+    collapsed: bool,
+}
+
+/// Scrollable, sortable table of every process, as a list or a tree
+#[derive(Debug)]
+pub struct ProcessTable {
+    /// Latest process data
+    processes: Vec<ProcessInfo>,
+    /// Displayed rows, in order
+    rows: Vec<Row>,
+    sort: SortColumn,
+    descending: bool,
+    /// Show the parent/child hierarchy instead of a flat list
+    tree: bool,
+    /// PIDs whose subtree is collapsed in tree mode
+    collapsed: HashSet<u32>,
+    /// Search filter and the text it was parsed from
+    filter: Option<(String, Filter)>,
+    /// Index of the highlighted row
+    selected: usize,
+    /// PID of the highlighted row, used to find it again after a refresh
+    selected_pid: Option<u32>,
+    /// First visible row
+    offset: usize,
+    /// Number of visible rows (set when drawing)
+    page_size: usize,
+}
+
+impl ProcessTable {
+    /// Create an empty table sorted by CPU usage
+    pub fn new() -> Self {
+        ProcessTable {
+            processes: Vec::new(),
+            rows: Vec::new(),
+            sort: SortColumn::Cpu,
+            descending: true,
+            tree: false,
+            collapsed: HashSet::new(),
+            filter: None,
+            selected: 0,
+            selected_pid: None,
+            offset: 0,
+            page_size: 1,
+        }
+    }
+
+    /// Replace the rows with fresh data, keeping the selected PID highlighted
+    ///
+    /// If the selected process exited, the selection stays at the same
+    /// position (clamped to the new length).
+    pub fn set_rows(&mut self, processes: Vec<ProcessInfo>) {
+        self.processes = processes;
+
+        // Forget collapsed processes that exited
+        let pids: HashSet<u32> = self.processes.iter().map(|p| p.pid).collect();
+        self.collapsed.retain(|pid| pids.contains(pid));
+
+        self.rebuild();
+    }
+
+    /// Sort by a column; choosing the current column again reverses the order
+    ///
+    /// In tree mode the siblings under each parent are sorted.
+    pub fn sort_by(&mut self, column: SortColumn) {
+        if self.sort == column {
+            self.descending = !self.descending;
+        } else {
+            self.sort = column;
+            self.descending = column.default_descending();
+        }
+        self.rebuild();
+    }
+
+    /// Only show processes matching a filter expression (empty shows all)
+    pub fn set_filter(&mut self, text: &str) -> Result<(), String> {
+        let text = text.trim();
+        self.filter = if text.is_empty() {
+            None
+        } else {
+            Some((text.to_string(), text.parse()?))
+        };
+        self.rebuild();
+        Ok(())
+    }
+
+    /// Text of the active filter (empty if none)
+    pub fn filter_text(&self) -> &str {
+        self.filter.as_ref().map_or("", |(text, _)| text.as_str())
+    }
+
+    /// Switch between the flat list and the tree
+    pub fn toggle_tree(&mut self) {
+        self.tree = !self.tree;
+        self.rebuild();
+    }
+
+    /// Whether the table shows the process tree
+    pub fn is_tree(&self) -> bool {
+        self.tree
+    }
+
+    /// Hide or show the children of the selected process (tree mode only)
+    pub fn set_collapsed(&mut self, collapsed: bool) {
+        let Some(row) = self.rows.get(self.selected) else {
+            return;
+        };
+        if !self.tree || row.totals.is_none() {
+            return;
+        }
+
+        let pid = row.process.pid;
+        if collapsed {
+            self.collapsed.insert(pid);
+        } else {
+            self.collapsed.remove(&pid);
+        }
+        self.rebuild();
+    }
+
+    /// Collapse the selected subtree, or expand it if already collapsed
+    pub fn toggle_collapsed(&mut self) {
+        let collapsed = self
+            .rows
+            .get(self.selected)
+            .is_some_and(|row| row.collapsed);
+        self.set_collapsed(!collapsed);
+    }
+
+    /// Move the selection by `delta` rows (negative moves up)
+    pub fn move_by(&mut self, delta: isize) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let last = self.rows.len() - 1;
+        let target = (self.selected as isize)
+            .saturating_add(delta)
+            .clamp(0, last as isize);
+        self.select(target as usize);
+    }
+
+    /// Move the selection one page down
+    pub fn page_down(&mut self) {
+        self.move_by(self.page_size as isize);
+    }
+
+    /// Move the selection one page up
+    pub fn page_up(&mut self) {
+        self.move_by(-(self.page_size as isize));
+    }
+
+    /// Select the first row
+    pub fn first(&mut self) {
+        self.select(0);
+    }
+
+    /// Select the last row
+    pub fn last(&mut self) {
+        self.select(self.rows.len().saturating_sub(1));
+    }
+
+    /// Currently highlighted process
+    pub fn selected(&self) -> Option<&ProcessInfo> {
+        self.rows.get(self.selected).map(|row| &row.process)
+    }
+
+    /// Set how many rows fit on screen and scroll so the selection is visible
+    pub fn set_page_size(&mut self, page_size: usize) {
+        self.page_size = page_size.max(1);
+        self.scroll_to_selection();
+    }
+
+    /// Render the header and the visible rows
+    ///
+    /// # Arguments
+    /// * `width` - Terminal width; rows are cut to it and the highlight fills it
+    /// * `style` - Colors of the header
+    pub fn lines(&self, width: usize, style: &Style) -> Vec<String> {
+        let mut lines = Vec::with_capacity(self.page_size + 1);
+
+        let header = if self.tree {
+            format!(
+                "{:>7}  {:>7}  {:>9}  {:>7}  {:>9}  {}",
+                self.column_title(SortColumn::Pid, "PID"),
+                self.column_title(SortColumn::Cpu, "CPU%"),
+                self.column_title(SortColumn::Memory, "MEM"),
+                "TREE%",
+                "TREE MEM",
+                self.column_title(SortColumn::Name, "NAME"),
+            )
+        } else {
+            format!(
+                "{:>7}  {:<name$}  {:>7}  {:>9}",
+                self.column_title(SortColumn::Pid, "PID"),
+                self.column_title(SortColumn::Name, "NAME"),
+                self.column_title(SortColumn::Cpu, "CPU%"),
+                self.column_title(SortColumn::Memory, "MEM"),
+                name = NAME_WIDTH
+            )
+        };
+        lines.push(style.heading(&fit(&header, width)).to_string());
+
+        let visible = self.rows.iter().enumerate().skip(self.offset);
+        for (index, row) in visible.take(self.page_size) {
+            let text = fit(&self.format_row(row), width);
+
+            if index == self.selected {
+                let padded = format!("{:<width$}", text, width = width);
+                lines.push(padded.reversed().to_string());
+            } else {
+                lines.push(text);
+            }
+        }
+
+        lines
+    }
+
+    /// Position summary for the status bar (e.g. "12/345 cargo")
+    pub fn position(&self) -> String {
+        match self.selected() {
+            Some(process) => format!(
+                "{}/{} {}",
+                self.selected + 1,
+                self.rows.len(),
+                truncate_string(&process.name, 16)
+            ),
+            None => "0/0".to_string(),
+        }
+    }
+
+    fn format_row(&self, row: &Row) -> String {
+        let process = &row.process;
+
+        if !self.tree {
+            return format!(
+                "{:>7}  {:<name$}  {:>6.1}%  {:>9}",
+                process.pid,
+                truncate_string(&process.name, NAME_WIDTH),
+                process.cpu_usage,
+                format_memory_mb(process.memory_mb),
+                name = NAME_WIDTH
+            );
+        }
+
+        let (total_cpu, total_mem) = match row.totals {
+            Some((cpu, mem)) => (format!("{:.1}%", cpu), format_memory_mb(mem)),
+            None => (String::new(), String::new()),
+        };
+        let marker = if row.collapsed { " [+]" } else { "" };
+
+        format!(
+            "{:>7}  {:>6.1}%  {:>9}  {:>7}  {:>9}  {}{}{}",
+            process.pid,
+            process.cpu_usage,
+            format_memory_mb(process.memory_mb),
+            total_cpu,
+            total_mem,
+            row.prefix,
+            process.name,
+            marker
+        )
+    }
+
+    fn column_title(&self, column: SortColumn, title: &str) -> String {
+        if self.sort != column {
+            return title.to_string();
+        }
+        let arrow = if self.descending { "▼" } else { "▲" };
+        format!("{}{}", title, arrow)
+    }
+
+    /// Compare two processes by the current sort settings
+    fn compare(&self, a: &ProcessInfo, b: &ProcessInfo) -> Ordering {
+        let order = self.sort.compare(a, b);
+        let order = if self.descending {
+            order.reverse()
+        } else {
+            order
+        };
+        // Stable order for equal values so rows don't jump around
+        order.then(a.pid.cmp(&b.pid))
+    }
+
+    /// Recompute the displayed rows from the process data
+    fn rebuild(&mut self) {
+        let processes = match &self.filter {
+            Some((_, filter)) => filter.apply(self.processes.clone()),
+            None => self.processes.clone(),
+        };
+
+        if self.tree {
+            let mut forest = tree::build_tree(&processes);
+            tree::sort_tree(&mut forest, &|a: &ProcessNode, b: &ProcessNode| {
+                self.compare(&a.process, &b.process)
+            });
+
+            self.rows = tree::flatten(&forest, &self.collapsed)
+                .into_iter()
+                .map(|row| Row {
+                    process: row.node.process.clone(),
+                    prefix: row.prefix,
+                    totals: (!row.node.children.is_empty())
+                        .then_some((row.node.total_cpu_usage, row.node.total_memory_mb)),
+                    collapsed: row.collapsed,
+                })
+                .collect();
+        } else {
+            let mut processes = processes;
+            processes.sort_by(|a, b| self.compare(a, b));
+
+            self.rows = processes
+                .into_iter()
+                .map(|process| Row {
+                    process,
+                    prefix: String::new(),
+                    totals: None,
+                    collapsed: false,
+                })
+                .collect();
+        }
+
+        self.reselect();
+    }
+
+    /// Find the selected PID again after the rows changed
+    fn reselect(&mut self) {
+        let found = self
+            .selected_pid
+            .and_then(|pid| self.rows.iter().position(|row| row.process.pid == pid));
+
+        let index = found.unwrap_or(self.selected);
+        self.select(index.min(self.rows.len().saturating_sub(1)));
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index;
+        self.selected_pid = self.rows.get(index).map(|row| row.process.pid);
+        self.scroll_to_selection();
+    }
+
+    fn scroll_to_selection(&mut self) {
+        if self.selected < self.offset {
+            self.offset = self.selected;
+        } else if self.selected >= self.offset + self.page_size {
+            self.offset = self.selected + 1 - self.page_size;
+        }
+
+        // Don't leave empty space at the bottom after rows disappear
+        let max_offset = self.rows.len().saturating_sub(self.page_size);
+        self.offset = self.offset.min(max_offset);
+    }
+}
+
+impl Default for ProcessTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Cut a line to the terminal width (deep trees would wrap otherwise)
+fn fit(line: &str, width: usize) -> String {
+    line.chars().take(width).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, name: &str, cpu: f32, memory_mb: f64) -> ProcessInfo {
+        ProcessInfo {
+            name: name.to_string(),
+            pid,
+            parent_pid: None,
+            user: None,
+            cpu_usage: cpu,
+            memory_mb,
+        }
+    }
+
+    fn sample() -> Vec<ProcessInfo> {
+        vec![
+            process(10, "bash", 1.0, 10.0),
+            process(20, "cargo", 50.0, 300.0),
+            process(30, "Xorg", 5.0, 900.0),
+        ]
+    }
+
+    fn pids(table: &ProcessTable) -> Vec<u32> {
+        table.rows.iter().map(|row| row.process.pid).collect()
+    }
+
+    #[test]
+    fn test_sort_columns() {
+        let mut table = ProcessTable::new();
+        table.set_rows(sample());
+        assert_eq!(pids(&table), vec![20, 30, 10]);
+
+        table.sort_by(SortColumn::Memory);
+        assert_eq!(pids(&table), vec![30, 20, 10]);
+
+        table.sort_by(SortColumn::Name);
+        assert_eq!(pids(&table), vec![10, 20, 30]);
+
+        // Same column again reverses
+        table.sort_by(SortColumn::Name);
+        assert_eq!(pids(&table), vec![30, 20, 10]);
+    }
+
+    #[test]
+    fn test_selection_follows_pid() {
+        let mut table = ProcessTable::new();
+        table.set_rows(sample());
+        table.move_by(1);
+        assert_eq!(table.selected().unwrap().pid, 30);
+
+        // Xorg becomes the busiest process and moves to the top
+        let mut rows = sample();
+        rows[2].cpu_usage = 90.0;
+        table.set_rows(rows);
+        assert_eq!(table.selected, 0);
+        assert_eq!(table.selected().unwrap().pid, 30);
+    }
+
+    #[test]
+    fn test_selection_when_process_exits() {
+        let mut table = ProcessTable::new();
+        table.set_rows(sample());
+        table.last();
+        assert_eq!(table.selected().unwrap().pid, 10);
+
+        let rows = sample().into_iter().filter(|p| p.pid != 10).collect();
+        table.set_rows(rows);
+        assert_eq!(table.selected().unwrap().pid, 30);
+    }
+
+    #[test]
+    fn test_paging_scrolls() {
+        let mut table = ProcessTable::new();
+        let rows = (0..1000)
+            .map(|pid| process(pid, "worker", 0.0, 1.0))
+            .collect();
+        table.set_rows(rows);
+        table.sort_by(SortColumn::Pid);
+        table.set_page_size(20);
+
+        table.page_down();
+        assert_eq!(table.selected, 20);
+        assert_eq!(table.offset, 1);
+
+        table.last();
+        assert_eq!(table.selected, 999);
+        assert_eq!(table.offset, 980);
+        assert_eq!(table.lines(80, &Style::default()).len(), 21);
+
+        table.page_up();
+        table.first();
+        assert_eq!(table.offset, 0);
+        assert_eq!(table.position(), "1/1000 worker");
+    }
+
+    #[test]
+    fn test_tree_mode_and_collapse() {
+        let mut table = ProcessTable::new();
+        let mut rows = sample();
+        rows[1].parent_pid = Some(10); // cargo started from bash
+        table.set_rows(rows);
+        table.set_page_size(10);
+        table.toggle_tree();
+        assert!(table.is_tree());
+        assert_eq!(pids(&table), vec![30, 10, 20]);
+        assert_eq!(table.rows[2].prefix, "└─ ");
+        assert_eq!(table.rows[1].totals, Some((51.0, 310.0)));
+
+        // The selection followed cargo into the tree
+        assert_eq!(table.selected().unwrap().pid, 20);
+
+        // Collapse bash, then its child is hidden
+        table.move_by(-1);
+        table.toggle_collapsed();
+        assert_eq!(pids(&table), vec![30, 10]);
+        assert!(table.lines(120, &Style::default())[2].contains("bash [+]"));
+
+        // Leaves can't be collapsed
+        table.first();
+        table.toggle_collapsed();
+        assert_eq!(pids(&table), vec![30, 10]);
+
+        table.move_by(1);
+        table.toggle_collapsed();
+        assert_eq!(pids(&table), vec![30, 10, 20]);
+    }
+
+    #[test]
+    fn test_filter_rows() {
+        let mut table = ProcessTable::new();
+        table.set_rows(sample());
+        table.set_filter("cpu>2 && mem<500MB").unwrap();
+        assert_eq!(pids(&table), vec![20]);
+
+        assert!(table.set_filter("cpu>").is_err());
+        assert_eq!(table.filter_text(), "cpu>2 && mem<500MB");
+
+        table.set_filter("").unwrap();
+        assert_eq!(pids(&table).len(), 3);
+    }
+}
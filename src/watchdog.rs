@@ -0,0 +1,207 @@
+// Watchdog module - Alert actions
+// Checks alert rules on every tick, runs the hook command and writes the log
+
+use crate::config::AlertSettings;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use system_monitor::alert::{AlertEngine, AlertEvent};
+use system_monitor::display::formatter::format_utc;
+use system_monitor::Snapshot;
+
+/// Evaluates alert rules and runs the configured actions
+pub struct Watchdog {
+    engine: AlertEngine,
+    command: Option<String>,
+    log: Option<File>,
+    host: String,
+}
+
+impl Watchdog {
+    /// Create a watchdog, or None when there are no rules
+    ///
+    /// Fails if the alert log can't be opened.
+    pub fn new(settings: &AlertSettings) -> io::Result<Option<Self>> {
+        if settings.rules.is_empty() {
+            return Ok(None);
+        }
+
+        let log = match &settings.log {
+            Some(path) => Some(OpenOptions::new().create(true).append(true).open(path)?),
+            None => None,
+        };
+
+        Ok(Some(Watchdog {
+            engine: AlertEngine::new(settings.rules.clone()),
+            command: settings.command.clone(),
+            log,
+            host: sysinfo::System::host_name().unwrap_or_default(),
+        }))
+    }
+
+    /// Whether a command or log file handles the alerts
+    pub fn has_actions(&self) -> bool {
+        self.command.is_some() || self.log.is_some()
+    }
+
+    /// Evaluate the rules against a snapshot and act on every change
+    ///
+    /// Returns the alerts that fired or resolved. Failing actions are
+    /// reported on stderr and don't stop monitoring.
+    pub fn check(&mut self, snapshot: &Snapshot) -> Vec<AlertEvent> {
+        let events = self.engine.evaluate(snapshot);
+
+        for event in &events {
+            if let Some(log) = &mut self.log {
+                if let Err(e) = writeln!(log, "{}", describe(event)).and_then(|()| log.flush()) {
+                    eprintln!("Error writing alert log: {}", e);
+                }
+            }
+            if let Some(command) = &self.command {
+                if let Err(e) = spawn_hook(command, event, &self.host) {
+                    eprintln!("Error running alert command: {}", e);
+                }
+            }
+        }
+
+        events
+    }
+}
+
+/// One-line description, e.g.
+/// "2026-01-02 03:04:05 UTC FIRING cpu.global > 90 for 30s (value 93.5)"
+pub fn describe(event: &AlertEvent) -> String {
+    format!(
+        "{} {} {} (value {:.1})",
+        format_utc(event.timestamp_ms / 1000),
+        event.state.to_string().to_uppercase(),
+        event.rule,
+        event.value
+    )
+}
+
+/// Start the hook command without waiting for it
+fn spawn_hook(command: &str, event: &AlertEvent, host: &str) -> io::Result<()> {
+    #[cfg(unix)]
+    let mut shell = {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    };
+    #[cfg(windows)]
+    let mut shell = {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    };
+
+    let mut child = shell
+        .env("SYSMON_ALERT_STATE", event.state.to_string())
+        .env("SYSMON_ALERT_RULE", &event.rule)
+        .env("SYSMON_ALERT_METRIC", &event.metric)
+        .env("SYSMON_ALERT_VALUE", format!("{:.2}", event.value))
+        .env("SYSMON_ALERT_THRESHOLD", event.threshold.to_string())
+        .env(
+            "SYSMON_ALERT_TIMESTAMP",
+            (event.timestamp_ms / 1000).to_string(),
+        )
+        .env("SYSMON_ALERT_HOST", host)
+        .stdin(Stdio::null())
+        .spawn()?;
+
+    // Reap the process so slow hooks never block the next tick
+    let rule = event.rule.clone();
+    thread::spawn(move || match child.wait() {
+        Ok(status) if !status.success() => {
+            eprintln!("Alert command for '{}' exited with {}", rule, status)
+        }
+        Err(e) => eprintln!("Error waiting for alert command: {}", e),
+        Ok(_) => {}
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use system_monitor::alert::AlertState;
+    use system_monitor::monitor::snapshot::SCHEMA_VERSION;
+    use system_monitor::{CpuInfo, MemoryInfo, NetworkInfo, Snapshot};
+
+    /// Snapshot with only a global CPU usage
+    fn snapshot(cpu: f32) -> Snapshot {
+        Snapshot {
+            schema_version: SCHEMA_VERSION,
+            timestamp_ms: 1_700_000_000_000,
+            uptime_secs: 3600,
+            cpu: CpuInfo {
+                global_usage: cpu,
+                cores: vec![],
+            },
+            memory: MemoryInfo {
+                total_gb: 16.0,
+                used_gb: 8.0,
+                percentage: 50.0,
+            },
+            disks: vec![],
+            network: NetworkInfo::from_interfaces(vec![]),
+            top_processes_cpu: vec![],
+            top_processes_memory: vec![],
+            process_tree: vec![],
+        }
+    }
+
+    #[test]
+    fn test_describe_event() {
+        let event = AlertEvent {
+            rule: "mem > 95".to_string(),
+            metric: "mem".to_string(),
+            state: AlertState::Firing,
+            value: 97.25,
+            threshold: 95.0,
+            timestamp_ms: 1_700_000_000_000,
+        };
+        assert_eq!(
+            describe(&event),
+            "2023-11-14 22:13:20 UTC FIRING mem > 95 (value 97.2)"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hook_and_log() {
+        let dir = std::env::temp_dir().join(format!("sysmon-watchdog-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let log = dir.join("alerts.log");
+        let hook_out = dir.join("hook.out");
+
+        let settings = AlertSettings {
+            rules: vec!["cpu > 10".parse().unwrap()],
+            command: Some(format!(
+                "echo \"$SYSMON_ALERT_STATE $SYSMON_ALERT_METRIC $SYSMON_ALERT_VALUE\" > {}",
+                hook_out.display()
+            )),
+            log: Some(log.clone()),
+        };
+        let mut watchdog = Watchdog::new(&settings).unwrap().unwrap();
+
+        assert_eq!(watchdog.check(&snapshot(50.0)).len(), 1);
+
+        // The hook runs in the background
+        for _ in 0..50 {
+            if hook_out.exists() {
+                break;
+            }
+            thread::sleep(std::time::Duration::from_millis(20));
+        }
+        thread::sleep(std::time::Duration::from_millis(50));
+        let hook = std::fs::read_to_string(&hook_out).unwrap();
+        assert_eq!(hook.trim(), "firing cpu.global 50.00");
+        assert!(std::fs::read_to_string(&log)
+            .unwrap()
+            .contains("FIRING cpu > 10"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}